@@ -0,0 +1,60 @@
+use std::{env, fs, path::Path};
+
+/// Version of the ejs solver used when `assets/ejs/VERSION` is missing.
+const DEFAULT_EJS_VERSION: &str = "0.3.1";
+const EJS_BUNDLES: [&str; 2] = ["yt.solver.lib.min.js", "yt.solver.core.min.js"];
+
+fn main() {
+    let ejs_dir = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("assets/ejs");
+    println!("cargo:rerun-if-changed={}", ejs_dir.display());
+
+    let version = fs::read_to_string(ejs_dir.join("VERSION"))
+        .map(|v| v.trim().to_string())
+        .unwrap_or(DEFAULT_EJS_VERSION.into());
+    let sums = fs::read_to_string(ejs_dir.join("SHA256SUMS")).unwrap_or_default();
+
+    // `sha256sum` output, a hex digest and a file name on each line.
+    let digest_of = |bundle: &str| {
+        sums.lines()
+            .filter_map(|line| line.split_once(char::is_whitespace))
+            .find(|(_, name)| name.trim().trim_start_matches('*') == bundle)
+            .map(|(digest, _)| digest.to_lowercase())
+    };
+    let digests: Vec<Option<String>> = EJS_BUNDLES.iter().map(|b| digest_of(b)).collect();
+
+    if digests.iter().any(Option::is_none) {
+        println!(
+            "cargo:warning=`assets/ejs/SHA256SUMS` doesn't pin every ejs bundle, so downloaded bundles will be refused. Run `scripts/update-ejs.sh` to pin them."
+        );
+    }
+
+    // The embedded bundles have to be vendored and pinned, so the feature never needs the network.
+    let embed = env::var_os("CARGO_FEATURE_EMBEDDED_SOLVER").is_some();
+    let embedded: Vec<String> = EJS_BUNDLES
+        .iter()
+        .zip(&digests)
+        .map(|(bundle, digest)| {
+            let path = ejs_dir.join(bundle);
+            if !embed {
+                return "None".into();
+            }
+            if digest.is_none() || !path.is_file() {
+                panic!(
+                    "The `embedded-solver` feature needs `assets/ejs/{bundle}` and its digest in `assets/ejs/SHA256SUMS`, run `scripts/update-ejs.sh` to vendor them."
+                );
+            }
+
+            format!("Some(include_str!({:?}))", path.display().to_string())
+        })
+        .collect();
+
+    let pins = format!(
+        "pub const EJS_VERSION: &str = {:?};\npub const EJS_LIB_SHA256: Option<&str> = {:?};\npub const EJS_CORE_SHA256: Option<&str> = {:?};\npub const EJS_LIB_EMBEDDED: Option<&str> = {};\npub const EJS_CORE_EMBEDDED: Option<&str> = {};\n",
+        version, digests[0], digests[1], embedded[0], embedded[1],
+    );
+    fs::write(
+        Path::new(&env::var("OUT_DIR").unwrap()).join("ejs_pins.rs"),
+        pins,
+    )
+    .unwrap();
+}
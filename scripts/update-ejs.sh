@@ -0,0 +1,17 @@
+#!/usr/bin/env sh
+# Vendors the ejs solver bundles of a release into assets/ejs and pins their SHA-256 digests.
+# Usage: scripts/update-ejs.sh [version]
+set -eu
+
+version="${1:-0.3.1}"
+dir="$(dirname "$0")/../assets/ejs"
+
+mkdir -p "$dir"
+cd "$dir"
+
+for bundle in yt.solver.lib.min.js yt.solver.core.min.js; do
+  curl -fsSL -o "$bundle" "https://github.com/yt-dlp/ejs/releases/download/$version/$bundle"
+done
+
+sha256sum yt.solver.lib.min.js yt.solver.core.min.js > SHA256SUMS
+echo "$version" > VERSION
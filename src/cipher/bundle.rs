@@ -0,0 +1,101 @@
+use anyhow::{Result, bail};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    cache::{CacheAccess, CacheStore},
+    yt_interface::YtSolverSource,
+};
+
+// Generated by `build.rs` from `assets/ejs`, see `scripts/update-ejs.sh`.
+include!(concat!(env!("OUT_DIR"), "/ejs_pins.rs"));
+
+pub const EJS_LIB_BUNDLE: &str = "yt.solver.lib.min.js";
+pub const EJS_CORE_BUNDLE: &str = "yt.solver.core.min.js";
+
+pub fn release_url(bundle: &str) -> String {
+    format!(
+        "https://github.com/yt-dlp/ejs/releases/download/{}/{}",
+        EJS_VERSION, bundle
+    )
+}
+
+/// The bundle embedded with the `embedded-solver` feature, `None` without it.
+pub fn embedded_bundle(bundle: &str) -> Option<&'static str> {
+    match bundle {
+        EJS_LIB_BUNDLE => EJS_LIB_EMBEDDED,
+        EJS_CORE_BUNDLE => EJS_CORE_EMBEDDED,
+        _ => None,
+    }
+}
+
+/// Checks a fetched bundle against the digest pinned for it, refusing bundles without one.
+pub fn verify_bundle(bundle: &str, code: &str) -> Result<()> {
+    let pinned = match bundle {
+        EJS_LIB_BUNDLE => EJS_LIB_SHA256,
+        EJS_CORE_BUNDLE => EJS_CORE_SHA256,
+        _ => None,
+    };
+
+    let Some(pinned) = pinned else {
+        bail!(
+            "No SHA-256 digest is pinned for the ejs bundle `{}` in `assets/ejs/SHA256SUMS`, run `scripts/update-ejs.sh` or load the bundles with `YtSolverSource::Directory`.",
+            bundle
+        )
+    };
+
+    let digest = format!("{:x}", Sha256::digest(code.as_bytes()));
+    if digest != pinned {
+        bail!(
+            "The ejs bundle `{}` doesn't match its pinned SHA-256 digest, expected {} but got {}.",
+            bundle,
+            pinned,
+            digest
+        )
+    }
+
+    Ok(())
+}
+
+/// Loads a solver bundle from `solver_source`, keeping downloaded ones in `code_cache`.
+pub async fn load_bundle(
+    solver_source: &YtSolverSource,
+    code_cache: &CacheStore,
+    bundle: &str,
+) -> Result<String> {
+    let url = match solver_source {
+        YtSolverSource::Pinned => match embedded_bundle(bundle) {
+            Some(code) => {
+                verify_bundle(bundle, code)?;
+                return Ok(code.into());
+            }
+            None => release_url(bundle),
+        },
+        YtSolverSource::Url(base_url) => {
+            format!("{}/{}", base_url.trim_end_matches('/'), bundle)
+        }
+        YtSolverSource::Directory(dir) => return read_local_bundle(dir, bundle).await,
+    };
+
+    // The code cache is shared with the player code, so hits are checked like downloads.
+    if let Some(cached_code) = code_cache.get(&url)? {
+        verify_bundle(bundle, &cached_code)?;
+        return Ok(cached_code);
+    }
+
+    let fetched_code = reqwest::get(&url).await?.error_for_status()?.text().await?;
+    verify_bundle(bundle, &fetched_code)?;
+
+    code_cache.add(url, fetched_code.clone())?;
+
+    Ok(fetched_code)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn read_local_bundle(dir: &str, bundle: &str) -> Result<String> {
+    Ok(tokio::fs::read_to_string(std::path::Path::new(dir).join(bundle)).await?)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub async fn read_local_bundle(_dir: &str, _bundle: &str) -> Result<String> {
+    bail!("Loading the ejs bundles from a local directory isn't supported in WebAssembly.")
+}
@@ -1,13 +1,23 @@
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use anyhow::{Result, bail};
+use url::Url;
 
 use crate::{
     cache::{CacheAccess, CacheStore, PlayerCacheHandle},
     cipher::js::SignatureJsHandle,
-    utils::{parse_query_string, replace_n_sig_query_param},
+    solver::ChallengeSolver,
+    utils::{
+        n_sig_path_segment, parse_query_string, replace_n_sig_path_segment,
+        replace_n_sig_query_param,
+    },
+    yt_interface::{YtSolverSource, YtStream, YtStreamResponse, YtStreamSource},
 };
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SignatureType {
     Nsignature,
     Signature,
@@ -22,21 +32,131 @@ impl SignatureType {
     }
 }
 
+impl SignatureType {
+    fn cache_id(&self, player_url: &str, challenge: String) -> (String, String) {
+        (format!("{}-{}", self.as_str(), player_url), challenge)
+    }
+}
+
+/// The parts of a stream URL that need deciphering.
+struct StreamChallenges {
+    url: String,
+    /// The encrypted signature, with the query parameter it's deciphered into.
+    signature: Option<(String, String)>,
+    nsig: Option<String>,
+    /// Whether `url` is an HLS or DASH manifest URL, which carries `n` in its path.
+    is_manifest: bool,
+}
+
+impl StreamChallenges {
+    fn from_source(source: &YtStreamSource) -> Option<Self> {
+        let (url, signature) = match source {
+            YtStreamSource::URL(url) => (url.clone(), None),
+            YtStreamSource::Signature(signature) => {
+                let sc = parse_query_string(signature).unwrap_or_default();
+                let (Some(url), Some(encrypted_sig)) = (sc.get("url"), sc.get("s")) else {
+                    return None;
+                };
+                let sp = sc.get("sp").cloned().unwrap_or("signature".into());
+
+                (url.clone(), Some((encrypted_sig.clone(), sp)))
+            }
+            YtStreamSource::Hls(variant) => return Self::from_manifest_url(&variant.url),
+            YtStreamSource::Dash(representation) => {
+                return Self::from_manifest_url(&representation.manifest_url);
+            }
+        };
+
+        let nsig = Url::parse(&url)
+            .ok()?
+            .query_pairs()
+            .find(|(key, _)| key == "n")
+            .map(|(_, value)| value.into_owned());
+
+        Some(Self {
+            url,
+            signature,
+            nsig,
+            is_manifest: false,
+        })
+    }
+
+    fn from_manifest_url(manifest_url: &str) -> Option<Self> {
+        Some(Self {
+            url: manifest_url.to_string(),
+            signature: None,
+            nsig: Some(n_sig_path_segment(manifest_url)?),
+            is_manifest: true,
+        })
+    }
+}
+
+/// Replaces the `/n/<nsig>/` path segment of the URLs of an HLS or DASH source with its solution.
+fn replace_manifest_n(
+    source: &YtStreamSource,
+    nsig: &str,
+    deciphered_n: &str,
+) -> Result<YtStreamSource, url::ParseError> {
+    let replace = |url: &mut String| -> Result<(), url::ParseError> {
+        if n_sig_path_segment(url).as_deref() == Some(nsig) {
+            *url = replace_n_sig_path_segment(url, deciphered_n)?;
+        }
+        Ok(())
+    };
+
+    let mut source = source.clone();
+    match &mut source {
+        YtStreamSource::Hls(variant) => replace(&mut variant.url)?,
+        YtStreamSource::Dash(representation) => {
+            replace(&mut representation.manifest_url)?;
+            replace(&mut representation.base_url)?;
+            if let Some(initialization_url) = &mut representation.initialization_url {
+                replace(initialization_url)?;
+            }
+            representation
+                .segment_urls
+                .iter_mut()
+                .try_for_each(replace)?;
+        }
+        YtStreamSource::URL(_) | YtStreamSource::Signature(_) => {}
+    }
+
+    Ok(source)
+}
+
 pub struct SignatureDecipher {
     pub player_cache: Arc<CacheStore<(String, String)>>,
     pub code_cache: Arc<CacheStore>,
+    pub solver_source: YtSolverSource,
+    pub solver: Option<Box<dyn ChallengeSolver>>,
 }
 
 impl SignatureDecipher {
     pub fn new(
         player_cache: Arc<CacheStore<(String, String)>>,
         code_cache: Arc<CacheStore>,
+        solver_source: YtSolverSource,
+        solver: Option<Box<dyn ChallengeSolver>>,
     ) -> Self {
         Self {
             player_cache,
             code_cache,
+            solver_source,
+            solver,
         }
     }
+
+    /// Solves the `/n/<value>/` path segment of an HLS or DASH manifest URL.
+    async fn decipher_n_path(&self, manifest_url: &str, player_url: String) -> Result<String> {
+        let Some(nsig) = n_sig_path_segment(manifest_url) else {
+            return Ok(manifest_url.to_string());
+        };
+        let deciphered_n = self
+            .decrypt_signature(SignatureType::Nsignature, nsig, player_url)
+            .await?;
+
+        Ok(replace_n_sig_path_segment(manifest_url, &deciphered_n)?)
+    }
 }
 
 pub trait SignatureDecipherHandle {
@@ -53,6 +173,18 @@ pub trait SignatureDecipherHandle {
         player_url: String,
     ) -> Result<String>;
     async fn decipher(&self, signature: String, player_url: String) -> Result<String>;
+    /// Solves the `n` parameter of a stream URL, returning the URL unchanged when it has none.
+    async fn decipher_n_param(&self, url: String, player_url: String) -> Result<String>;
+    /// Returns a playable URL for the stream, deciphering its signature and `n` parameter where needed.
+    ///
+    /// HLS variants resolve to their media playlist and DASH representations to their manifest.
+    async fn resolve_stream_url(&self, stream: &YtStream, player_url: String) -> Result<String>;
+    /// Deciphers the signature and `n` parameter of every stream in one JS run,
+    /// turning all `Signature` and `URL` streams into playable `URL` ones.
+    ///
+    /// HLS and DASH streams keep their source, with the `/n/<value>/` path segment of their URLs
+    /// solved in the same run. Streams whose challenges couldn't be solved keep their source.
+    async fn decipher_streams(&self, response: YtStreamResponse) -> Result<YtStreamResponse>;
 }
 
 impl SignatureDecipherHandle for SignatureDecipher {
@@ -66,7 +198,7 @@ impl SignatureDecipherHandle for SignatureDecipher {
 
         if let Some(code) = self.code_cache.get(&player_js_code_key)? {
             let res = self
-                .parse_signature_js(code, example_sig, signature_type)
+                .parse_signature_js(player_js_code_key, code, example_sig, signature_type)
                 .await?;
             return Ok(res);
         }
@@ -82,10 +214,7 @@ impl SignatureDecipherHandle for SignatureDecipher {
         encrypted_signature: String,
         player_url: String,
     ) -> Result<String> {
-        let cache_id = (
-            format!("{}-{}", signature_type.as_str(), player_url),
-            encrypted_signature.clone(),
-        );
+        let cache_id = signature_type.cache_id(&player_url, encrypted_signature.clone());
 
         if let Some(cached_deciphered_value) = self.player_cache.get(&cache_id)? {
             return Ok(cached_deciphered_value);
@@ -115,18 +244,232 @@ impl SignatureDecipherHandle for SignatureDecipher {
             decrypted_signature,
         );
 
-        Ok(
-            match parse_query_string(&url_with_sig)
-                .unwrap_or_default()
-                .get("n")
+        self.decipher_n_param(url_with_sig, player_url).await
+    }
+
+    async fn decipher_n_param(&self, url: String, player_url: String) -> Result<String> {
+        let nsig = Url::parse(&url)?
+            .query_pairs()
+            .find(|(key, _)| key == "n")
+            .map(|(_, value)| value.into_owned());
+
+        Ok(match nsig {
+            Some(nsig) => replace_n_sig_query_param(
+                &url,
+                self.decrypt_signature(SignatureType::Nsignature, nsig, player_url)
+                    .await?,
+            )?,
+            None => url,
+        })
+    }
+
+    async fn resolve_stream_url(&self, stream: &YtStream, player_url: String) -> Result<String> {
+        match &stream.source {
+            YtStreamSource::URL(url) => self.decipher_n_param(url.clone(), player_url).await,
+            YtStreamSource::Signature(signature) => {
+                self.decipher(signature.clone(), player_url).await
+            }
+            // Manifests are played from their URL, which carries `n` in its path instead of its query.
+            YtStreamSource::Hls(variant) => self.decipher_n_path(&variant.url, player_url).await,
+            YtStreamSource::Dash(representation) => {
+                self.decipher_n_path(&representation.manifest_url, player_url)
+                    .await
+            }
+        }
+    }
+
+    async fn decipher_streams(&self, mut response: YtStreamResponse) -> Result<YtStreamResponse> {
+        let player_url = response.player_url.clone();
+        let stream_challenges: Vec<Option<StreamChallenges>> = response
+            .streams
+            .iter()
+            .map(|stream| StreamChallenges::from_source(&stream.source))
+            .collect();
+
+        let mut solved: [HashMap<String, String>; 2] = Default::default();
+        let mut unsolved: [HashSet<String>; 2] = Default::default();
+        let signature_types = [SignatureType::Signature, SignatureType::Nsignature];
+
+        for challenges in stream_challenges.iter().flatten() {
+            let pending = [
+                challenges.signature.as_ref().map(|(s, _)| s),
+                challenges.nsig.as_ref(),
+            ];
+
+            for (i, challenge) in pending.into_iter().enumerate() {
+                let Some(challenge) = challenge else {
+                    continue;
+                };
+                if solved[i].contains_key(challenge) || unsolved[i].contains(challenge) {
+                    continue;
+                }
+
+                let cache_id = signature_types[i].cache_id(&player_url, challenge.clone());
+                match self.player_cache.get(&cache_id)? {
+                    Some(cached) => {
+                        solved[i].insert(challenge.clone(), cached);
+                    }
+                    None => {
+                        unsolved[i].insert(challenge.clone());
+                    }
+                }
+            }
+        }
+
+        if unsolved.iter().any(|challenges| !challenges.is_empty()) {
+            let player_js_code_key = self.player_cache.player_js_cache_key(&player_url)?;
+            let Some(code) = self.code_cache.get(&player_js_code_key)? else {
+                bail!(
+                    "The player.js was not downloaded before, deciphering failed because the code was not found."
+                )
+            };
+
+            let (signature_types, requests): (Vec<usize>, Vec<(SignatureType, Vec<String>)>) =
+                signature_types
+                    .into_iter()
+                    .zip(unsolved)
+                    .enumerate()
+                    .filter(|(_, (_, challenges))| !challenges.is_empty())
+                    .map(|(i, (signature_type, challenges))| {
+                        (i, (signature_type, challenges.into_iter().collect()))
+                    })
+                    .unzip();
+            let responses = self
+                .solve_challenges_js(player_js_code_key, code, requests.clone())
+                .await?;
+
+            for ((i, (signature_type, _)), data) in
+                signature_types.into_iter().zip(requests).zip(responses)
             {
-                Some(nsig) => replace_n_sig_query_param(
-                    &url_with_sig,
-                    self.decrypt_signature(SignatureType::Nsignature, nsig.clone(), player_url)
-                        .await?,
-                )?,
-                None => url_with_sig,
-            },
-        )
+                for (challenge, deciphered) in data {
+                    self.player_cache.add(
+                        signature_type.cache_id(&player_url, challenge.clone()),
+                        deciphered.clone(),
+                    )?;
+                    solved[i].insert(challenge, deciphered);
+                }
+            }
+        }
+
+        let [signatures, nsigs] = solved;
+
+        for (stream, challenges) in response.streams.iter_mut().zip(stream_challenges) {
+            let Some(challenges) = challenges else {
+                continue;
+            };
+
+            if challenges.is_manifest {
+                let Some((nsig, deciphered_n)) = challenges
+                    .nsig
+                    .and_then(|nsig| nsigs.get(&nsig).map(|deciphered_n| (nsig, deciphered_n)))
+                else {
+                    #[cfg(feature = "logging")]
+                    log::warn!(
+                        "The `n` path segment of format {:?} could not be deciphered.",
+                        stream.itag
+                    );
+                    continue;
+                };
+                match replace_manifest_n(&stream.source, &nsig, deciphered_n) {
+                    Ok(source) => stream.source = source,
+                    Err(_err) => {
+                        #[cfg(feature = "logging")]
+                        log::warn!(
+                            "The manifest URL of format {:?} could not be rebuilt: {}",
+                            stream.itag,
+                            _err
+                        );
+                    }
+                }
+                continue;
+            }
+
+            let mut url = challenges.url;
+
+            // A stream the solver couldn't handle keeps its source, the others are still usable.
+            if let Some((encrypted_sig, sp)) = challenges.signature {
+                let Some(signature) = signatures.get(&encrypted_sig) else {
+                    #[cfg(feature = "logging")]
+                    log::warn!(
+                        "The signature of format {:?} could not be deciphered.",
+                        stream.itag
+                    );
+                    continue;
+                };
+                url = format!("{}&{}={}", url, sp, signature);
+            }
+
+            if let Some(nsig) = challenges.nsig {
+                let Some(deciphered_n) = nsigs.get(&nsig) else {
+                    #[cfg(feature = "logging")]
+                    log::warn!(
+                        "The `n` parameter of format {:?} could not be deciphered.",
+                        stream.itag
+                    );
+                    continue;
+                };
+                url = match replace_n_sig_query_param(&url, deciphered_n.clone()) {
+                    Ok(url) => url,
+                    Err(_err) => {
+                        #[cfg(feature = "logging")]
+                        log::warn!(
+                            "The URL of format {:?} could not be rebuilt: {}",
+                            stream.itag,
+                            _err
+                        );
+                        continue;
+                    }
+                };
+            }
+
+            stream.source = YtStreamSource::URL(url);
+        }
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yt_interface::YtDashRepresentation;
+
+    #[test]
+    fn replaces_manifest_n_in_every_url() {
+        let base = "https://rr1---sn-abc.googlevideo.com/videoplayback/id/1/n/Ab12cD34/itag/137";
+        let other = "https://rr1---sn-abc.googlevideo.com/videoplayback/id/1/n/Ef56gH78/sq/2";
+        let source = YtStreamSource::Dash(YtDashRepresentation {
+            manifest_url: "https://manifest.googlevideo.com/api/manifest/dash/n/Ab12cD34/sparams/n"
+                .to_string(),
+            id: "137".to_string(),
+            base_url: format!("{}/", base),
+            bandwidth: 4_000_000,
+            codecs: None,
+            frame_rate: None,
+            initialization_url: Some(format!("{}/sq/0", base)),
+            segment_urls: vec![format!("{}/sq/1", base), other.to_string()],
+        });
+        let challenges = StreamChallenges::from_source(&source).unwrap();
+        assert_eq!(challenges.nsig.as_deref(), Some("Ab12cD34"));
+        assert!(challenges.is_manifest);
+
+        let YtStreamSource::Dash(representation) =
+            replace_manifest_n(&source, "Ab12cD34", "solved").unwrap()
+        else {
+            unreachable!()
+        };
+        let solved = base.replace("Ab12cD34", "solved");
+        assert_eq!(
+            representation.manifest_url,
+            "https://manifest.googlevideo.com/api/manifest/dash/n/solved/sparams/n"
+        );
+        assert_eq!(representation.base_url, format!("{}/", solved));
+        assert_eq!(
+            representation.initialization_url,
+            Some(format!("{}/sq/0", solved))
+        );
+        assert_eq!(representation.segment_urls[0], format!("{}/sq/1", solved));
+        // A URL with another `n` is left alone.
+        assert_eq!(representation.segment_urls[1], other.to_string());
     }
 }
@@ -1,175 +1,92 @@
-#[cfg(not(target_arch = "wasm32"))]
-use std::collections::HashMap;
-
-#[cfg(target_arch = "wasm32")]
-use anyhow::{Result, anyhow};
-#[cfg(not(target_arch = "wasm32"))]
-use anyhow::{Result, bail};
-#[cfg(not(target_arch = "wasm32"))]
-use deno_core::JsRuntime;
-#[cfg(target_arch = "wasm32")]
-use js_sys::{Function, eval};
-#[cfg(not(target_arch = "wasm32"))]
-use serde_json::json;
-#[cfg(target_arch = "wasm32")]
-use wasm_bindgen::prelude::*;
+use anyhow::{Result, anyhow, bail};
 
 use crate::{
-    cache::CacheAccess,
-    cipher::decipher::{SignatureDecipher, SignatureType},
+    cipher::{
+        bundle::{EJS_CORE_BUNDLE, EJS_LIB_BUNDLE, load_bundle},
+        decipher::{SignatureDecipher, SignatureType},
+    },
+    solver::{Solutions, SolveRequest, SolverBundles},
 };
 
 pub trait SignatureJsHandle {
-    async fn get_js_modules(&self) -> Result<(String, String)>;
+    /// Loads the ejs bundles from the solver source, once a JS backend asks for them.
+    fn solver_bundles(&self) -> SolverBundles;
     async fn parse_signature_js(
         &self,
+        player_key: String,
         code: String,
         example_sig: String,
         signature_type: SignatureType,
     ) -> Result<String>;
+    /// Solves every challenge of `requests` in a single `jsc` run over the player code.
+    async fn solve_challenges_js(
+        &self,
+        player_key: String,
+        code: String,
+        requests: Vec<(SignatureType, Vec<String>)>,
+    ) -> Result<Solutions>;
 }
 
 impl SignatureJsHandle for SignatureDecipher {
-    async fn get_js_modules(&self) -> Result<(String, String)> {
-        const YT_DLP_YT_SOLVER_PKG_LIB_URL: &str =
-            "https://github.com/yt-dlp/ejs/releases/download/0.3.1/yt.solver.lib.min.js";
-        const YT_DLP_YT_SOLVER_PKG_CORE_URL: &str =
-            "https://github.com/yt-dlp/ejs/releases/download/0.3.1/yt.solver.core.min.js";
+    fn solver_bundles(&self) -> SolverBundles {
+        let solver_source = self.solver_source.clone();
+        let code_cache = self.code_cache.clone();
 
-        let lib_code = match self.code_cache.get(&YT_DLP_YT_SOLVER_PKG_LIB_URL.into())? {
-            Some(cached_lib_code) => cached_lib_code,
-            None => {
-                let fetched_lib = reqwest::get(YT_DLP_YT_SOLVER_PKG_LIB_URL)
-                    .await?
-                    .text()
-                    .await?;
+        SolverBundles::new(move || {
+            let solver_source = solver_source.clone();
+            let code_cache = code_cache.clone();
 
-                self.code_cache
-                    .add(YT_DLP_YT_SOLVER_PKG_LIB_URL.into(), fetched_lib.clone())?;
+            async move {
+                let lib_code = load_bundle(&solver_source, &code_cache, EJS_LIB_BUNDLE).await?;
+                let core_code = load_bundle(&solver_source, &code_cache, EJS_CORE_BUNDLE).await?;
 
-                fetched_lib
+                Ok((lib_code, core_code))
             }
-        };
-
-        let core_code = match self.code_cache.get(&YT_DLP_YT_SOLVER_PKG_CORE_URL.into())? {
-            Some(cached_lib_code) => cached_lib_code,
-            None => {
-                let fetched_lib = reqwest::get(YT_DLP_YT_SOLVER_PKG_CORE_URL)
-                    .await?
-                    .text()
-                    .await?;
-
-                self.code_cache
-                    .add(YT_DLP_YT_SOLVER_PKG_CORE_URL.into(), fetched_lib.clone())?;
-
-                fetched_lib
-            }
-        };
-
-        Ok((lib_code, core_code))
+        })
     }
 
-    // Taken from `youtube_explode_dart`'s implementation with `yt-dlp`'s ejs cipher library.
-    // See: https://github.com/Hexer10/youtube_explode_dart/blob/a993b3d463713b0aabd945f07a7e6a1635bcf1e7/lib/src/reverse_engineering/challenges/ejs/ejs.dart
-    #[cfg(not(target_arch = "wasm32"))]
     async fn parse_signature_js(
         &self,
+        player_key: String,
         code: String,
         example_sig: String,
         signature_type: SignatureType,
     ) -> Result<String> {
-        let (lib_code, core_code) = self.get_js_modules().await?;
-
-        let js_env = format!(
-            "{}\nObject.assign(globalThis, lib);\n{}",
-            lib_code, core_code
-        );
-
-        let mut deno = JsRuntime::new(Default::default());
-
-        deno.execute_script("<setup_environment>", js_env)?;
-
-        let input = json!({
-            "type": "player",
-            "player": code,
-            "requests": [{"type": signature_type.as_str(), "challenges": [example_sig]}],
-            "output_preprocessed": true
-        });
-
-        let set_input_js = format!("globalThis.__input = {};", input.to_string());
-        deno.execute_script("<set_input>", set_input_js)?;
-
-        let js_call = r#"(function() {
-            var res = jsc(globalThis.__input);
-            return JSON.stringify(res);
-        })();"#;
-        let global_value = deno.execute_script("<parse_sig>", js_call)?;
-
-        deno.run_event_loop(Default::default()).await?;
-
-        let local_value = global_value.open(deno.v8_isolate());
-
-        let mut scope = deno.handle_scope();
-        let result_str = local_value.to_rust_string_lossy(&mut scope);
-
-        let result: HashMap<String, serde_json::Value> = serde_json::from_str(&result_str)?;
-        let Some(deciphered_sig) = result
-            .get("responses")
-            .and_then(|r| r.get(0))
-            .and_then(|r| r.get("data"))
-            .and_then(|r| r.get(example_sig))
-            .and_then(|v| v.as_str())
-        else {
-            bail!("Signature deciphering failed because ytcore returned an invalid response.")
-        };
-
-        Ok(deciphered_sig.into())
+        let mut solved = self
+            .solve_challenges_js(
+                player_key,
+                code,
+                vec![(signature_type, vec![example_sig.clone()])],
+            )
+            .await?;
+
+        solved
+            .pop()
+            .and_then(|mut data| data.remove(&example_sig))
+            .ok_or_else(|| {
+                anyhow!("Signature deciphering failed because ytcore returned an invalid response.")
+            })
     }
 
-    #[cfg(target_arch = "wasm32")]
-    async fn parse_signature_js(
+    async fn solve_challenges_js(
         &self,
+        player_key: String,
         code: String,
-        example_sig: String,
-        signature_type: SignatureType,
-    ) -> Result<String> {
-        let (lib_code, core_code) = self.get_js_modules().await?;
-
-        let js_env = format!(
-            "{}\nObject.assign(globalThis, lib);\n{}",
-            lib_code, core_code
-        );
-
-        eval(&js_env).map_err(|err| anyhow!("JS eval failed: {:?}", err))?;
-
-        let func = eval("jsc")
-            .map_err(|_| anyhow!("jsc not defined"))?
-            .dyn_into::<Function>()
-            .map_err(|_| anyhow!("Failed to defined `jsc` in the JS context."))?;
-
-        let input = serde_json::json!({
-            "type": "player",
-            "player": code,
-            "requests": [{"type": signature_type.as_str(), "challenges": [example_sig]}],
-            "output_preprocessed": true
-        });
-
-        let js_input = serde_wasm_bindgen::to_value(&input).map_err(|_| {
-            anyhow!("Signature deciphering failed due to the failure of serializing input for the JS context.")
-        })?;
-        let result_val = func
-            .call1(&JsValue::NULL, &js_input)
-            .map_err(|e| anyhow!("jsc() call failed: {:?}", e))?;
-
-        let result: serde_json::Value =
-            serde_wasm_bindgen::from_value(result_val).map_err(|_| {
-                anyhow!("Signature deciphering failed because the JS bridge returned an error.")
-            })?;
-        let deciphered = result["responses"][0]["data"][&example_sig]
-            .as_str()
-            .unwrap_or_default()
-            .to_string();
+        requests: Vec<(SignatureType, Vec<String>)>,
+    ) -> Result<Solutions> {
+        let Some(solver) = &self.solver else {
+            bail!(
+                "No JS challenge solver is available, enable the `deno` or `quickjs` feature or pass one to `Tydle::with_solver`."
+            )
+        };
 
-        Ok(deciphered)
+        solver
+            .solve(SolveRequest {
+                player_key,
+                player_code: code,
+                bundles: self.solver_bundles(),
+                challenges: requests,
+            })
+            .await
     }
 }
@@ -1,3 +1,4 @@
+mod bundle;
 mod js;
 
 pub mod decipher;
@@ -0,0 +1,905 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Result, anyhow, bail};
+use reqwest::{StatusCode, header};
+use tokio::{
+    fs::OpenOptions,
+    io::{AsyncWrite, AsyncWriteExt},
+    task::JoinSet,
+    time::sleep,
+};
+
+use crate::{
+    extractor::client::INNERTUBE_CLIENTS,
+    yt_interface::{YtStream, YtStreamSource},
+};
+
+/// YouTube throttles large requests, so streams are fetched in ranges of this size by default.
+const DEFAULT_CHUNK_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Delay before the first retry of a chunk, doubled on every attempt after it.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub struct YtDownloadOptions {
+    /// Size of each ranged request in bytes.
+    pub chunk_size: u64,
+    /// How many times a failed chunk is requested again before giving up.
+    pub retries: u32,
+    /// Bytes the sink already holds, the download continues from this offset.
+    pub start_offset: u64,
+    /// How many chunks are fetched in parallel. Chunks are still written to the sink in order.
+    ///
+    /// Parallel downloads need the size of the stream, streams without a `file_size` fall back to one connection.
+    pub connections: usize,
+}
+
+impl Default for YtDownloadOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            retries: 3,
+            start_offset: 0,
+            connections: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct YtDownloadProgress {
+    /// Bytes in the sink so far, including the ones resumed from.
+    pub downloaded_bytes: u64,
+    /// Size of the stream, `None` until it is known.
+    pub total_bytes: Option<u64>,
+}
+
+impl YtDownloadProgress {
+    /// Progress between `0.0` and `1.0`, if the size of the stream is known.
+    pub fn fraction(&self) -> Option<f64> {
+        self.total_bytes
+            .filter(|total| *total > 0)
+            .map(|total| self.downloaded_bytes as f64 / total as f64)
+    }
+}
+
+/// Downloads `YtStream`s in ranged chunks into any `AsyncWrite` sink.
+pub struct StreamDownloader {
+    http_client: reqwest::Client,
+    options: YtDownloadOptions,
+}
+
+impl StreamDownloader {
+    pub fn new(options: YtDownloadOptions) -> Self {
+        Self {
+            http_client: reqwest::Client::new(),
+            options,
+        }
+    }
+
+    /// Download a stream into `sink`, continuing from `YtDownloadOptions::start_offset`.
+    ///
+    /// The stream needs a playable URL, so `Signature` streams have to be turned into a `YtStreamSource::URL`
+    /// with `Cipher::resolve_stream_url` first.
+    /// Returns the size of the stream once every byte has been written.
+    ///
+    /// With more than one of `YtDownloadOptions::connections`, chunks are fetched in parallel
+    /// and held in memory until every chunk before them has been written.
+    ///
+    /// ```no_run
+    /// use tydle::{Tydle, TydleOptions, Extract, VideoId, Filterable};
+    /// use tydle::downloader::{StreamDownloader, YtDownloadOptions};
+    /// use anyhow::Result;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///   let ty = Tydle::new(TydleOptions { ..Default::default() })?;
+    ///   let streams = ty.get_streams(&VideoId::new("dQw4w9WgXcQ")?).await?.streams;
+    ///   let stream = streams
+    ///     .video_only()
+    ///     .only_urls()
+    ///     .with_highest_bitrate()
+    ///     .into_iter()
+    ///     .next()
+    ///     .expect("no URL streams");
+    ///
+    ///   let downloader = StreamDownloader::new(YtDownloadOptions {
+    ///     connections: 4,
+    ///     ..Default::default()
+    ///   });
+    ///   let mut file = tokio::fs::File::create("video.bin").await?;
+    ///   downloader
+    ///     .download(&stream, &mut file, |progress| println!("{:?}", progress.fraction()))
+    ///     .await?;
+    ///
+    ///   Ok(())
+    /// }
+    /// ```
+    pub async fn download<W, F>(
+        &self,
+        stream: &YtStream,
+        sink: &mut W,
+        mut on_progress: F,
+    ) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin,
+        F: FnMut(YtDownloadProgress),
+    {
+        let url = self.stream_url(stream)?;
+        let chunk_size = self.options.chunk_size.max(1);
+        let mut total_bytes = stream.file_size;
+        let mut downloaded_bytes = self.options.start_offset;
+
+        if let Some(total) = total_bytes
+            && downloaded_bytes > total
+        {
+            bail!(
+                "The sink already holds {} bytes but the stream is only {} bytes long.",
+                downloaded_bytes,
+                total
+            )
+        }
+
+        on_progress(YtDownloadProgress {
+            downloaded_bytes,
+            total_bytes,
+        });
+
+        if let Some(total) = total_bytes
+            && self.options.connections > 1
+        {
+            return self
+                .download_concurrently(url, stream, total, sink, on_progress)
+                .await;
+        }
+
+        while total_bytes.is_none_or(|total| downloaded_bytes < total) {
+            let range_end = match total_bytes {
+                Some(total) => (downloaded_bytes + chunk_size).min(total) - 1,
+                None => downloaded_bytes + chunk_size - 1,
+            };
+
+            let requested = range_end - downloaded_bytes + 1;
+            let mut attempt = 0;
+            let mut chunk_len = 0;
+
+            loop {
+                match self
+                    .download_chunk(
+                        url,
+                        stream,
+                        downloaded_bytes + chunk_len,
+                        range_end,
+                        sink,
+                        &mut total_bytes,
+                        &mut on_progress,
+                    )
+                    .await
+                {
+                    Ok(len) => {
+                        chunk_len += len;
+                        break;
+                    }
+                    // The sink keeps what was written, so the retry continues right after it.
+                    Err(ChunkError::Retryable { written, error: _e })
+                        if attempt < self.options.retries =>
+                    {
+                        chunk_len += written;
+                        attempt += 1;
+
+                        #[cfg(feature = "logging")]
+                        log::warn!(
+                            "Retrying chunk at byte {} ({}/{}): {}",
+                            downloaded_bytes + chunk_len,
+                            attempt,
+                            self.options.retries,
+                            _e
+                        );
+
+                        if chunk_len >= requested {
+                            break;
+                        }
+                        sleep(retry_delay(attempt)).await;
+                    }
+                    Err(ChunkError::Retryable { error, .. } | ChunkError::Fatal(error)) => {
+                        return Err(error);
+                    }
+                }
+            }
+
+            downloaded_bytes += chunk_len;
+
+            // Without a known size, a short chunk means the end of the stream was reached.
+            if total_bytes.is_none() && chunk_len < requested {
+                total_bytes = Some(downloaded_bytes);
+            }
+
+            if chunk_len == 0 && total_bytes.is_some_and(|total| downloaded_bytes < total) {
+                bail!(
+                    "The server returned no data at byte {} of the stream.",
+                    downloaded_bytes
+                )
+            }
+        }
+
+        sink.flush().await?;
+
+        let total = total_bytes.unwrap_or(downloaded_bytes);
+        if downloaded_bytes != total {
+            bail!(
+                "Downloaded {} bytes but the stream is {} bytes long.",
+                downloaded_bytes,
+                total
+            )
+        }
+
+        Ok(total)
+    }
+
+    /// Download a stream into a file, resuming from whatever a previous attempt left in it.
+    pub async fn download_to_file<F>(
+        &self,
+        stream: &YtStream,
+        path: impl AsRef<Path>,
+        on_progress: F,
+    ) -> Result<u64>
+    where
+        F: FnMut(YtDownloadProgress),
+    {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path.as_ref())
+            .await?;
+        let start_offset = file.metadata().await?.len();
+
+        #[cfg(feature = "logging")]
+        if start_offset > 0 {
+            log::info!(
+                "Resuming download of {} at byte {}",
+                path.as_ref().display(),
+                start_offset
+            );
+        }
+
+        let resumed = Self {
+            http_client: self.http_client.clone(),
+            options: YtDownloadOptions {
+                start_offset,
+                ..self.options.clone()
+            },
+        };
+
+        resumed.download(stream, &mut file, on_progress).await
+    }
+
+    /// Download the video-only and audio-only streams of a `YtFormatSelection::Merged` at the same time,
+    /// each into its own file and resuming like `download_to_file`. Returns both paths, ready for `muxer::mux`.
+    ///
+    /// `on_progress` gets the combined progress of both downloads.
+    pub async fn download_pair_to_files<F>(
+        &self,
+        video: &YtStream,
+        audio: &YtStream,
+        video_path: impl AsRef<Path>,
+        audio_path: impl AsRef<Path>,
+        on_progress: F,
+    ) -> Result<(PathBuf, PathBuf)>
+    where
+        F: FnMut(YtDownloadProgress),
+    {
+        let (video_path, audio_path) = (video_path.as_ref(), audio_path.as_ref());
+        let empty = YtDownloadProgress {
+            downloaded_bytes: 0,
+            total_bytes: None,
+        };
+        let progress = [Cell::new(empty), Cell::new(empty)];
+        let on_progress = RefCell::new(on_progress);
+
+        // Both downloads run on this task, so the callback is never borrowed twice.
+        let report = |i: usize, stream_progress: YtDownloadProgress| {
+            progress[i].set(stream_progress);
+            let [video, audio] = [progress[0].get(), progress[1].get()];
+
+            (on_progress.borrow_mut())(YtDownloadProgress {
+                downloaded_bytes: video.downloaded_bytes + audio.downloaded_bytes,
+                total_bytes: video.total_bytes.zip(audio.total_bytes).map(|(v, a)| v + a),
+            });
+        };
+
+        tokio::try_join!(
+            self.download_to_file(video, video_path, |p| report(0, p)),
+            self.download_to_file(audio, audio_path, |p| report(1, p)),
+        )?;
+
+        Ok((video_path.to_path_buf(), audio_path.to_path_buf()))
+    }
+
+    fn stream_url<'a>(&self, stream: &'a YtStream) -> Result<&'a str> {
+        match &stream.source {
+            YtStreamSource::URL(url) => Ok(url),
+            YtStreamSource::Signature(_) => {
+                bail!(
+                    "The stream's signature has to be deciphered with `resolve_stream_url` before it can be downloaded."
+                )
+            }
+            YtStreamSource::Hls(_) | YtStreamSource::Dash(_) => {
+                bail!("Segmented HLS and DASH streams can't be downloaded as a single file.")
+            }
+        }
+    }
+
+    async fn download_concurrently<W, F>(
+        &self,
+        url: &str,
+        stream: &YtStream,
+        total_bytes: u64,
+        sink: &mut W,
+        mut on_progress: F,
+    ) -> Result<u64>
+    where
+        W: AsyncWrite + Unpin,
+        F: FnMut(YtDownloadProgress),
+    {
+        let chunk_size = self.options.chunk_size.max(1);
+        let ranges: Vec<(u64, u64)> = (self.options.start_offset..total_bytes)
+            .step_by(chunk_size as usize)
+            .map(|start| (start, (start + chunk_size).min(total_bytes) - 1))
+            .collect();
+
+        let mut tasks = JoinSet::new();
+        let mut completed: BTreeMap<usize, Vec<u8>> = BTreeMap::new();
+        let mut next_to_schedule = 0;
+        let mut next_to_write = 0;
+        let mut downloaded_bytes = self.options.start_offset;
+
+        while next_to_write < ranges.len() {
+            // Chunks waiting on an earlier one count against the limit too, which bounds memory use.
+            while next_to_schedule < ranges.len()
+                && tasks.len() + completed.len() < self.options.connections
+            {
+                let (range_start, range_end) = ranges[next_to_schedule];
+                let request = self.chunk_request(url, stream, range_start, range_end);
+                let retries = self.options.retries;
+                let index = next_to_schedule;
+
+                tasks.spawn(async move {
+                    let mut attempt = 0;
+
+                    loop {
+                        let Some(request) = request.try_clone() else {
+                            return (index, Err(anyhow!("The chunk request can't be retried.")));
+                        };
+
+                        match fetch_chunk(request, range_start, range_end).await {
+                            Ok(chunk) => return (index, Ok(chunk)),
+                            Err(ChunkError::Retryable { error: _e, .. }) if attempt < retries => {
+                                attempt += 1;
+
+                                #[cfg(feature = "logging")]
+                                log::warn!(
+                                    "Retrying chunk at byte {} ({}/{}): {}",
+                                    range_start,
+                                    attempt,
+                                    retries,
+                                    _e
+                                );
+
+                                sleep(retry_delay(attempt)).await;
+                            }
+                            Err(ChunkError::Retryable { error, .. } | ChunkError::Fatal(error)) => {
+                                return (index, Err(error));
+                            }
+                        }
+                    }
+                });
+                next_to_schedule += 1;
+            }
+
+            let Some(joined) = tasks.join_next().await else {
+                bail!("Chunk downloads stopped before the stream was complete.")
+            };
+            let (index, chunk) = joined?;
+            let chunk = chunk?;
+
+            downloaded_bytes += chunk.len() as u64;
+            on_progress(YtDownloadProgress {
+                downloaded_bytes,
+                total_bytes: Some(total_bytes),
+            });
+            completed.insert(index, chunk);
+
+            while let Some(chunk) = completed.remove(&next_to_write) {
+                sink.write_all(&chunk).await?;
+                next_to_write += 1;
+            }
+        }
+
+        sink.flush().await?;
+
+        if downloaded_bytes != total_bytes {
+            bail!(
+                "Downloaded {} bytes but the stream is {} bytes long.",
+                downloaded_bytes,
+                total_bytes
+            )
+        }
+
+        Ok(total_bytes)
+    }
+
+    /// A ranged request for part of a stream, sent with the user agent of the client that returned it.
+    fn chunk_request(
+        &self,
+        url: &str,
+        stream: &YtStream,
+        range_start: u64,
+        range_end: u64,
+    ) -> reqwest::RequestBuilder {
+        let request = self.http_client.get(url).header(
+            header::RANGE,
+            format!("bytes={}-{}", range_start, range_end),
+        );
+
+        match INNERTUBE_CLIENTS
+            .get(&stream.client)
+            .and_then(|c| c.innertube_context.get("client"))
+            .and_then(|c| c.get("userAgent"))
+            .and_then(|ua| ua.as_str())
+        {
+            Some(user_agent) => request.header(header::USER_AGENT, user_agent),
+            None => request,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn download_chunk<W, F>(
+        &self,
+        url: &str,
+        stream: &YtStream,
+        range_start: u64,
+        range_end: u64,
+        sink: &mut W,
+        total_bytes: &mut Option<u64>,
+        on_progress: &mut F,
+    ) -> Result<u64, ChunkError>
+    where
+        W: AsyncWrite + Unpin,
+        F: FnMut(YtDownloadProgress),
+    {
+        let mut response = self
+            .chunk_request(url, stream, range_start, range_end)
+            .send()
+            .await
+            .map_err(ChunkError::retryable)?;
+
+        match response.status() {
+            StatusCode::PARTIAL_CONTENT => {}
+            // A range past the end is the end of a stream of unknown size.
+            StatusCode::RANGE_NOT_SATISFIABLE if total_bytes.is_none() => return Ok(0),
+            // The whole stream in one response is only usable when starting from the beginning.
+            StatusCode::OK if range_start == 0 => {}
+            StatusCode::OK => {
+                return Err(ChunkError::Fatal(anyhow!(
+                    "The server ignored the requested byte range, so the download can't be resumed."
+                )));
+            }
+            status if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS => {
+                return Err(ChunkError::retryable(anyhow!(
+                    "Chunk request failed with HTTP {}",
+                    status
+                )));
+            }
+            status => {
+                return Err(ChunkError::Fatal(anyhow!(
+                    "Chunk request failed with HTTP {}. The URL may have expired.",
+                    status
+                )));
+            }
+        }
+
+        let full_response = response.status() == StatusCode::OK;
+        let content_range_total = response
+            .headers()
+            .get(header::CONTENT_RANGE)
+            .and_then(|cr| cr.to_str().ok())
+            .and_then(|cr| cr.rsplit_once('/'))
+            .and_then(|(_, total)| total.parse::<u64>().ok());
+
+        match (*total_bytes, content_range_total) {
+            (None, Some(total)) => *total_bytes = Some(total),
+            (Some(expected), Some(total)) if expected != total => {
+                return Err(ChunkError::Fatal(anyhow!(
+                    "The server reports {} bytes but the stream should be {} bytes long.",
+                    total,
+                    expected
+                )));
+            }
+            _ => {}
+        }
+
+        let mut written = 0;
+
+        while let Some(bytes) = response.chunk().await.map_err(|e| ChunkError::Retryable {
+            written,
+            error: e.into(),
+        })? {
+            sink.write_all(&bytes)
+                .await
+                .map_err(|e| ChunkError::Fatal(e.into()))?;
+            written += bytes.len() as u64;
+
+            on_progress(YtDownloadProgress {
+                downloaded_bytes: range_start + written,
+                total_bytes: *total_bytes,
+            });
+        }
+
+        if full_response && total_bytes.is_none() {
+            *total_bytes = Some(written);
+        }
+
+        Ok(written)
+    }
+}
+
+enum ChunkError {
+    /// The chunk can be requested again, starting `written` bytes after where this attempt started.
+    Retryable {
+        written: u64,
+        error: anyhow::Error,
+    },
+    Fatal(anyhow::Error),
+}
+
+impl ChunkError {
+    fn retryable(e: impl Into<anyhow::Error>) -> Self {
+        Self::Retryable {
+            written: 0,
+            error: e.into(),
+        }
+    }
+}
+
+/// Exponential backoff for the `attempt`th retry, so throttled requests aren't hammered.
+fn retry_delay(attempt: u32) -> Duration {
+    RETRY_BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(RETRY_MAX_DELAY)
+}
+
+/// Fetch one byte range of a stream into memory, failing unless exactly that range comes back.
+async fn fetch_chunk(
+    request: reqwest::RequestBuilder,
+    range_start: u64,
+    range_end: u64,
+) -> Result<Vec<u8>, ChunkError> {
+    let response = request.send().await.map_err(ChunkError::retryable)?;
+
+    match response.status() {
+        StatusCode::PARTIAL_CONTENT => {}
+        StatusCode::OK => {
+            return Err(ChunkError::Fatal(anyhow!(
+                "The server ignored the requested byte range."
+            )));
+        }
+        status if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS => {
+            return Err(ChunkError::retryable(anyhow!(
+                "Chunk request failed with HTTP {}",
+                status
+            )));
+        }
+        status => {
+            return Err(ChunkError::Fatal(anyhow!(
+                "Chunk request failed with HTTP {}. The URL may have expired.",
+                status
+            )));
+        }
+    }
+
+    let chunk = response.bytes().await.map_err(ChunkError::retryable)?;
+    let expected = range_end - range_start + 1;
+
+    // A short body is a dropped connection, which is worth another try.
+    if chunk.len() as u64 != expected {
+        return Err(ChunkError::retryable(anyhow!(
+            "Expected {} bytes at byte {} but got {}.",
+            expected,
+            range_start,
+            chunk.len()
+        )));
+    }
+
+    Ok(chunk.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use tokio::{
+        io::AsyncReadExt,
+        net::{TcpListener, TcpStream},
+    };
+
+    use super::*;
+    use crate::yt_interface::{AudioTrackInfo, Ext, YtClient};
+
+    /// A local server answering ranged requests for `body`, failing the first `failures` with HTTP `status`.
+    struct TestServer {
+        url: String,
+        /// The `Range` header of every request, in the order they came in.
+        ranges: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl TestServer {
+        async fn start(body: Vec<u8>, failures: usize, status: &'static str) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}/videoplayback", listener.local_addr().unwrap());
+            let ranges = Arc::new(Mutex::new(vec![]));
+            let failures = Arc::new(AtomicUsize::new(failures));
+            let body = Arc::new(body);
+
+            let server_ranges = ranges.clone();
+            tokio::spawn(async move {
+                loop {
+                    let (socket, _) = listener.accept().await.unwrap();
+                    tokio::spawn(Self::respond(
+                        socket,
+                        body.clone(),
+                        server_ranges.clone(),
+                        failures.clone(),
+                        status,
+                    ));
+                }
+            });
+
+            Self { url, ranges }
+        }
+
+        async fn respond(
+            mut socket: TcpStream,
+            body: Arc<Vec<u8>>,
+            ranges: Arc<Mutex<Vec<String>>>,
+            failures: Arc<AtomicUsize>,
+            status: &str,
+        ) {
+            let mut request = vec![];
+            while !request.ends_with(b"\r\n\r\n") {
+                let mut byte = [0];
+                if socket.read(&mut byte).await.unwrap() == 0 {
+                    return;
+                }
+                request.push(byte[0]);
+            }
+
+            let request = String::from_utf8(request).unwrap();
+            let range = request
+                .lines()
+                .find_map(|line| {
+                    line.to_lowercase()
+                        .strip_prefix("range: ")
+                        .map(String::from)
+                })
+                .unwrap();
+            ranges.lock().unwrap().push(range.clone());
+
+            let response = match failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |f| f.checked_sub(1))
+                .is_ok()
+            {
+                true => format!(
+                    "HTTP/1.1 {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                )
+                .into_bytes(),
+                false => {
+                    let (start, end) = range.trim_start_matches("bytes=").split_once('-').unwrap();
+                    let start: usize = start.parse().unwrap();
+                    let end = end.parse::<usize>().unwrap().min(body.len() - 1);
+
+                    let mut response = format!(
+                        "HTTP/1.1 206 Partial Content\r\ncontent-range: bytes {}-{}/{}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                        start,
+                        end,
+                        body.len(),
+                        end - start + 1
+                    )
+                    .into_bytes();
+                    response.extend_from_slice(&body[start..=end]);
+                    response
+                }
+            };
+
+            socket.write_all(&response).await.unwrap();
+        }
+
+        fn ranges(&self) -> Vec<String> {
+            self.ranges.lock().unwrap().clone()
+        }
+    }
+
+    fn body(len: u8) -> Vec<u8> {
+        (0..len).collect()
+    }
+
+    fn stream(url: &str, file_size: Option<u64>) -> YtStream {
+        YtStream {
+            asr: None,
+            audio_channels: None,
+            file_size,
+            file_size_approx: 0.0,
+            height: None,
+            width: None,
+            fps: None,
+            format_duration: 0.0,
+            has_drm: false,
+            itag: Some("137".to_string()),
+            source: YtStreamSource::URL(url.to_string()),
+            tbr: 0.0,
+            audio_track: AudioTrackInfo {
+                display_name: None,
+                language: None,
+                is_default: true,
+            },
+            quality_label: String::new(),
+            is_drc: false,
+            projection: None,
+            spatial_audio: None,
+            client: YtClient::Web,
+            clients: vec![YtClient::Web],
+            ext: Ext::Mp4,
+            is_live: false,
+            mime_type: None,
+            codecs: None,
+            vcodec: None,
+            acodec: None,
+            loudness_db: None,
+            color_info: None,
+            dynamic_range: None,
+            init_range: None,
+            index_range: None,
+            last_modified: None,
+        }
+    }
+
+    fn downloader(start_offset: u64, connections: usize) -> StreamDownloader {
+        StreamDownloader::new(YtDownloadOptions {
+            chunk_size: 25,
+            retries: 2,
+            start_offset,
+            connections,
+        })
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("tydle-{}-{}", std::process::id(), name))
+    }
+
+    #[tokio::test]
+    async fn resumes_from_offset_in_ranges() {
+        let server = TestServer::start(body(100), 0, "").await;
+        let mut sink = vec![];
+
+        let total = downloader(40, 1)
+            .download(&stream(&server.url, Some(100)), &mut sink, |_| {})
+            .await
+            .unwrap();
+
+        assert_eq!(total, 100);
+        assert_eq!(sink, body(100)[40..]);
+        assert_eq!(
+            server.ranges(),
+            ["bytes=40-64", "bytes=65-89", "bytes=90-99"]
+        );
+    }
+
+    #[tokio::test]
+    async fn learns_the_size_of_streams_from_the_first_range() {
+        let server = TestServer::start(body(60), 0, "").await;
+        let mut sink = vec![];
+
+        let total = downloader(0, 4)
+            .download(&stream(&server.url, None), &mut sink, |_| {})
+            .await
+            .unwrap();
+
+        assert_eq!(total, 60);
+        assert_eq!(sink, body(60));
+        assert_eq!(
+            server.ranges(),
+            ["bytes=0-24", "bytes=25-49", "bytes=50-59"]
+        );
+    }
+
+    #[tokio::test]
+    async fn resumes_files_from_their_length() {
+        let server = TestServer::start(body(100), 0, "").await;
+        let path = temp_path("resume");
+        std::fs::write(&path, &body(100)[..30]).unwrap();
+
+        downloader(0, 3)
+            .download_to_file(&stream(&server.url, Some(100)), &path, |_| {})
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), body(100));
+        assert_eq!(server.ranges().len(), 3);
+        assert!(server.ranges().contains(&"bytes=30-54".to_string()));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn retries_failed_chunks() {
+        for connections in [1, 3] {
+            let server = TestServer::start(body(50), 2, "503 Service Unavailable").await;
+            let mut sink = vec![];
+
+            downloader(0, connections)
+                .download(&stream(&server.url, Some(50)), &mut sink, |_| {})
+                .await
+                .unwrap();
+
+            assert_eq!(sink, body(50));
+            assert_eq!(server.ranges().len(), 4);
+        }
+    }
+
+    #[tokio::test]
+    async fn gives_up_on_expired_urls() {
+        let server = TestServer::start(body(50), 1, "403 Forbidden").await;
+
+        let result = downloader(0, 1)
+            .download(&stream(&server.url, Some(50)), &mut vec![], |_| {})
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(server.ranges(), ["bytes=0-24"]);
+    }
+
+    #[test]
+    fn backs_off_exponentially() {
+        let delays: Vec<u64> = [1, 2, 3, 7, 40]
+            .into_iter()
+            .map(|attempt| retry_delay(attempt).as_millis() as u64)
+            .collect();
+
+        assert_eq!(delays, [500, 1000, 2000, 30_000, 30_000]);
+    }
+
+    #[tokio::test]
+    async fn downloads_video_and_audio_together() {
+        let video_server = TestServer::start(body(80), 0, "").await;
+        let audio_server = TestServer::start(body(30), 0, "").await;
+        let (video_path, audio_path) = (temp_path("video.mp4"), temp_path("audio.m4a"));
+        let mut last_progress = None;
+
+        let paths = downloader(0, 2)
+            .download_pair_to_files(
+                &stream(&video_server.url, Some(80)),
+                &stream(&audio_server.url, Some(30)),
+                &video_path,
+                &audio_path,
+                |progress| last_progress = Some(progress),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(paths, (video_path.clone(), audio_path.clone()));
+        assert_eq!(std::fs::read(&video_path).unwrap(), body(80));
+        assert_eq!(std::fs::read(&audio_path).unwrap(), body(30));
+        let last_progress = last_progress.unwrap();
+        assert_eq!(last_progress.downloaded_bytes, 110);
+        assert_eq!(last_progress.total_bytes, Some(110));
+        std::fs::remove_file(video_path).unwrap();
+        std::fs::remove_file(audio_path).unwrap();
+    }
+}
@@ -32,6 +32,13 @@ pub trait ExtractorApiHandle {
         api_key: Option<String>,
         default_client: Option<&YtClient>,
     ) -> Result<HashMap<String, Value>>;
+    /// Request a page from the `browse` endpoint, either the first page of `browse_id` or the page behind a continuation token.
+    async fn browse(
+        &self,
+        browse_id: Option<&str>,
+        params: Option<&str>,
+        continuation: Option<&str>,
+    ) -> Result<Value>;
 }
 
 impl ExtractorApiHandle for YtExtractor {
@@ -151,4 +158,29 @@ impl ExtractorApiHandle for YtExtractor {
         let response = request_builder.send().await?;
         Ok(response.json().await?)
     }
+
+    async fn browse(
+        &self,
+        browse_id: Option<&str>,
+        params: Option<&str>,
+        continuation: Option<&str>,
+    ) -> Result<Value> {
+        let mut query: HashMap<String, Value> = HashMap::new();
+
+        if let Some(token) = continuation {
+            query.insert("continuation".into(), token.into());
+        } else if let Some(id) = browse_id {
+            query.insert("browseId".into(), id.into());
+
+            if let Some(browse_params) = params {
+                query.insert("params".into(), browse_params.into());
+            }
+        }
+
+        let response = self
+            .call_api(YtEndpoint::Browse, query, None, None, None, None)
+            .await?;
+
+        Ok(Value::Object(response.into_iter().collect()))
+    }
 }
@@ -0,0 +1,294 @@
+use anyhow::{Result, bail};
+use serde_json::Value;
+
+use crate::{
+    extractor::{api::ExtractorApiHandle, extract::YtExtractor, json::ExtractorJsonHandle},
+    utils::{parse_count, parse_duration},
+    yt_interface::{
+        VideoId, YtChannel, YtChannelEntry, YtChannelInfo, YtChannelTab, YtChannelTabOptions,
+        YtChannelTabResponse, YtMediaType,
+    },
+};
+
+pub trait ExtractorChannelHandle {
+    async fn extract_channel_info(&self, channel: &YtChannel) -> Result<YtChannelInfo>;
+    /// Fetch a channel tab and walk its continuations until the limit is reached.
+    async fn extract_channel_tab(
+        &self,
+        channel: &YtChannel,
+        tab: YtChannelTab,
+        options: &YtChannelTabOptions,
+    ) -> Result<YtChannelTabResponse>;
+    fn extract_channel_metadata(
+        &self,
+        channel: &YtChannel,
+        response: &Value,
+    ) -> Result<YtChannelInfo>;
+    /// The content of the selected tab, or `None` if YouTube fell back to another tab because the requested one doesn't exist.
+    fn select_channel_tab<'v>(&self, response: &'v Value, tab: YtChannelTab) -> Option<&'v Value>;
+    fn parse_channel_entries(&self, content: &Value, tab: YtChannelTab) -> Vec<YtChannelEntry>;
+    fn parse_video_renderer(&self, renderer: &Value, tab: YtChannelTab) -> Option<YtChannelEntry>;
+    fn parse_short_renderer(&self, renderer: &Value) -> Option<YtChannelEntry>;
+}
+
+impl ExtractorChannelHandle for YtExtractor {
+    async fn extract_channel_info(&self, channel: &YtChannel) -> Result<YtChannelInfo> {
+        let response = self.browse(Some(channel.get_id()), None, None).await?;
+        self.extract_channel_metadata(channel, &response)
+    }
+
+    async fn extract_channel_tab(
+        &self,
+        channel: &YtChannel,
+        tab: YtChannelTab,
+        options: &YtChannelTabOptions,
+    ) -> Result<YtChannelTabResponse> {
+        #[cfg(feature = "logging")]
+        log::info!(
+            "{}: Downloading channel {} tab",
+            channel.get_id(),
+            tab.title()
+        );
+
+        let response = self
+            .browse(Some(channel.get_id()), Some(tab.browse_params()), None)
+            .await?;
+        let info = self.extract_channel_metadata(channel, &response)?;
+
+        let Some(content) = self.select_channel_tab(&response, tab) else {
+            #[cfg(feature = "logging")]
+            log::warn!("{}: Channel has no {} tab.", channel.get_id(), tab.title());
+
+            return Ok(YtChannelTabResponse {
+                info,
+                tab,
+                entries: vec![],
+            });
+        };
+
+        let limit = options.limit.map(|l| l as usize);
+        let mut entries = self.parse_channel_entries(content, tab);
+        let mut continuation = self.find_continuation_token(content);
+
+        while let Some(token) = continuation {
+            if limit.is_some_and(|l| entries.len() >= l) {
+                break;
+            }
+
+            #[cfg(feature = "logging")]
+            log::info!(
+                "{}: Downloading {} tab continuation after {} entries",
+                channel.get_id(),
+                tab.title(),
+                entries.len()
+            );
+
+            let page = self.browse(None, None, Some(&token)).await?;
+            entries.extend(self.parse_channel_entries(&page, tab));
+            continuation = self.find_continuation_token(&page);
+        }
+
+        if let Some(l) = limit {
+            entries.truncate(l);
+        }
+
+        Ok(YtChannelTabResponse { info, tab, entries })
+    }
+
+    fn extract_channel_metadata(
+        &self,
+        channel: &YtChannel,
+        response: &Value,
+    ) -> Result<YtChannelInfo> {
+        let Some(metadata) = self
+            .find_renderers(response, "channelMetadataRenderer")
+            .first()
+            .copied()
+        else {
+            bail!(
+                "Could not extract channel \"{}\": {}",
+                channel.get_id(),
+                self.extract_alert(response)
+                    .unwrap_or("YouTube did not return any channel metadata.".into())
+            )
+        };
+
+        let header_texts: Vec<&str> = self
+            .find_renderers(response, "contentMetadataViewModel")
+            .first()
+            .map(|cmv| {
+                self.find_renderers(cmv, "content")
+                    .into_iter()
+                    .filter_map(|c| c.as_str())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let legacy_header = self
+            .find_renderers(response, "c4TabbedHeaderRenderer")
+            .first()
+            .copied()
+            .unwrap_or(&Value::Null);
+
+        let handle = metadata
+            .get("vanityChannelUrl")
+            .and_then(|u| u.as_str())
+            .and_then(|u| u.rsplit('/').next())
+            .filter(|h| h.starts_with('@'))
+            .map(String::from)
+            .or_else(|| {
+                header_texts
+                    .iter()
+                    .find(|t| t.starts_with('@'))
+                    .map(|t| t.to_string())
+            })
+            .or_else(|| self.get_text(legacy_header, Some(vec![vec!["channelHandleText"]]), None));
+
+        let subscriber_count_text = header_texts
+            .iter()
+            .find(|t| t.contains("subscriber"))
+            .map(|t| t.to_string())
+            .or_else(|| {
+                self.get_text(legacy_header, Some(vec![vec!["subscriberCountText"]]), None)
+            });
+
+        let banner = self
+            .find_renderers(response, "imageBannerViewModel")
+            .first()
+            .and_then(|b| b.get("image"))
+            .or_else(|| legacy_header.get("banner"))
+            .map(|b| self.parse_thumbnails(b))
+            .unwrap_or_default();
+
+        let name = metadata
+            .get("title")
+            .and_then(|t| t.as_str())
+            .map(String::from)
+            .or_else(|| channel.get_name().map(String::from));
+
+        Ok(YtChannelInfo {
+            channel: YtChannel::new(channel.get_id(), name)?,
+            handle,
+            description: metadata
+                .get("description")
+                .and_then(|d| d.as_str())
+                .filter(|d| !d.is_empty())
+                .map(String::from),
+            subscriber_count_text,
+            avatar: metadata
+                .get("avatar")
+                .map(|a| self.parse_thumbnails(a))
+                .unwrap_or_default(),
+            banner,
+        })
+    }
+
+    fn select_channel_tab<'v>(&self, response: &'v Value, tab: YtChannelTab) -> Option<&'v Value> {
+        self.find_renderers(response, "tabRenderer")
+            .into_iter()
+            .find(|t| {
+                t.get("selected")
+                    .and_then(|s| s.as_bool())
+                    .unwrap_or_default()
+            })
+            .filter(|t| {
+                t.get("title")
+                    .and_then(|title| title.as_str())
+                    .is_some_and(|title| title.eq_ignore_ascii_case(tab.title()))
+            })
+            .and_then(|t| t.get("content"))
+    }
+
+    fn parse_channel_entries(&self, content: &Value, tab: YtChannelTab) -> Vec<YtChannelEntry> {
+        match tab {
+            YtChannelTab::Shorts => self
+                .find_renderers(content, "shortsLockupViewModel")
+                .into_iter()
+                .chain(self.find_renderers(content, "reelItemRenderer"))
+                .filter_map(|r| self.parse_short_renderer(r))
+                .collect(),
+            _ => self
+                .find_renderers(content, "videoRenderer")
+                .into_iter()
+                .filter_map(|r| self.parse_video_renderer(r, tab))
+                .collect(),
+        }
+    }
+
+    fn parse_video_renderer(&self, renderer: &Value, tab: YtChannelTab) -> Option<YtChannelEntry> {
+        let video_id = renderer
+            .get("videoId")
+            .and_then(|id| id.as_str())
+            .and_then(|id| VideoId::new(id).ok())?;
+
+        Some(YtChannelEntry {
+            video_id,
+            title: self
+                .get_text(renderer, Some(vec![vec!["title"]]), None)
+                .unwrap_or_default(),
+            duration: self
+                .get_text(renderer, Some(vec![vec!["lengthText"]]), None)
+                .and_then(|l| parse_duration(&l)),
+            view_count: self
+                .get_text(renderer, Some(vec![vec!["viewCountText"]]), None)
+                .and_then(|v| parse_count(&v)),
+            published_time_text: self.get_text(
+                renderer,
+                Some(vec![vec!["publishedTimeText"]]),
+                None,
+            ),
+            media_type: match tab {
+                YtChannelTab::Live => YtMediaType::LiveStream,
+                YtChannelTab::Shorts => YtMediaType::Short,
+                YtChannelTab::Videos => YtMediaType::Video,
+            },
+            thumbnails: renderer
+                .get("thumbnail")
+                .map(|t| self.parse_thumbnails(t))
+                .unwrap_or_default(),
+        })
+    }
+
+    fn parse_short_renderer(&self, renderer: &Value) -> Option<YtChannelEntry> {
+        let video_id = renderer
+            .get("videoId")
+            .and_then(|id| id.as_str())
+            .map(String::from)
+            .or_else(|| {
+                renderer
+                    .get("onTap")
+                    .and_then(|tap| self.find_key(tap, "videoId"))
+            })
+            .and_then(|id| VideoId::new(id).ok())?;
+
+        let overlay = renderer.get("overlayMetadata").unwrap_or(&Value::Null);
+
+        let title = overlay
+            .get("primaryText")
+            .and_then(|t| t.get("content"))
+            .and_then(|t| t.as_str())
+            .map(String::from)
+            .or_else(|| self.get_text(renderer, Some(vec![vec!["headline"]]), None))
+            .unwrap_or_default();
+
+        let view_count = overlay
+            .get("secondaryText")
+            .and_then(|t| t.get("content"))
+            .and_then(|t| t.as_str())
+            .map(String::from)
+            .or_else(|| self.get_text(renderer, Some(vec![vec!["viewCountText"]]), None))
+            .and_then(|v| parse_count(&v));
+
+        Some(YtChannelEntry {
+            video_id,
+            title,
+            duration: None,
+            view_count,
+            published_time_text: None,
+            media_type: YtMediaType::Short,
+            thumbnails: renderer
+                .get("thumbnail")
+                .map(|t| self.parse_thumbnails(t))
+                .unwrap_or_default(),
+        })
+    }
+}
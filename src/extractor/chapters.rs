@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+
+use fancy_regex::Regex;
+use serde_json::Value;
+
+use crate::{
+    extractor::{extract::YtExtractor, json::ExtractorJsonHandle},
+    utils::parse_duration,
+    yt_interface::{YtChapter, YtThumbnail},
+};
+
+/// A chapter before its end is known.
+pub struct ChapterStart {
+    title: String,
+    start_time: f64,
+    thumbnails: Vec<YtThumbnail>,
+}
+
+pub trait ExtractorChaptersHandle {
+    /// Chapters from the player bar markers, then the engagement panel macro markers, then the description.
+    fn extract_chapters(
+        &self,
+        initial_data: &HashMap<String, Value>,
+        duration: u64,
+        description: &str,
+    ) -> Vec<YtChapter>;
+    fn extract_chapters_from_player_bar(
+        &self,
+        initial_data: &HashMap<String, Value>,
+    ) -> Vec<ChapterStart>;
+    fn extract_chapters_from_engagement_panels(
+        &self,
+        initial_data: &HashMap<String, Value>,
+    ) -> Vec<ChapterStart>;
+}
+
+impl ExtractorChaptersHandle for YtExtractor {
+    fn extract_chapters(
+        &self,
+        initial_data: &HashMap<String, Value>,
+        duration: u64,
+        description: &str,
+    ) -> Vec<YtChapter> {
+        let mut starts = self.extract_chapters_from_player_bar(initial_data);
+
+        if starts.is_empty() {
+            starts = self.extract_chapters_from_engagement_panels(initial_data);
+        }
+
+        if starts.is_empty() {
+            starts = extract_chapters_from_description(description, duration);
+        }
+
+        finish_chapters(starts, duration)
+    }
+
+    fn extract_chapters_from_player_bar(
+        &self,
+        initial_data: &HashMap<String, Value>,
+    ) -> Vec<ChapterStart> {
+        let Some(overlays) = initial_data.get("playerOverlays") else {
+            return vec![];
+        };
+
+        // The markers map can hold both the uploader's chapters and YouTube's generated ones.
+        let markers: Vec<&Value> = self
+            .find_renderers(overlays, "markersMap")
+            .into_iter()
+            .filter_map(|map| map.as_array())
+            .flatten()
+            .collect();
+        let chapter_list = ["DESCRIPTION_CHAPTERS", "AUTO_CHAPTERS"]
+            .iter()
+            .find_map(|key| {
+                markers
+                    .iter()
+                    .find(|marker| marker.get("key").and_then(|k| k.as_str()) == Some(*key))
+            })
+            .and_then(|marker| marker.get("value"))
+            .unwrap_or(overlays);
+
+        self.find_renderers(chapter_list, "chapterRenderer")
+            .into_iter()
+            .filter_map(|chapter| {
+                Some(ChapterStart {
+                    title: self.get_text(chapter, Some(vec![vec!["title"]]), None)?,
+                    start_time: chapter.get("timeRangeStartMillis")?.as_f64()? / 1000.0,
+                    thumbnails: chapter
+                        .get("thumbnail")
+                        .map(|t| self.parse_thumbnails(t))
+                        .unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+
+    fn extract_chapters_from_engagement_panels(
+        &self,
+        initial_data: &HashMap<String, Value>,
+    ) -> Vec<ChapterStart> {
+        let panels = initial_data
+            .get("engagementPanels")
+            .map(|panels| self.find_renderers(panels, "engagementPanelSectionListRenderer"))
+            .unwrap_or_default();
+
+        // Prefer chapters the uploader wrote over the ones YouTube generated.
+        for panel_identifier in [
+            "engagement-panel-macro-markers-description-chapters",
+            "engagement-panel-macro-markers-auto-chapters",
+        ] {
+            let Some(panel) = panels.iter().find(|panel| {
+                panel.get("panelIdentifier").and_then(|id| id.as_str()) == Some(panel_identifier)
+            }) else {
+                continue;
+            };
+
+            let chapters: Vec<ChapterStart> = self
+                .find_renderers(panel, "macroMarkersListItemRenderer")
+                .into_iter()
+                .filter_map(|marker| {
+                    let start_time = marker
+                        .get("onTap")
+                        .and_then(|tap| tap.get("watchEndpoint"))
+                        .and_then(|we| we.get("startTimeSeconds"))
+                        .and_then(|s| s.as_f64())
+                        .or_else(|| {
+                            self.get_text(marker, Some(vec![vec!["timeDescription"]]), None)
+                                .and_then(|t| parse_duration(&t))
+                                .map(|s| s as f64)
+                        })?;
+
+                    Some(ChapterStart {
+                        title: self.get_text(marker, Some(vec![vec!["title"]]), None)?,
+                        start_time,
+                        thumbnails: marker
+                            .get("thumbnail")
+                            .map(|t| self.parse_thumbnails(t))
+                            .unwrap_or_default(),
+                    })
+                })
+                .collect();
+
+            if !chapters.is_empty() {
+                return chapters;
+            }
+        }
+
+        vec![]
+    }
+}
+
+/// Read chapters from description lines like `0:00 Intro`, `[1:02:03] - Outro` or `Intro (0:00)`.
+///
+/// Follows YouTube's own rules for description chapters: the first one has to start at 0:00 and
+/// there have to be at least two, otherwise the timestamps are just links into the video.
+fn extract_chapters_from_description(description: &str, duration: u64) -> Vec<ChapterStart> {
+    let Ok(leading_re) = Regex::new(
+        r"^\s*(?:\d+[.)]\s+)?[\[(]?(?P<time>(?:\d+:)?\d{1,2}:\d{2})[\])]?\s*(?:[-–—:|]\s*)?(?P<title>.+?)\s*$",
+    ) else {
+        return vec![];
+    };
+    let Ok(trailing_re) = Regex::new(
+        r"^\s*(?P<title>.+?)\s*(?:[-–—:|]\s*)?[\[(]?(?P<time>(?:\d+:)?\d{1,2}:\d{2})[\])]?\s*$",
+    ) else {
+        return vec![];
+    };
+
+    let mut chapters: Vec<ChapterStart> = vec![];
+
+    for line in description.lines() {
+        let Some(caps) = leading_re
+            .captures(line)
+            .ok()
+            .flatten()
+            .or_else(|| trailing_re.captures(line).ok().flatten())
+        else {
+            continue;
+        };
+
+        let (Some(time), Some(title)) = (caps.name("time"), caps.name("title")) else {
+            continue;
+        };
+        let Some(start) = parse_duration(time.as_str()) else {
+            continue;
+        };
+
+        // A 0:00 line starts the chapter list, anything before it was a stray timestamp.
+        if start == 0 {
+            chapters.clear();
+        }
+
+        let in_order = chapters
+            .last()
+            .is_none_or(|last| start as f64 > last.start_time);
+
+        if !in_order || (duration > 0 && start >= duration) {
+            continue;
+        }
+
+        chapters.push(ChapterStart {
+            title: title.as_str().to_string(),
+            start_time: start as f64,
+            thumbnails: vec![],
+        });
+    }
+
+    match chapters.first() {
+        Some(first) if first.start_time == 0.0 && chapters.len() >= 2 => chapters,
+        _ => vec![],
+    }
+}
+
+fn finish_chapters(mut starts: Vec<ChapterStart>, duration: u64) -> Vec<YtChapter> {
+    starts.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+
+    let end_times: Vec<f64> = starts
+        .iter()
+        .skip(1)
+        .map(|next| next.start_time)
+        .chain([duration as f64])
+        .collect();
+
+    starts
+        .into_iter()
+        .zip(end_times)
+        .map(|(chapter, end_time)| YtChapter {
+            title: chapter.title,
+            start_time: chapter.start_time,
+            end_time: end_time.max(chapter.start_time),
+            thumbnails: chapter.thumbnails,
+        })
+        .collect()
+}
@@ -39,6 +39,21 @@ pub struct InnerTubeClient {
     pub priority: isize,
 }
 
+/// Lower is preferred. `client_priority` decides when set, like `TydleOptions::client_priority`,
+/// otherwise the `priority` of the InnerTube clients does.
+pub(crate) fn client_rank(client: &YtClient, client_priority: &[YtClient]) -> isize {
+    match client_priority.is_empty() {
+        true => INNERTUBE_CLIENTS
+            .get(client)
+            .map(|c| -c.priority)
+            .unwrap_or_default(),
+        false => client_priority
+            .iter()
+            .position(|c| c == client)
+            .unwrap_or(client_priority.len()) as isize,
+    }
+}
+
 impl InnerTubeClient {
     pub fn to_json_val_hashmap(&self) -> Result<HashMap<String, Value>> {
         let serialized = serde_json::to_value(self)?;
@@ -0,0 +1,466 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::{
+    extractor::{api::ExtractorApiHandle, extract::YtExtractor, json::ExtractorJsonHandle},
+    utils::parse_count,
+    yt_interface::{
+        VideoId, YT_URL, YtChannel, YtComment, YtCommentOptions, YtCommentSort, YtCommentsResponse,
+        YtEndpoint, YtTextRun, YtThumbnail,
+    },
+};
+
+pub trait ExtractorCommentsHandle {
+    /// Fetch a video's top-level comments in the requested order.
+    async fn extract_comments(
+        &self,
+        video_id: &VideoId,
+        options: &YtCommentOptions,
+    ) -> Result<YtCommentsResponse>;
+    /// Fetch the replies to a top-level comment.
+    async fn extract_comment_replies(
+        &self,
+        comment: &YtComment,
+        options: &YtCommentOptions,
+    ) -> Result<YtCommentsResponse>;
+    /// Keep fetching comments or replies from a previous response's continuation.
+    async fn extract_comments_continuation(
+        &self,
+        continuation: &str,
+        options: &YtCommentOptions,
+    ) -> Result<YtCommentsResponse>;
+    /// Follow continuations from `token` until the comment limit is reached, stopping at a page boundary.
+    async fn collect_comments(
+        &self,
+        token: &str,
+        options: &YtCommentOptions,
+    ) -> Result<YtCommentsResponse>;
+    async fn call_next(&self, query: HashMap<String, Value>) -> Result<Value>;
+    fn parse_comments_page(&self, response: &Value) -> YtCommentsResponse;
+    /// The continuation that reloads the comments in `sort` order, from the sort menu of the first page.
+    fn find_sort_continuation(&self, response: &Value, sort: YtCommentSort) -> Option<String>;
+    /// Parse a comment from the view model that links into the response's entity mutations.
+    fn parse_comment_view_model(
+        &self,
+        view_model: &Value,
+        entities: &HashMap<&str, &Value>,
+    ) -> Option<YtComment>;
+    /// Parse a comment from the older `commentRenderer` layout.
+    fn parse_comment_renderer(&self, renderer: &Value) -> Option<YtComment>;
+}
+
+/// Reply IDs are the parent's ID followed by a dot and the reply's own ID.
+fn parent_comment_id(comment_id: &str) -> Option<String> {
+    comment_id
+        .split_once('.')
+        .map(|(parent_id, _)| parent_id.to_string())
+}
+
+fn absolute_url(url: &str) -> String {
+    match url.starts_with('/') {
+        true => format!("{}{}", YT_URL, url),
+        false => url.to_string(),
+    }
+}
+
+impl ExtractorCommentsHandle for YtExtractor {
+    async fn extract_comments(
+        &self,
+        video_id: &VideoId,
+        options: &YtCommentOptions,
+    ) -> Result<YtCommentsResponse> {
+        #[cfg(feature = "logging")]
+        log::info!("{}: Downloading comments", video_id.as_str());
+
+        let mut query = HashMap::new();
+        query.insert("videoId".into(), video_id.as_str().into());
+
+        let watch_next = self.call_next(query).await?;
+        let comments_section = self
+            .find_renderers(&watch_next, "itemSectionRenderer")
+            .into_iter()
+            .find(|section| {
+                section.get("sectionIdentifier").and_then(|id| id.as_str())
+                    == Some("comment-item-section")
+            });
+
+        let Some(token) =
+            comments_section.and_then(|section| self.find_continuation_token(section))
+        else {
+            #[cfg(feature = "logging")]
+            log::warn!(
+                "{}: Could not find a comments section, comments may be turned off.",
+                video_id.as_str()
+            );
+
+            return Ok(YtCommentsResponse::default());
+        };
+
+        if options.sort == YtCommentSort::Top {
+            return self.collect_comments(&token, options).await;
+        }
+
+        let mut query = HashMap::new();
+        query.insert("continuation".into(), token.clone().into());
+
+        let first_page = self.call_next(query).await?;
+        let sorted_token = self
+            .find_sort_continuation(&first_page, options.sort)
+            .unwrap_or(token);
+
+        self.collect_comments(&sorted_token, options).await
+    }
+
+    async fn extract_comment_replies(
+        &self,
+        comment: &YtComment,
+        options: &YtCommentOptions,
+    ) -> Result<YtCommentsResponse> {
+        let Some(token) = &comment.replies_continuation else {
+            return Ok(YtCommentsResponse::default());
+        };
+
+        #[cfg(feature = "logging")]
+        log::info!("Downloading replies to comment {}", comment.id);
+
+        self.collect_comments(token, options).await
+    }
+
+    async fn extract_comments_continuation(
+        &self,
+        continuation: &str,
+        options: &YtCommentOptions,
+    ) -> Result<YtCommentsResponse> {
+        self.collect_comments(continuation, options).await
+    }
+
+    async fn collect_comments(
+        &self,
+        token: &str,
+        options: &YtCommentOptions,
+    ) -> Result<YtCommentsResponse> {
+        let limit = options.max_comments.map(|l| l as usize);
+        let mut result = YtCommentsResponse {
+            continuation: Some(token.to_string()),
+            ..Default::default()
+        };
+
+        while let Some(token) = result.continuation.take() {
+            if limit.is_some_and(|l| result.comments.len() >= l) {
+                result.continuation = Some(token);
+                break;
+            }
+
+            #[cfg(feature = "logging")]
+            log::info!(
+                "Downloading comments page after {} comments",
+                result.comments.len()
+            );
+
+            let mut query = HashMap::new();
+            query.insert("continuation".into(), token.into());
+
+            let page = self.parse_comments_page(&self.call_next(query).await?);
+            result.comments.extend(page.comments);
+            result.total_count = result.total_count.or(page.total_count);
+            result.continuation = page.continuation;
+        }
+
+        // The last page is kept whole, as its continuation resumes after it.
+        Ok(result)
+    }
+
+    async fn call_next(&self, query: HashMap<String, Value>) -> Result<Value> {
+        let response = self
+            .call_api(YtEndpoint::Next, query, None, None, None, None)
+            .await?;
+
+        Ok(Value::Object(response.into_iter().collect()))
+    }
+
+    fn parse_comments_page(&self, response: &Value) -> YtCommentsResponse {
+        let entities: HashMap<&str, &Value> = response
+            .get("frameworkUpdates")
+            .and_then(|fu| fu.get("entityBatchUpdate"))
+            .and_then(|ebu| ebu.get("mutations"))
+            .and_then(|m| m.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|mutation| {
+                Some((
+                    mutation.get("entityKey")?.as_str()?,
+                    mutation.get("payload")?,
+                ))
+            })
+            .collect();
+
+        let items = response
+            .get("onResponseReceivedEndpoints")
+            .map(|endpoints| self.find_renderers(endpoints, "continuationItems"))
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|items| items.as_array())
+            .flatten();
+
+        let mut page = YtCommentsResponse::default();
+
+        for item in items {
+            if let Some(thread) = item.get("commentThreadRenderer") {
+                let comment = thread
+                    .get("commentViewModel")
+                    .and_then(|cvm| cvm.get("commentViewModel"))
+                    .and_then(|view_model| self.parse_comment_view_model(view_model, &entities))
+                    .or_else(|| {
+                        thread
+                            .get("comment")
+                            .and_then(|c| c.get("commentRenderer"))
+                            .and_then(|renderer| self.parse_comment_renderer(renderer))
+                    });
+
+                if let Some(mut comment) = comment {
+                    comment.replies_continuation = thread
+                        .get("replies")
+                        .and_then(|replies| self.find_continuation_token(replies));
+                    page.comments.push(comment);
+                }
+            } else if let Some(view_model) = item.get("commentViewModel") {
+                page.comments
+                    .extend(self.parse_comment_view_model(view_model, &entities));
+            } else if let Some(renderer) = item.get("commentRenderer") {
+                page.comments.extend(self.parse_comment_renderer(renderer));
+            } else if let Some(renderer) = item.get("continuationItemRenderer") {
+                page.continuation = self.find_key(renderer, "token");
+            } else if let Some(header) = item.get("commentsHeaderRenderer") {
+                page.total_count = self
+                    .get_text(header, Some(vec![vec!["countText"]]), None)
+                    .and_then(|c| parse_count(&c));
+            }
+        }
+
+        page
+    }
+
+    fn find_sort_continuation(&self, response: &Value, sort: YtCommentSort) -> Option<String> {
+        let sort_index = match sort {
+            YtCommentSort::Top => 0,
+            YtCommentSort::Newest => 1,
+        };
+
+        self.find_renderers(response, "sortFilterSubMenuRenderer")
+            .first()
+            .and_then(|menu| menu.get("subMenuItems"))
+            .and_then(|items| items.as_array())
+            .and_then(|items| items.get(sort_index))
+            .and_then(|item| item.get("serviceEndpoint"))
+            .and_then(|endpoint| self.find_key(endpoint, "token"))
+    }
+
+    fn parse_comment_view_model(
+        &self,
+        view_model: &Value,
+        entities: &HashMap<&str, &Value>,
+    ) -> Option<YtComment> {
+        let entity = |key: &str, payload: &str| {
+            view_model
+                .get(key)
+                .and_then(|k| k.as_str())
+                .and_then(|k| entities.get(k))
+                .and_then(|e| e.get(payload))
+        };
+
+        let comment = entity("commentKey", "commentEntityPayload")?;
+        let properties = comment.get("properties").unwrap_or(&Value::Null);
+        let author = comment.get("author").unwrap_or(&Value::Null);
+        let toolbar = comment.get("toolbar").unwrap_or(&Value::Null);
+
+        let id = properties
+            .get("commentId")
+            .or_else(|| view_model.get("commentId"))
+            .and_then(|id| id.as_str())?
+            .to_string();
+
+        let content = properties.get("content").unwrap_or(&Value::Null);
+        let text = content
+            .get("content")
+            .and_then(|c| c.as_str())
+            .unwrap_or_default()
+            .to_string();
+
+        // Command runs index into the text in UTF-16 code units, like the JavaScript strings they come from.
+        let utf16: Vec<u16> = text.encode_utf16().collect();
+        let mut runs = vec![];
+        let mut position = 0;
+        let mut command_runs: Vec<&Value> = content
+            .get("commandRuns")
+            .and_then(|r| r.as_array())
+            .into_iter()
+            .flatten()
+            .collect();
+        command_runs.sort_by_key(|run| run.get("startIndex").and_then(|s| s.as_u64()));
+
+        for run in command_runs {
+            let (Some(start), Some(length)) = (
+                run.get("startIndex").and_then(|s| s.as_u64()),
+                run.get("length").and_then(|l| l.as_u64()),
+            ) else {
+                continue;
+            };
+            let (start, end) = (start as usize, (start + length) as usize);
+
+            if start < position || end > utf16.len() {
+                continue;
+            }
+
+            if start > position {
+                runs.push(YtTextRun {
+                    text: String::from_utf16_lossy(&utf16[position..start]),
+                    url: None,
+                });
+            }
+
+            runs.push(YtTextRun {
+                text: String::from_utf16_lossy(&utf16[start..end]),
+                url: run
+                    .get("onTap")
+                    .and_then(|tap| self.find_key(tap, "url"))
+                    .map(|url| absolute_url(&url)),
+            });
+            position = end;
+        }
+
+        if position < utf16.len() {
+            runs.push(YtTextRun {
+                text: String::from_utf16_lossy(&utf16[position..]),
+                url: None,
+            });
+        }
+
+        let author_channel = author
+            .get("channelId")
+            .and_then(|id| id.as_str())
+            .and_then(|id| {
+                let name = author
+                    .get("displayName")
+                    .and_then(|n| n.as_str())
+                    .map(String::from);
+
+                YtChannel::new(id, name).ok()
+            });
+
+        let toolbar_count = |key: &str| {
+            toolbar
+                .get(key)
+                .and_then(|c| c.as_str())
+                .map(|c| parse_count(c).unwrap_or_default())
+        };
+
+        Some(YtComment {
+            parent_id: parent_comment_id(&id),
+            id,
+            text,
+            runs,
+            author: author_channel,
+            author_thumbnails: author
+                .get("avatarThumbnailUrl")
+                .and_then(|url| url.as_str())
+                .map(|url| {
+                    vec![YtThumbnail {
+                        url: url.to_string(),
+                        height: None,
+                        width: None,
+                    }]
+                })
+                .unwrap_or_default(),
+            is_author_uploader: author
+                .get("isCreator")
+                .and_then(|c| c.as_bool())
+                .unwrap_or_default(),
+            like_count: toolbar_count("likeCountNotliked"),
+            reply_count: toolbar_count("replyCount").unwrap_or_default(),
+            is_pinned: view_model.get("pinnedText").is_some(),
+            is_hearted: entity("toolbarStateKey", "engagementToolbarStateEntityPayload")
+                .and_then(|state| state.get("heartState"))
+                .and_then(|h| h.as_str())
+                == Some("TOOLBAR_HEART_STATE_HEARTED"),
+            published_time_text: properties
+                .get("publishedTime")
+                .and_then(|p| p.as_str())
+                .map(String::from),
+            replies_continuation: None,
+        })
+    }
+
+    fn parse_comment_renderer(&self, renderer: &Value) -> Option<YtComment> {
+        let id = renderer.get("commentId")?.as_str()?.to_string();
+
+        let runs: Vec<YtTextRun> = renderer
+            .get("contentText")
+            .and_then(|ct| ct.get("runs"))
+            .and_then(|r| r.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|run| {
+                Some(YtTextRun {
+                    text: run.get("text")?.as_str()?.to_string(),
+                    url: run
+                        .get("navigationEndpoint")
+                        .and_then(|ne| self.find_key(ne, "url"))
+                        .map(|url| absolute_url(&url)),
+                })
+            })
+            .collect();
+
+        let author = renderer
+            .get("authorEndpoint")
+            .and_then(|ae| ae.get("browseEndpoint"))
+            .and_then(|be| be.get("browseId"))
+            .and_then(|id| id.as_str())
+            .and_then(|id| {
+                YtChannel::new(
+                    id,
+                    self.get_text(renderer, Some(vec![vec!["authorText"]]), None),
+                )
+                .ok()
+            });
+
+        Some(YtComment {
+            parent_id: parent_comment_id(&id),
+            id,
+            text: runs.iter().map(|run| run.text.as_str()).collect(),
+            runs,
+            author,
+            author_thumbnails: renderer
+                .get("authorThumbnail")
+                .map(|t| self.parse_thumbnails(t))
+                .unwrap_or_default(),
+            is_author_uploader: renderer
+                .get("authorIsChannelOwner")
+                .and_then(|o| o.as_bool())
+                .unwrap_or_default(),
+            like_count: Some(
+                self.get_text(renderer, Some(vec![vec!["voteCount"]]), None)
+                    .and_then(|v| parse_count(&v))
+                    .unwrap_or_default(),
+            ),
+            reply_count: renderer
+                .get("replyCount")
+                .and_then(|r| r.as_u64())
+                .unwrap_or_default(),
+            is_pinned: renderer.get("pinnedCommentBadge").is_some(),
+            is_hearted: self
+                .find_renderers(renderer, "creatorHeartRenderer")
+                .first()
+                .and_then(|heart| heart.get("isHearted"))
+                .and_then(|h| h.as_bool())
+                .unwrap_or_default(),
+            published_time_text: self.get_text(
+                renderer,
+                Some(vec![vec!["publishedTimeText"]]),
+                None,
+            ),
+            replies_continuation: None,
+        })
+    }
+}
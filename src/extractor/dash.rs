@@ -0,0 +1,695 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Result, anyhow, bail};
+use fancy_regex::{Captures, Regex};
+use once_cell::sync::Lazy;
+use reqwest::Url;
+use serde_json::Value;
+
+use crate::{
+    AudioTrackInfo, STREAMING_DATA_CLIENT_NAME,
+    extractor::{download::ExtractorDownloadHandle, extract::YtExtractor},
+    utils::{
+        dynamic_range, escape_xml, file_size_from_tbr, mime_type_to_ext, split_codecs, unescape_xml,
+    },
+    yt_interface::{
+        YtByteRange, YtClient, YtColorInfo, YtDashRepresentation, YtStream, YtStreamResponse,
+        YtStreamSource,
+    },
+};
+
+static XML_TOKEN_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?s)<!--.*?-->|<\?.*?\?>|<!\[CDATA\[(?P<cdata>.*?)\]\]>|<!.*?>|<(?P<close>/)?(?P<name>[\w:.-]+)(?P<attrs>[^>]*?)(?P<self_closing>/)?>",
+    )
+    .expect("XML token regex is valid")
+});
+
+static XML_ATTRIBUTE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?P<key>[\w:.-]+)\s*=\s*(?:"(?P<dq>[^"]*)"|'(?P<sq>[^']*)')"#)
+        .expect("XML attribute regex is valid")
+});
+
+static TEMPLATE_IDENTIFIER_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\$(?P<id>RepresentationID|Bandwidth|Number|Time)(?:%0(?P<width>\d+)d)?\$")
+        .expect("segment template regex is valid")
+});
+
+/// Just enough of an XML element tree to read MPDs.
+#[derive(Default)]
+struct XmlElement {
+    name: String,
+    attributes: HashMap<String, String>,
+    children: Vec<XmlElement>,
+    text: String,
+}
+
+impl XmlElement {
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+
+    fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElement> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+}
+
+/// A `Representation` of an MPD with the attributes it inherits from its `AdaptationSet`.
+struct DashRepresentation {
+    representation: YtDashRepresentation,
+    mime_type: Option<String>,
+    width: Option<u64>,
+    height: Option<u64>,
+    audio_sampling_rate: Option<u64>,
+    audio_channels: Option<u64>,
+    /// Transfer characteristics named like YouTube's `colorInfo`, like `smpte2084`.
+    transfer_characteristics: Option<String>,
+    language: Option<String>,
+    is_main: bool,
+    init_range: Option<YtByteRange>,
+    index_range: Option<YtByteRange>,
+    /// `mediaPresentationDuration` in milliseconds, missing for live streams.
+    duration: Option<f64>,
+}
+
+pub trait ExtractorDashHandle {
+    /// Download the DASH manifest of a player response and turn each representation into a stream.
+    async fn extract_dash_formats(
+        &self,
+        player_response: &HashMap<String, Value>,
+        is_live: bool,
+    ) -> Result<Vec<YtStream>>;
+}
+
+impl ExtractorDashHandle for YtExtractor {
+    async fn extract_dash_formats(
+        &self,
+        player_response: &HashMap<String, Value>,
+        is_live: bool,
+    ) -> Result<Vec<YtStream>> {
+        let Some(manifest_url) = player_response
+            .get("streamingData")
+            .and_then(|sd| sd.get("dashManifestUrl"))
+            .and_then(|u| u.as_str())
+        else {
+            return Ok(vec![]);
+        };
+
+        let client = YtClient::from_str(
+            player_response
+                .get(STREAMING_DATA_CLIENT_NAME)
+                .and_then(|c| c.as_str())
+                .unwrap_or("UNKNOWN"),
+        );
+        let length_ms = player_response
+            .get("videoDetails")
+            .and_then(|vd| vd.get("lengthSeconds"))
+            .and_then(|l| l.as_str())
+            .and_then(|l| l.parse::<f64>().ok())
+            .map(|l| l * 1000.0)
+            .filter(|l| *l > 0.0);
+
+        #[cfg(feature = "logging")]
+        log::info!("Downloading {} DASH manifest", client.as_str());
+
+        let manifest_url = Url::parse(manifest_url)?;
+        let mpd = self.download_page(manifest_url.clone(), &client).await?;
+        let representations = parse_mpd(&mpd, &manifest_url)?;
+
+        let streams = representations
+            .into_iter()
+            .map(|dash| {
+                let format_duration = length_ms.or(dash.duration).unwrap_or_default();
+                let tbr = dash.representation.bandwidth as f64;
+                let is_audio = dash
+                    .mime_type
+                    .as_deref()
+                    .is_some_and(|m| m.starts_with("audio/"));
+                let (vcodec, acodec) = split_codecs(
+                    dash.mime_type.as_deref(),
+                    dash.representation.codecs.as_deref(),
+                );
+                let dynamic_range = vcodec
+                    .as_deref()
+                    .map(|vcodec| dynamic_range(vcodec, dash.transfer_characteristics.as_deref()));
+                let quality_label = match (dash.height, dash.representation.frame_rate) {
+                    (Some(height), Some(fps)) if fps > 30.0 => {
+                        format!("{}p{}", height, fps.round())
+                    }
+                    (Some(height), _) => format!("{}p", height),
+                    _ if is_audio => "audio".to_string(),
+                    _ => "dash".to_string(),
+                };
+
+                YtStream {
+                    asr: dash.audio_sampling_rate,
+                    audio_channels: dash.audio_channels,
+                    file_size: None,
+                    file_size_approx: file_size_from_tbr(tbr, format_duration),
+                    height: dash.height,
+                    width: dash.width,
+                    fps: dash.representation.frame_rate,
+                    format_duration,
+                    has_drm: false,
+                    itag: dash
+                        .representation
+                        .id
+                        .chars()
+                        .all(|c| c.is_ascii_digit())
+                        .then(|| dash.representation.id.clone()),
+                    tbr,
+                    audio_track: AudioTrackInfo {
+                        display_name: dash.language.clone(),
+                        language: dash.language,
+                        is_default: dash.is_main,
+                    },
+                    quality_label,
+                    is_drc: false,
+                    projection: None,
+                    spatial_audio: None,
+                    client,
+                    clients: vec![client],
+                    ext: dash
+                        .mime_type
+                        .as_deref()
+                        .map(mime_type_to_ext)
+                        .unwrap_or_default(),
+                    is_live,
+                    codecs: dash.representation.codecs.clone(),
+                    vcodec,
+                    acodec,
+                    loudness_db: None,
+                    color_info: dash.transfer_characteristics.clone().map(
+                        |transfer_characteristics| YtColorInfo {
+                            transfer_characteristics: Some(transfer_characteristics),
+                            ..Default::default()
+                        },
+                    ),
+                    dynamic_range,
+                    mime_type: dash.mime_type,
+                    init_range: dash.init_range,
+                    index_range: dash.index_range,
+                    last_modified: None,
+                    source: YtStreamSource::Dash(dash.representation),
+                }
+            })
+            .collect();
+
+        Ok(streams)
+    }
+}
+
+/// Representations grouped by MIME type and audio track, each with its URL.
+type AdaptationSetGroup<'a> = (&'a str, Option<&'a str>, Vec<(&'a YtStream, &'a str)>);
+
+/// Generate a static on-demand MPD from the adaptive formats of a stream response.
+pub fn generate_dash_manifest(stream_response: &YtStreamResponse) -> Result<String> {
+    // Adaptation sets split by MIME type and audio track, in the order the formats came in.
+    let mut adaptation_sets: Vec<AdaptationSetGroup> = vec![];
+    // Representation ids are itags, which have to be unique across the MPD.
+    let mut itags = HashSet::new();
+
+    for stream in &stream_response.streams {
+        let (YtStreamSource::URL(url), Some(mime_type), Some(_), Some(_)) = (
+            &stream.source,
+            stream.mime_type.as_deref(),
+            stream.init_range,
+            stream.index_range,
+        ) else {
+            continue;
+        };
+
+        if stream.is_live {
+            continue;
+        }
+        if let Some(itag) = &stream.itag
+            && !itags.insert(itag)
+        {
+            continue;
+        }
+
+        let audio_track = match mime_type.starts_with("audio/") {
+            true => stream.audio_track.display_name.as_deref(),
+            false => None,
+        };
+
+        match adaptation_sets
+            .iter_mut()
+            .find(|(m, a, _)| *m == mime_type && *a == audio_track)
+        {
+            Some((_, _, representations)) => representations.push((stream, url)),
+            None => adaptation_sets.push((mime_type, audio_track, vec![(stream, url)])),
+        }
+    }
+
+    if adaptation_sets.is_empty() {
+        bail!(
+            "None of the streams can be described by a DASH manifest. Adaptive formats with a deciphered URL are required."
+        )
+    }
+
+    let duration_ms = adaptation_sets
+        .iter()
+        .flat_map(|(_, _, representations)| representations)
+        .map(|(stream, _)| stream.format_duration)
+        .fold(0.0, f64::max);
+
+    let mut mpd = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<MPD xmlns=\"urn:mpeg:dash:schema:mpd:2011\" profiles=\"urn:mpeg:dash:profile:isoff-on-demand:2011\" type=\"static\" minBufferTime=\"PT1.500S\" mediaPresentationDuration=\"PT{:.3}S\">\n<Period>\n",
+        duration_ms / 1000.0
+    );
+
+    for (id, (mime_type, audio_track, representations)) in adaptation_sets.iter().enumerate() {
+        let content_type = mime_type.split('/').next().unwrap_or_default();
+
+        mpd.push_str(&format!(
+            "<AdaptationSet id=\"{}\" contentType=\"{}\" mimeType=\"{}\" subsegmentAlignment=\"true\" subsegmentStartsWithSAP=\"1\">\n",
+            id,
+            escape_xml(content_type),
+            escape_xml(mime_type)
+        ));
+
+        if let Some(audio_track) = audio_track {
+            mpd.push_str(&format!("<Label>{}</Label>\n", escape_xml(audio_track)));
+        }
+
+        for (index, (stream, url)) in representations.iter().enumerate() {
+            let (Some(init_range), Some(index_range)) = (stream.init_range, stream.index_range)
+            else {
+                continue;
+            };
+
+            let mut attributes = vec![
+                (
+                    "id",
+                    stream
+                        .itag
+                        .clone()
+                        .unwrap_or_else(|| format!("{}-{}", id, index)),
+                ),
+                ("bandwidth", (stream.tbr.round() as u64).to_string()),
+            ];
+
+            if let Some(codecs) = &stream.codecs {
+                attributes.push(("codecs", codecs.clone()));
+            }
+            if let Some(width) = stream.width {
+                attributes.push(("width", width.to_string()));
+            }
+            if let Some(height) = stream.height {
+                attributes.push(("height", height.to_string()));
+            }
+            if let Some(asr) = stream.asr {
+                attributes.push(("audioSamplingRate", asr.to_string()));
+            }
+
+            let attributes: String = attributes
+                .iter()
+                .map(|(key, value)| format!(" {}=\"{}\"", key, escape_xml(value)))
+                .collect();
+
+            mpd.push_str(&format!(
+                "<Representation{}>\n<BaseURL>{}</BaseURL>\n<SegmentBase indexRange=\"{}-{}\" indexRangeExact=\"true\">\n<Initialization range=\"{}-{}\"/>\n</SegmentBase>\n</Representation>\n",
+                attributes,
+                escape_xml(url),
+                index_range.start,
+                index_range.end,
+                init_range.start,
+                init_range.end
+            ));
+        }
+
+        mpd.push_str("</AdaptationSet>\n");
+    }
+
+    mpd.push_str("</Period>\n</MPD>\n");
+
+    Ok(mpd)
+}
+
+fn parse_xml(raw: &str) -> Result<XmlElement> {
+    // The bottom of the stack only collects the document element.
+    let mut stack = vec![XmlElement::default()];
+    let mut last_end = 0;
+
+    for caps in XML_TOKEN_RE.captures_iter(raw) {
+        let caps = caps?;
+        let Some(token) = caps.get(0) else {
+            continue;
+        };
+        let text = &raw[last_end..token.start()];
+        last_end = token.end();
+
+        if let Some(current) = stack.last_mut() {
+            current.text.push_str(&unescape_xml(text)?);
+
+            if let Some(cdata) = caps.name("cdata") {
+                current.text.push_str(cdata.as_str());
+            }
+        }
+
+        let Some(name) = caps.name("name") else {
+            continue;
+        };
+        // Namespace prefixes don't matter for the few elements we read.
+        let name = name.as_str().rsplit(':').next().unwrap_or_default();
+
+        if caps.name("close").is_some() {
+            let element = stack
+                .pop()
+                .filter(|element| element.name == name && !stack.is_empty())
+                .ok_or_else(|| anyhow!("Unexpected closing tag </{}> in XML.", name))?;
+
+            if let Some(parent) = stack.last_mut() {
+                parent.children.push(element);
+            }
+
+            continue;
+        }
+
+        let mut element = XmlElement {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let attrs = caps.name("attrs").map(|a| a.as_str()).unwrap_or_default();
+
+        for attr_caps in XML_ATTRIBUTE_RE.captures_iter(attrs) {
+            let attr_caps = attr_caps?;
+            let (Some(key), Some(value)) = (
+                attr_caps.name("key"),
+                attr_caps.name("dq").or_else(|| attr_caps.name("sq")),
+            ) else {
+                continue;
+            };
+
+            element
+                .attributes
+                .insert(key.as_str().to_string(), unescape_xml(value.as_str())?);
+        }
+
+        match caps.name("self_closing") {
+            Some(_) => {
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(element);
+                }
+            }
+            None => stack.push(element),
+        }
+    }
+
+    if stack.len() != 1 {
+        bail!(
+            "Unclosed <{}> tag in XML.",
+            stack.last().map(|e| e.name.as_str()).unwrap_or_default()
+        )
+    }
+
+    stack
+        .pop()
+        .and_then(|document| document.children.into_iter().next())
+        .ok_or_else(|| anyhow!("The XML document is empty."))
+}
+
+/// Parse `xs:duration` values like `PT1H2M3.5S` into milliseconds.
+fn parse_xs_duration(duration: &str) -> Option<f64> {
+    let time = duration.trim().strip_prefix('P')?;
+    let (days, time) = match time.split_once('T') {
+        Some((days, time)) => (days, time),
+        None => (time, ""),
+    };
+    let days: f64 = match days.strip_suffix('D') {
+        Some(d) => d.parse().ok()?,
+        None if days.is_empty() => 0.0,
+        None => return None,
+    };
+
+    let mut seconds = days * 86_400.0;
+    let mut number = String::new();
+
+    for c in time.chars() {
+        match c {
+            'H' => seconds += number.parse::<f64>().ok()? * 3600.0,
+            'M' => seconds += number.parse::<f64>().ok()? * 60.0,
+            'S' => seconds += number.parse::<f64>().ok()?,
+            _ => {
+                number.push(c);
+                continue;
+            }
+        }
+
+        number.clear();
+    }
+
+    Some(seconds * 1000.0)
+}
+
+fn parse_byte_range(range: &str) -> Option<YtByteRange> {
+    let (start, end) = range.split_once('-')?;
+
+    Some(YtByteRange {
+        start: start.trim().parse().ok()?,
+        end: end.trim().parse().ok()?,
+    })
+}
+
+/// Frame rates are either plain numbers or fractions like `30000/1001`.
+fn parse_frame_rate(frame_rate: &str) -> Option<f64> {
+    match frame_rate.split_once('/') {
+        Some((numerator, denominator)) => {
+            let denominator: f64 = denominator.parse().ok()?;
+            (denominator != 0.0).then_some(numerator.parse::<f64>().ok()? / denominator)
+        }
+        None => frame_rate.parse().ok(),
+    }
+}
+
+/// Resolve the `BaseURL` of an element against the URL inherited from its parent.
+fn resolve_base_url(element: &XmlElement, parent_url: &Url) -> Url {
+    element
+        .child("BaseURL")
+        .and_then(|base| parent_url.join(base.text.trim()).ok())
+        .unwrap_or_else(|| parent_url.clone())
+}
+
+/// Fill in `$RepresentationID$`, `$Bandwidth$`, `$Number$` and `$Time$`, including `%0Nd` widths.
+fn fill_template(
+    template: &str,
+    representation_id: &str,
+    bandwidth: u64,
+    number: u64,
+    time: u64,
+) -> Result<String> {
+    let filled = TEMPLATE_IDENTIFIER_RE.replace_all(template, |caps: &Captures| {
+        let width: usize = caps
+            .name("width")
+            .and_then(|w| w.as_str().parse().ok())
+            .unwrap_or_default();
+
+        match caps.name("id").map(|id| id.as_str()) {
+            Some("RepresentationID") => representation_id.to_string(),
+            Some("Bandwidth") => format!("{:0width$}", bandwidth, width = width),
+            Some("Number") => format!("{:0width$}", number, width = width),
+            _ => format!("{:0width$}", time, width = width),
+        }
+    });
+
+    Ok(filled.replace("$$", "$"))
+}
+
+fn parse_mpd(raw: &str, manifest_url: &Url) -> Result<Vec<DashRepresentation>> {
+    let mpd = parse_xml(raw)?;
+
+    if mpd.name != "MPD" {
+        bail!("The DASH manifest is not an MPD document.")
+    }
+
+    let duration = mpd
+        .attr("mediaPresentationDuration")
+        .and_then(parse_xs_duration);
+    let mpd_url = resolve_base_url(&mpd, manifest_url);
+
+    let mut representations = vec![];
+
+    for period in mpd.children_named("Period") {
+        let period_url = resolve_base_url(period, &mpd_url);
+
+        for adaptation_set in period.children_named("AdaptationSet") {
+            let set_url = resolve_base_url(adaptation_set, &period_url);
+            let is_main = adaptation_set
+                .children_named("Role")
+                .any(|role| role.attr("value") == Some("main"));
+
+            for representation in adaptation_set.children_named("Representation") {
+                let inherited = |name: &str| {
+                    representation
+                        .attr(name)
+                        .or_else(|| adaptation_set.attr(name))
+                        .map(String::from)
+                };
+                let inherited_number =
+                    |name: &str| inherited(name).and_then(|n| n.parse::<u64>().ok());
+                // Segment information can sit on the representation or be shared by the whole set.
+                let segment_info = |name: &str| {
+                    representation
+                        .child(name)
+                        .or_else(|| adaptation_set.child(name))
+                };
+
+                let Some(id) = representation.attr("id") else {
+                    continue;
+                };
+                let bandwidth = inherited_number("bandwidth").unwrap_or_default();
+                let base_url = resolve_base_url(representation, &set_url);
+
+                let mut initialization_url = None;
+                let mut segment_urls = vec![];
+                let mut init_range = None;
+                let mut index_range = None;
+
+                if let Some(segment_base) = segment_info("SegmentBase") {
+                    index_range = segment_base.attr("indexRange").and_then(parse_byte_range);
+                    init_range = segment_base
+                        .child("Initialization")
+                        .and_then(|init| init.attr("range"))
+                        .and_then(parse_byte_range);
+                }
+
+                if let Some(segment_list) = segment_info("SegmentList") {
+                    initialization_url = segment_list
+                        .child("Initialization")
+                        .and_then(|init| init.attr("sourceURL"))
+                        .and_then(|source| base_url.join(source).ok())
+                        .map(String::from);
+                    segment_urls = segment_list
+                        .children_named("SegmentURL")
+                        .filter_map(|segment| segment.attr("media"))
+                        .filter_map(|media| base_url.join(media).ok())
+                        .map(String::from)
+                        .collect();
+                }
+
+                if let Some(segment_template) = segment_info("SegmentTemplate") {
+                    if let Some(initialization) = segment_template.attr("initialization") {
+                        initialization_url = Some(
+                            base_url
+                                .join(&fill_template(initialization, id, bandwidth, 0, 0)?)?
+                                .to_string(),
+                        );
+                    }
+
+                    if let Some(media) = segment_template.attr("media") {
+                        let template_number = |name: &str, default: u64| {
+                            segment_template
+                                .attr(name)
+                                .and_then(|n| n.parse::<u64>().ok())
+                                .unwrap_or(default)
+                        };
+                        let mut number = template_number("startNumber", 1);
+                        let timescale = template_number("timescale", 1).max(1);
+                        let timeline: Vec<&XmlElement> = segment_template
+                            .child("SegmentTimeline")
+                            .map(|timeline| timeline.children_named("S").collect())
+                            .unwrap_or_default();
+
+                        if !timeline.is_empty() {
+                            let mut time = 0;
+
+                            for segment in timeline {
+                                let attr = |name: &str| {
+                                    segment.attr(name).and_then(|n| n.parse::<i64>().ok())
+                                };
+                                let segment_duration = attr("d").unwrap_or_default().max(0) as u64;
+                                time = attr("t").map(|t| t.max(0) as u64).unwrap_or(time);
+
+                                for _ in 0..=attr("r").unwrap_or_default().max(0) {
+                                    segment_urls.push(
+                                        base_url
+                                            .join(&fill_template(
+                                                media, id, bandwidth, number, time,
+                                            )?)?
+                                            .to_string(),
+                                    );
+                                    number += 1;
+                                    time += segment_duration;
+                                }
+                            }
+                        } else if let Some(total_ms) = duration {
+                            let segment_duration = template_number("duration", 0);
+
+                            if segment_duration > 0 {
+                                let segment_ms =
+                                    segment_duration as f64 * 1000.0 / timescale as f64;
+                                let count = (total_ms / segment_ms).ceil() as u64;
+
+                                for index in 0..count {
+                                    segment_urls.push(
+                                        base_url
+                                            .join(&fill_template(
+                                                media,
+                                                id,
+                                                bandwidth,
+                                                number + index,
+                                                index * segment_duration,
+                                            )?)?
+                                            .to_string(),
+                                    );
+                                }
+                            }
+                        }
+                    }
+                }
+
+                representations.push(DashRepresentation {
+                    representation: YtDashRepresentation {
+                        manifest_url: manifest_url.to_string(),
+                        id: id.to_string(),
+                        base_url: base_url.to_string(),
+                        bandwidth,
+                        codecs: inherited("codecs"),
+                        frame_rate: inherited("frameRate").and_then(|f| parse_frame_rate(&f)),
+                        initialization_url,
+                        segment_urls,
+                    },
+                    mime_type: inherited("mimeType"),
+                    width: inherited_number("width"),
+                    height: inherited_number("height"),
+                    audio_sampling_rate: inherited_number("audioSamplingRate"),
+                    audio_channels: representation
+                        .child("AudioChannelConfiguration")
+                        .or_else(|| adaptation_set.child("AudioChannelConfiguration"))
+                        .and_then(|config| config.attr("value"))
+                        .and_then(|channels| channels.parse().ok()),
+                    transfer_characteristics: representation
+                        .children
+                        .iter()
+                        .chain(&adaptation_set.children)
+                        .filter(|property| {
+                            property.name == "SupplementalProperty"
+                                || property.name == "EssentialProperty"
+                        })
+                        .find(|property| {
+                            property.attr("schemeIdUri")
+                                == Some("urn:mpeg:mpegB:cicp:TransferCharacteristics")
+                        })
+                        .and_then(|property| match property.attr("value")? {
+                            "1" => Some("bt709".to_string()),
+                            "16" => Some("smpte2084".to_string()),
+                            "18" => Some("arib_std_b67".to_string()),
+                            _ => None,
+                        }),
+                    language: inherited("lang"),
+                    is_main,
+                    init_range,
+                    index_range,
+                    duration,
+                });
+            }
+        }
+    }
+
+    Ok(representations)
+}
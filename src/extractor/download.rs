@@ -38,6 +38,8 @@ pub trait ExtractorDownloadHandle {
         webpage_client: &YtClient,
         video_id: &VideoId,
     ) -> Result<String>;
+    /// Download any YouTube page with the client's user agent, cookies and forwarded address.
+    async fn download_page(&self, page_url: Url, webpage_client: &YtClient) -> Result<String>;
 }
 
 impl ExtractorDownloadHandle for YtExtractor {
@@ -117,11 +119,18 @@ impl ExtractorDownloadHandle for YtExtractor {
     ) -> Result<String> {
         #[cfg(feature = "logging")]
         log::info!("{}: Downloading webpage {}", video_id.as_str(), webpage_url);
-        let mut webpage_request = self.http_client.get(webpage_url).query(&[
+        let mut page_url = webpage_url;
+        page_url.query_pairs_mut().extend_pairs([
             ("bpctr", "9999999999"),
             ("has_verified", "1"),
             ("v", video_id.as_str()),
         ]);
+
+        self.download_page(page_url, webpage_client).await
+    }
+
+    async fn download_page(&self, page_url: Url, webpage_client: &YtClient) -> Result<String> {
+        let mut webpage_request = self.http_client.get(page_url);
         let innertube_client = INNERTUBE_CLIENTS.get(webpage_client).unwrap();
 
         let client = innertube_client.innertube_context.get("client").unwrap();
@@ -12,13 +12,16 @@ use crate::{
     cache::CacheStore,
     cookies::CookieJar,
     extractor::{
-        auth::ExtractorAuthHandle, client::INNERTUBE_CLIENTS, download::ExtractorDownloadHandle,
-        json::ExtractorJsonHandle, player::ExtractorPlayerHandle, ytcfg::ExtractorYtCfgHandle,
+        auth::ExtractorAuthHandle, chapters::ExtractorChaptersHandle, client::INNERTUBE_CLIENTS,
+        dash::ExtractorDashHandle, download::ExtractorDownloadHandle,
+        formats::ExtractorFormatsHandle, hls::ExtractorHlsHandle, json::ExtractorJsonHandle,
+        live::ExtractorLiveHandle, player::ExtractorPlayerHandle, ytcfg::ExtractorYtCfgHandle,
     },
-    utils::{file_size_from_tbr, mime_type_to_ext},
+    utils::{dynamic_range, file_size_from_tbr, mime_type_to_ext, split_codecs},
     yt_interface::{
-        VideoId, YtAgeLimit, YtChannel, YtClient, YtManifest, YtMediaType, YtStream,
-        YtStreamResponse, YtStreamSource, YtThumbnail, YtVideoInfo,
+        VideoId, YtAgeLimit, YtByteRange, YtChannel, YtClient, YtColorInfo, YtLiveStatus,
+        YtManifest, YtMediaType, YtStream, YtStreamResponse, YtStreamSource, YtThumbnail,
+        YtVideoInfo,
     },
 };
 
@@ -59,7 +62,7 @@ pub trait InfoExtractor {
         webpage_url: &str,
         webpage_client: &YtClient,
         video_id: &VideoId,
-    ) -> Result<(Vec<HashMap<String, Value>>, String)>;
+    ) -> Result<YtManifest>;
 }
 
 impl YtExtractor {
@@ -235,16 +238,8 @@ impl InfoExtractor for YtExtractor {
             }
 
             for fmt in all_formats {
-                let target_duration_sec = fmt.get("targetDurationSec");
-
-                // Skip livestream.
-                if target_duration_sec.is_some() {
-                    #[cfg(feature = "logging")]
-                    log::info!(
-                        "Skipped a format. Found livestream because livestreams are not supported."
-                    );
-                    continue;
-                }
+                // Only the segment-based formats of live streams carry a target segment duration.
+                let is_live = fmt.get("targetDurationSec").is_some();
 
                 let audio_track = fmt
                     .get("audioTrack")
@@ -253,11 +248,12 @@ impl InfoExtractor for YtExtractor {
                     .cloned()
                     .unwrap_or(Map::new());
 
-                let itag = fmt
-                    .get("itag")
-                    .unwrap_or_default()
-                    .as_str()
-                    .and_then(|s| Some(s.to_string()));
+                // The itag is a number in player responses.
+                let itag = fmt.get("itag").and_then(|i| match i {
+                    Value::Number(n) => Some(n.to_string()),
+                    Value::String(s) => Some(s.clone()),
+                    _ => None,
+                });
 
                 let mut quality = fmt
                     .get("quality")
@@ -320,7 +316,11 @@ impl InfoExtractor for YtExtractor {
 
                 let format_duration = fmt
                     .get("approxDurationMs")
-                    .and_then(|d| d.as_f64())
+                    // InnerTube sends it as a string.
+                    .and_then(|d| match d.as_str() {
+                        Some(d) => d.parse().ok(),
+                        None => d.as_f64(),
+                    })
                     .unwrap_or_default();
 
                 let tbr = fmt
@@ -345,6 +345,14 @@ impl InfoExtractor for YtExtractor {
                     .and_then(|v| v.as_str())
                     .map(|s| s.to_string());
 
+                // Track IDs look like `en.4` or `pt-BR.3`.
+                let audio_language = audio_track
+                    .get("id")
+                    .and_then(|v| v.as_str())
+                    .and_then(|id| id.split('.').next())
+                    .filter(|language| !language.is_empty())
+                    .map(|language| language.to_string());
+
                 let is_default = audio_track
                     .get("audioIsDefault")
                     .and_then(|v| v.as_bool())
@@ -362,6 +370,64 @@ impl InfoExtractor for YtExtractor {
 
                 let re = Regex::new(r#"((?:[^/]+)/(?:[^;]+))(?:;\s*codecs="([^"]+)")?"#)?;
 
+                let mime_captures = re.captures(
+                    fmt.get("mimeType")
+                        .unwrap_or_default()
+                        .as_str()
+                        .unwrap_or_default(),
+                )?;
+                let mime_type = mime_captures
+                    .as_ref()
+                    .and_then(|caps| caps.get(1))
+                    .map(|m| m.as_str().to_string());
+                let codecs = mime_captures
+                    .as_ref()
+                    .and_then(|caps| caps.get(2))
+                    .map(|m| m.as_str().to_string());
+                let (vcodec, acodec) = split_codecs(mime_type.as_deref(), codecs.as_deref());
+                let color_info = fmt.get("colorInfo").map(|color_info| {
+                    let color_value = |key: &str, prefix: &str| {
+                        color_info
+                            .get(key)
+                            .and_then(|v| v.as_str())
+                            .map(|v| v.trim_start_matches(prefix).to_lowercase())
+                    };
+
+                    YtColorInfo {
+                        primaries: color_value("primaries", "COLOR_PRIMARIES_"),
+                        // YouTube spells PQ `SMPTEST2084`, the standard name is `smpte2084`.
+                        transfer_characteristics: color_value(
+                            "transferCharacteristics",
+                            "COLOR_TRANSFER_CHARACTERISTICS_",
+                        )
+                        .map(|transfer| match transfer.as_str() {
+                            "smptest2084" => "smpte2084".to_string(),
+                            _ => transfer,
+                        }),
+                        matrix_coefficients: color_value(
+                            "matrixCoefficients",
+                            "COLOR_MATRIX_COEFFICIENTS_",
+                        ),
+                    }
+                });
+                let dynamic_range = vcodec.as_deref().map(|vcodec| {
+                    dynamic_range(
+                        vcodec,
+                        color_info
+                            .as_ref()
+                            .and_then(|c| c.transfer_characteristics.as_deref()),
+                    )
+                });
+                let byte_range = |key: &str| {
+                    let range = fmt.get(key)?;
+                    let bound = |bound: &str| range.get(bound)?.as_str()?.parse().ok();
+
+                    Some(YtByteRange {
+                        start: bound("start")?,
+                        end: bound("end")?,
+                    })
+                };
+
                 let ext: Ext = match re.captures(
                     fmt.get("mimeType")
                         .unwrap_or_default()
@@ -380,12 +446,14 @@ impl InfoExtractor for YtExtractor {
 
                 streams.push(YtStream {
                     asr: fmt.get("audioSampleRate").and_then(|v| v.as_u64()),
+                    audio_channels: fmt.get("audioChannels").and_then(|v| v.as_u64()),
                     file_size: fmt
                         .get("contentLength")
                         .and_then(|v| v.as_str().and_then(|s| s.parse().ok())),
                     file_size_approx: file_size_from_tbr(tbr, format_duration),
                     height: fmt.get("height").and_then(|h| h.as_u64()),
                     width: fmt.get("width").and_then(|w| w.as_u64()),
+                    fps: fmt.get("fps").and_then(|f| f.as_f64()),
                     format_duration,
                     has_drm,
                     itag,
@@ -394,16 +462,32 @@ impl InfoExtractor for YtExtractor {
                     quality_label: name,
                     audio_track: AudioTrackInfo {
                         display_name: audio_display,
+                        language: audio_language,
                         is_default,
                     },
                     projection,
                     spatial_audio,
                     client: YtClient::from_str(client_name),
+                    clients: vec![YtClient::from_str(client_name)],
                     is_drc: fmt
                         .get("isDrc")
                         .and_then(|dr| dr.as_bool())
                         .unwrap_or_default(),
                     ext,
+                    is_live,
+                    mime_type,
+                    codecs,
+                    vcodec,
+                    acodec,
+                    loudness_db: fmt.get("loudnessDb").and_then(|l| l.as_f64()),
+                    color_info,
+                    dynamic_range,
+                    init_range: byte_range("initRange"),
+                    index_range: byte_range("indexRange"),
+                    last_modified: fmt
+                        .get("lastModified")
+                        .and_then(|l| l.as_str())
+                        .and_then(|l| l.parse().ok()),
                 });
             }
         }
@@ -581,6 +665,9 @@ impl InfoExtractor for YtExtractor {
                 thumbnails: extracted_thumbnails,
                 age_limit: extracted_age_limit.unwrap_or_default(),
                 media_type: extracted_media_type.unwrap_or_default(),
+                chapters: vec![],
+                live_status: YtLiveStatus::default(),
+                scheduled_start_time: None,
             });
         }
 
@@ -594,7 +681,7 @@ impl InfoExtractor for YtExtractor {
         webpage_url: &str,
         webpage_client: &YtClient,
         video_id: &VideoId,
-    ) -> Result<(Vec<HashMap<String, Value>>, String)> {
+    ) -> Result<YtManifest> {
         let webpage = self
             .download_webpage(webpage_url, webpage_client, video_id)
             .await?;
@@ -613,11 +700,11 @@ impl InfoExtractor for YtExtractor {
 
         let is_premium_subscriber = self.is_premium_subscriber(&initial_data)?;
         let clients = self.get_clients(is_premium_subscriber)?;
-        let player_responses = self
+        let (player_responses, player_url) = self
             .extract_player_responses(&clients, video_id, &webpage, webpage_client, &webpage_ytcfg)
             .await?;
 
-        Ok(player_responses)
+        Ok(YtManifest::new(player_responses, player_url).with_initial_data(initial_data))
     }
 
     fn http_scheme(&self) -> &str {
@@ -629,38 +716,86 @@ impl InfoExtractor for YtExtractor {
 
     async fn extract_manifest(&self, video_id: &VideoId) -> Result<YtManifest> {
         let webpage_url = format!("{}://www.youtube.com/watch", self.http_scheme());
-        let (initial_extracted_data, player_url) =
-            self.extract(&webpage_url, &YtClient::Web, video_id).await?;
-
-        Ok(YtManifest::new(initial_extracted_data, player_url))
+        self.extract(&webpage_url, &YtClient::Web, video_id).await
     }
 
     async fn extract_streams(&self, video_id: &VideoId) -> Result<YtStreamResponse> {
         let yt_manifest = self.extract_manifest(video_id).await?;
-
-        let formats = self.extract_formats(yt_manifest.extracted_manifest)?;
-        let stream_response = YtStreamResponse::new(yt_manifest.player_url, formats);
-
-        Ok(stream_response)
+        self.extract_streams_from_manifest(&yt_manifest).await
     }
 
     async fn extract_streams_from_manifest(
         &self,
         manifest: &YtManifest,
     ) -> Result<YtStreamResponse> {
-        let formats = self.extract_formats(manifest.extracted_manifest.clone())?;
-        Ok(YtStreamResponse::new(manifest.player_url.clone(), formats))
+        let mut formats = self.extract_formats(manifest.extracted_manifest.clone())?;
+        let live_status = self.extract_live_status(&manifest.extracted_manifest);
+
+        let is_live = live_status == YtLiveStatus::Live;
+        let mut seen_manifests = HashSet::new();
+
+        for player_response in &manifest.extracted_manifest {
+            let manifest_url = |key: &str| {
+                player_response
+                    .get("streamingData")
+                    .and_then(|sd| sd.get(key))
+                    .and_then(|u| u.as_str())
+            };
+
+            // A broken manifest shouldn't cost the formats we already have.
+            if let Some(hls_manifest_url) = manifest_url("hlsManifestUrl")
+                && !self.tydle_options.skip_hls
+                && seen_manifests.insert(hls_manifest_url)
+            {
+                match self.extract_hls_formats(player_response, is_live).await {
+                    Ok(hls_formats) => formats.extend(hls_formats),
+                    Err(_e) => {
+                        #[cfg(feature = "logging")]
+                        log::warn!("Skipping HLS formats: {}", _e);
+                    }
+                }
+            }
+
+            if let Some(dash_manifest_url) = manifest_url("dashManifestUrl")
+                && !self.tydle_options.skip_dash
+                && seen_manifests.insert(dash_manifest_url)
+            {
+                match self.extract_dash_formats(player_response, is_live).await {
+                    Ok(dash_formats) => formats.extend(dash_formats),
+                    Err(_e) => {
+                        #[cfg(feature = "logging")]
+                        log::warn!("Skipping DASH formats: {}", _e);
+                    }
+                }
+            }
+        }
+
+        if !self.tydle_options.keep_duplicate_formats {
+            formats = self.merge_duplicate_formats(formats);
+        }
+
+        let mut stream_response = YtStreamResponse::new(manifest.player_url.clone(), formats);
+        self.extract_live_streaming_data(&manifest.extracted_manifest, &mut stream_response);
+
+        Ok(stream_response)
     }
 
     async fn extract_video_info(&self, video_id: &VideoId) -> Result<YtVideoInfo> {
         let yt_manifest = self.extract_manifest(video_id).await?;
-
-        let yt_video_info = self.extract_metadata(yt_manifest.extracted_manifest)?;
-        Ok(yt_video_info)
+        self.extract_video_info_from_manifest(&yt_manifest).await
     }
 
     async fn extract_video_info_from_manifest(&self, manifest: &YtManifest) -> Result<YtVideoInfo> {
-        let yt_video_info = self.extract_metadata(manifest.extracted_manifest.clone())?;
+        let mut yt_video_info = self.extract_metadata(manifest.extracted_manifest.clone())?;
+        yt_video_info.chapters = self.extract_chapters(
+            &manifest.initial_data,
+            yt_video_info.duration,
+            &yt_video_info.description,
+        );
+        yt_video_info.live_status = self.extract_live_status(&manifest.extracted_manifest);
+        yt_video_info.scheduled_start_time =
+            self.extract_scheduled_start_time(&manifest.extracted_manifest);
+
         Ok(yt_video_info)
     }
 }
@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+use crate::{
+    extractor::{client::client_rank, extract::YtExtractor},
+    yt_interface::{YtStream, YtStreamSource},
+};
+
+pub trait ExtractorFormatsHandle {
+    /// Merge the formats several clients returned for the same itag and audio track into one,
+    /// keeping the source of the preferred client and recording every client that offered it.
+    fn merge_duplicate_formats(&self, streams: Vec<YtStream>) -> Vec<YtStream>;
+}
+
+impl ExtractorFormatsHandle for YtExtractor {
+    fn merge_duplicate_formats(&self, streams: Vec<YtStream>) -> Vec<YtStream> {
+        let mut merged: Vec<YtStream> = vec![];
+        let mut positions = HashMap::new();
+
+        for stream in streams {
+            // Formats without an itag can't be matched up with the ones of other clients.
+            let Some(itag) = stream.itag.clone() else {
+                merged.push(stream);
+                continue;
+            };
+
+            // DRC audio shares its itag with the regular format, and manifests reuse itags of adaptive formats.
+            // Signed and unsigned URLs are the same HTTPS format, only HLS and DASH are told apart.
+            let protocol = match stream.source {
+                YtStreamSource::URL(_) | YtStreamSource::Signature(_) => "https",
+                YtStreamSource::Hls(_) => "m3u8",
+                YtStreamSource::Dash(_) => "dash",
+            };
+            let key = (
+                itag,
+                stream.audio_track.language.clone(),
+                stream.audio_track.display_name.clone(),
+                stream.is_drc,
+                protocol,
+            );
+
+            let Some(&position) = positions.get(&key) else {
+                positions.insert(key, merged.len());
+                merged.push(stream);
+                continue;
+            };
+
+            let kept: &mut YtStream = &mut merged[position];
+            let mut clients = std::mem::take(&mut kept.clients);
+
+            for client in &stream.clients {
+                if !clients.contains(client) {
+                    clients.push(*client);
+                }
+            }
+
+            // Ties keep the client that was queried first.
+            let client_priority = &self.tydle_options.client_priority;
+            if client_rank(&stream.client, client_priority)
+                < client_rank(&kept.client, client_priority)
+            {
+                *kept = stream;
+            }
+
+            kept.clients = clients;
+        }
+
+        merged
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+
+use anyhow::{Result, bail};
+use fancy_regex::Regex;
+use reqwest::Url;
+use serde_json::Value;
+
+use crate::{
+    AudioTrackInfo, Ext, STREAMING_DATA_CLIENT_NAME,
+    extractor::{download::ExtractorDownloadHandle, extract::YtExtractor},
+    utils::{dynamic_range, file_size_from_tbr, split_codecs},
+    yt_interface::{YtClient, YtColorInfo, YtHlsVariant, YtStream, YtStreamSource},
+};
+
+/// An `#EXT-X-MEDIA` audio rendition of a master playlist.
+struct HlsAudioRendition {
+    group_id: String,
+    name: Option<String>,
+    language: Option<String>,
+    /// The leading channel count of `CHANNELS`, like `2` or `6/JOC`.
+    channels: Option<u64>,
+    is_default: bool,
+}
+
+/// An `#EXT-X-STREAM-INF` variant of a master playlist.
+struct HlsVariant {
+    variant: YtHlsVariant,
+    width: Option<u64>,
+    height: Option<u64>,
+}
+
+pub trait ExtractorHlsHandle {
+    /// Download the HLS master playlist of a player response and turn each variant into a stream.
+    async fn extract_hls_formats(
+        &self,
+        player_response: &HashMap<String, Value>,
+        is_live: bool,
+    ) -> Result<Vec<YtStream>>;
+}
+
+impl ExtractorHlsHandle for YtExtractor {
+    async fn extract_hls_formats(
+        &self,
+        player_response: &HashMap<String, Value>,
+        is_live: bool,
+    ) -> Result<Vec<YtStream>> {
+        let Some(manifest_url) = player_response
+            .get("streamingData")
+            .and_then(|sd| sd.get("hlsManifestUrl"))
+            .and_then(|u| u.as_str())
+        else {
+            return Ok(vec![]);
+        };
+
+        let client = YtClient::from_str(
+            player_response
+                .get(STREAMING_DATA_CLIENT_NAME)
+                .and_then(|c| c.as_str())
+                .unwrap_or("UNKNOWN"),
+        );
+        let format_duration = player_response
+            .get("videoDetails")
+            .and_then(|vd| vd.get("lengthSeconds"))
+            .and_then(|l| l.as_str())
+            .and_then(|l| l.parse::<f64>().ok())
+            .unwrap_or_default()
+            * 1000.0;
+
+        #[cfg(feature = "logging")]
+        log::info!("Downloading {} HLS master playlist", client.as_str());
+
+        let manifest_url = Url::parse(manifest_url)?;
+        let master_playlist = self.download_page(manifest_url.clone(), &client).await?;
+
+        if !master_playlist.trim_start().starts_with("#EXTM3U") {
+            bail!(
+                "The HLS manifest returned by the {} client is not an M3U8 playlist.",
+                client.as_str()
+            )
+        }
+
+        let (variants, audio_renditions) = parse_master_playlist(&master_playlist, &manifest_url)?;
+        let itag_re = Regex::new(r"/itag/(\d+)/")?;
+
+        let mut streams = vec![];
+
+        for HlsVariant {
+            variant,
+            width,
+            height,
+        } in variants
+        {
+            let itag = itag_re
+                .captures(&variant.url)?
+                .and_then(|caps| caps.get(1))
+                .map(|m| m.as_str().to_string());
+            let audio_rendition = variant.audio_group.as_ref().and_then(|group_id| {
+                audio_renditions
+                    .iter()
+                    .filter(|rendition| &rendition.group_id == group_id)
+                    .max_by_key(|rendition| rendition.is_default)
+            });
+            let quality_label = match (height, variant.frame_rate) {
+                (Some(height), Some(fps)) if fps > 30.0 => format!("{}p{}", height, fps.round()),
+                (Some(height), _) => format!("{}p", height),
+                _ => "hls".to_string(),
+            };
+            let tbr = variant.average_bandwidth.unwrap_or(variant.bandwidth) as f64;
+            let (vcodec, acodec) = split_codecs(None, variant.codecs.as_deref());
+            // `VIDEO-RANGE` names the transfer function, PQ for HDR10 and HLG.
+            let transfer_characteristics = match variant.video_range.as_deref() {
+                Some("PQ") => Some("smpte2084"),
+                Some("HLG") => Some("arib_std_b67"),
+                _ => None,
+            };
+            let dynamic_range = vcodec
+                .as_deref()
+                .map(|vcodec| dynamic_range(vcodec, transfer_characteristics));
+
+            streams.push(YtStream {
+                asr: None,
+                audio_channels: audio_rendition.and_then(|rendition| rendition.channels),
+                file_size: None,
+                file_size_approx: file_size_from_tbr(tbr, format_duration),
+                height,
+                width,
+                fps: variant.frame_rate,
+                format_duration,
+                has_drm: false,
+                itag,
+                tbr,
+                audio_track: AudioTrackInfo {
+                    display_name: audio_rendition.and_then(|rendition| rendition.name.clone()),
+                    language: audio_rendition.and_then(|rendition| rendition.language.clone()),
+                    is_default: audio_rendition.is_some_and(|rendition| rendition.is_default),
+                },
+                quality_label,
+                is_drc: false,
+                projection: None,
+                spatial_audio: None,
+                client,
+                clients: vec![client],
+                ext: Ext::Mp4,
+                is_live,
+                mime_type: None,
+                codecs: variant.codecs.clone(),
+                vcodec,
+                acodec,
+                loudness_db: None,
+                color_info: transfer_characteristics.map(|transfer_characteristics| YtColorInfo {
+                    transfer_characteristics: Some(transfer_characteristics.to_string()),
+                    ..Default::default()
+                }),
+                dynamic_range,
+                init_range: None,
+                index_range: None,
+                last_modified: None,
+                source: YtStreamSource::Hls(variant),
+            });
+        }
+
+        Ok(streams)
+    }
+}
+
+/// Split an M3U8 attribute list like `BANDWIDTH=1280000,CODECS="avc1.4d401f,mp4a.40.2"`,
+/// keeping commas inside quoted values.
+fn parse_attribute_list(attributes: &str) -> HashMap<String, String> {
+    let mut parsed = HashMap::new();
+    let mut rest = attributes.trim();
+
+    while let Some((key, after_key)) = rest.split_once('=') {
+        let (value, after_value) = match after_key.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"').unwrap_or(quoted.len());
+                let after = quoted.get(end + 1..).unwrap_or_default();
+                (&quoted[..end], after)
+            }
+            None => {
+                let end = after_key.find(',').unwrap_or(after_key.len());
+                (&after_key[..end], &after_key[end..])
+            }
+        };
+
+        parsed.insert(key.trim().to_uppercase(), value.to_string());
+        rest = after_value.trim_start_matches(',').trim_start();
+    }
+
+    parsed
+}
+
+fn parse_master_playlist(
+    playlist: &str,
+    manifest_url: &Url,
+) -> Result<(Vec<HlsVariant>, Vec<HlsAudioRendition>)> {
+    let mut variants = vec![];
+    let mut audio_renditions = vec![];
+    let mut pending_attributes: Option<HashMap<String, String>> = None;
+
+    for line in playlist.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Some(attributes) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+            pending_attributes = Some(parse_attribute_list(attributes));
+            continue;
+        }
+
+        if let Some(attributes) = line.strip_prefix("#EXT-X-MEDIA:") {
+            let attributes = parse_attribute_list(attributes);
+
+            if attributes.get("TYPE").map(String::as_str) != Some("AUDIO") {
+                continue;
+            }
+
+            let Some(group_id) = attributes.get("GROUP-ID") else {
+                continue;
+            };
+
+            audio_renditions.push(HlsAudioRendition {
+                group_id: group_id.clone(),
+                name: attributes.get("NAME").cloned(),
+                language: attributes.get("LANGUAGE").cloned(),
+                channels: attributes
+                    .get("CHANNELS")
+                    .and_then(|channels| channels.split('/').next())
+                    .and_then(|channels| channels.parse().ok()),
+                is_default: attributes.get("DEFAULT").map(String::as_str) == Some("YES"),
+            });
+            continue;
+        }
+
+        if line.starts_with('#') {
+            continue;
+        }
+
+        // Any other line is the URI of the variant described by the preceding tag.
+        let Some(attributes) = pending_attributes.take() else {
+            continue;
+        };
+        let Some(bandwidth) = attributes.get("BANDWIDTH").and_then(|b| b.parse().ok()) else {
+            continue;
+        };
+        let (width, height) = match attributes
+            .get("RESOLUTION")
+            .and_then(|r| r.split_once(['x', 'X']))
+        {
+            Some((width, height)) => (width.parse().ok(), height.parse().ok()),
+            None => (None, None),
+        };
+
+        variants.push(HlsVariant {
+            variant: YtHlsVariant {
+                url: manifest_url.join(line)?.to_string(),
+                bandwidth,
+                average_bandwidth: attributes
+                    .get("AVERAGE-BANDWIDTH")
+                    .and_then(|b| b.parse().ok()),
+                codecs: attributes.get("CODECS").cloned(),
+                frame_rate: attributes.get("FRAME-RATE").and_then(|f| f.parse().ok()),
+                video_range: attributes.get("VIDEO-RANGE").cloned(),
+                audio_group: attributes.get("AUDIO").cloned(),
+            },
+            width,
+            height,
+        });
+    }
+
+    Ok((variants, audio_renditions))
+}
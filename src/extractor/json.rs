@@ -4,10 +4,23 @@ use anyhow::{Result, anyhow};
 use fancy_regex::Regex;
 use serde_json::Value;
 
-use crate::extractor::extract::YtExtractor;
+use crate::{
+    extractor::extract::YtExtractor,
+    yt_interface::{YtChannel, YtThumbnail},
+};
 
 pub trait ExtractorJsonHandle {
     fn find_key(&self, value: &Value, target: &str) -> Option<String>;
+    /// Collect every object stored under `renderer_key`, in document order.
+    fn find_renderers<'v>(&self, value: &'v Value, renderer_key: &str) -> Vec<&'v Value>;
+    /// Token of the first `continuationItemRenderer`, used to request the next page of a list.
+    fn find_continuation_token(&self, value: &Value) -> Option<String>;
+    /// Parse a `{ "thumbnails": [...] }` object, or the `{ "sources": [...] }` images of view models.
+    fn parse_thumbnails(&self, thumbnail: &Value) -> Vec<YtThumbnail>;
+    /// Build the channel linked from a text object's runs, like a video's `shortBylineText`.
+    fn extract_channel_from_runs(&self, text: &Value) -> Option<YtChannel>;
+    /// Text of the first error alert YouTube attached to a browse response.
+    fn extract_alert(&self, response: &Value) -> Option<String>;
     fn search_json(
         &self,
         start_pattern: &str,
@@ -49,6 +62,85 @@ impl ExtractorJsonHandle for YtExtractor {
         None
     }
 
+    fn find_renderers<'v>(&self, value: &'v Value, renderer_key: &str) -> Vec<&'v Value> {
+        let mut found = Vec::new();
+
+        match value {
+            Value::Object(map) => {
+                for (k, v) in map {
+                    if k == renderer_key {
+                        found.push(v);
+                    } else {
+                        found.extend(self.find_renderers(v, renderer_key));
+                    }
+                }
+            }
+            Value::Array(arr) => {
+                for v in arr {
+                    found.extend(self.find_renderers(v, renderer_key));
+                }
+            }
+            _ => {}
+        }
+
+        found
+    }
+
+    fn find_continuation_token(&self, value: &Value) -> Option<String> {
+        self.find_renderers(value, "continuationItemRenderer")
+            .into_iter()
+            .find_map(|renderer| self.find_key(renderer, "token"))
+    }
+
+    fn parse_thumbnails(&self, thumbnail: &Value) -> Vec<YtThumbnail> {
+        thumbnail
+            .get("thumbnails")
+            .or_else(|| thumbnail.get("sources"))
+            .and_then(|t| t.as_array())
+            .map(|thumbnails| {
+                thumbnails
+                    .iter()
+                    .filter_map(|t| {
+                        t.get("url")
+                            .and_then(|v| v.as_str())
+                            .map(|url| YtThumbnail {
+                                url: match url.starts_with("//") {
+                                    true => format!("https:{}", url),
+                                    false => url.to_string(),
+                                },
+                                height: t.get("height").and_then(|h| h.as_u64()),
+                                width: t.get("width").and_then(|w| w.as_u64()),
+                            })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn extract_channel_from_runs(&self, text: &Value) -> Option<YtChannel> {
+        text.get("runs")
+            .and_then(|r| r.as_array())
+            .into_iter()
+            .flatten()
+            .find_map(|run| {
+                let channel_id = run
+                    .get("navigationEndpoint")
+                    .and_then(|ne| ne.get("browseEndpoint"))
+                    .and_then(|be| be.get("browseId"))
+                    .and_then(|id| id.as_str())?;
+                let name = run.get("text").and_then(|t| t.as_str()).map(String::from);
+
+                YtChannel::new(channel_id, name).ok()
+            })
+    }
+
+    fn extract_alert(&self, response: &Value) -> Option<String> {
+        self.find_renderers(response, "alertRenderer")
+            .into_iter()
+            .filter(|alert| alert.get("type").and_then(|t| t.as_str()) == Some("ERROR"))
+            .find_map(|alert| self.get_text(alert, Some(vec![vec!["text"]]), None))
+    }
+
     fn get_text(
         &self,
         data: &Value,
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::{
+    extractor::extract::YtExtractor,
+    utils::parse_iso8601,
+    yt_interface::{YtLiveStatus, YtStreamResponse},
+};
+
+pub trait ExtractorLiveHandle {
+    fn extract_live_status(&self, player_responses: &[HashMap<String, Value>]) -> YtLiveStatus;
+    /// Unix timestamp from the offline slate of an upcoming stream, or the broadcast details of a past one.
+    fn extract_scheduled_start_time(
+        &self,
+        player_responses: &[HashMap<String, Value>],
+    ) -> Option<u64>;
+    /// Fill in the live status, manifest URLs and DVR window of a stream response.
+    fn extract_live_streaming_data(
+        &self,
+        player_responses: &[HashMap<String, Value>],
+        stream_response: &mut YtStreamResponse,
+    );
+}
+
+impl ExtractorLiveHandle for YtExtractor {
+    fn extract_live_status(&self, player_responses: &[HashMap<String, Value>]) -> YtLiveStatus {
+        let Some(player_response) = player_responses
+            .iter()
+            .find(|pr| pr.get("videoDetails").is_some())
+        else {
+            return YtLiveStatus::NotLive;
+        };
+
+        let video_details = player_response.get("videoDetails").unwrap_or(&Value::Null);
+        let detail = |key: &str| {
+            video_details
+                .get(key)
+                .and_then(|v| v.as_bool())
+                .unwrap_or_default()
+        };
+
+        let is_offline = player_response
+            .get("playabilityStatus")
+            .and_then(|ps| ps.get("status"))
+            .and_then(|s| s.as_str())
+            == Some("LIVE_STREAM_OFFLINE");
+        let has_broadcast_details = player_response
+            .get("microformat")
+            .and_then(|mf| mf.get("playerMicroformatRenderer"))
+            .and_then(|pmr| pmr.get("liveBroadcastDetails"))
+            .is_some();
+
+        if detail("isUpcoming") || is_offline {
+            YtLiveStatus::Upcoming
+        } else if detail("isPostLiveDvr") {
+            YtLiveStatus::PostLive
+        } else if detail("isLive") {
+            YtLiveStatus::Live
+        } else if detail("isLiveContent") || has_broadcast_details {
+            YtLiveStatus::WasLive
+        } else {
+            YtLiveStatus::NotLive
+        }
+    }
+
+    fn extract_scheduled_start_time(
+        &self,
+        player_responses: &[HashMap<String, Value>],
+    ) -> Option<u64> {
+        player_responses.iter().find_map(|player_response| {
+            player_response
+                .get("playabilityStatus")
+                .and_then(|ps| ps.get("liveStreamability"))
+                .and_then(|ls| ls.get("liveStreamabilityRenderer"))
+                .and_then(|lsr| lsr.get("offlineSlate"))
+                .and_then(|os| os.get("liveStreamOfflineSlateRenderer"))
+                .and_then(|slate| slate.get("scheduledStartTime"))
+                .and_then(|t| t.as_str())
+                .and_then(|t| t.parse().ok())
+                .or_else(|| {
+                    player_response
+                        .get("microformat")
+                        .and_then(|mf| mf.get("playerMicroformatRenderer"))
+                        .and_then(|pmr| pmr.get("liveBroadcastDetails"))
+                        .and_then(|lbd| lbd.get("startTimestamp"))
+                        .and_then(|t| t.as_str())
+                        .and_then(parse_iso8601)
+                })
+        })
+    }
+
+    fn extract_live_streaming_data(
+        &self,
+        player_responses: &[HashMap<String, Value>],
+        stream_response: &mut YtStreamResponse,
+    ) {
+        let streaming_data: Vec<&Value> = player_responses
+            .iter()
+            .filter_map(|pr| pr.get("streamingData"))
+            .collect();
+        let manifest_url = |key: &str| {
+            streaming_data
+                .iter()
+                .find_map(|sd| sd.get(key).and_then(|u| u.as_str()))
+                .map(String::from)
+        };
+
+        stream_response.live_status = self.extract_live_status(player_responses);
+        stream_response.hls_manifest_url = manifest_url("hlsManifestUrl");
+        stream_response.dash_manifest_url = manifest_url("dashManifestUrl");
+
+        let dvr_enabled = player_responses.iter().any(|pr| {
+            pr.get("videoDetails")
+                .and_then(|vd| vd.get("isLiveDvrEnabled"))
+                .and_then(|dvr| dvr.as_bool())
+                .unwrap_or_default()
+        });
+
+        if !dvr_enabled {
+            return;
+        }
+
+        stream_response.dvr_window = streaming_data
+            .iter()
+            .filter_map(|sd| sd.get("adaptiveFormats").and_then(|af| af.as_array()))
+            .flatten()
+            .find_map(|fmt| {
+                let max_dvr = fmt.get("maxDvrDurationSec")?;
+                max_dvr
+                    .as_u64()
+                    .or_else(|| max_dvr.as_str().and_then(|s| s.parse().ok()))
+            });
+    }
+}
@@ -1,11 +1,22 @@
 mod api;
 mod auth;
-mod client;
+mod chapters;
+pub(crate) mod client;
 mod cookies;
 mod download;
+mod formats;
+mod hls;
 mod json;
+mod live;
 mod player;
 mod token_policy;
 mod ytcfg;
 
+pub mod channel;
+pub mod comments;
+pub mod dash;
 pub mod extract;
+pub mod playlist;
+pub mod resolve;
+pub mod search;
+pub mod subtitles;
@@ -0,0 +1,211 @@
+use std::collections::HashSet;
+
+use anyhow::{Result, bail};
+use serde_json::Value;
+
+use crate::{
+    extractor::{api::ExtractorApiHandle, extract::YtExtractor, json::ExtractorJsonHandle},
+    utils::{parse_count, parse_duration},
+    yt_interface::{PlaylistId, VideoId, YtPlaylist, YtPlaylistEntry, YtPlaylistOptions},
+};
+
+pub trait ExtractorPlaylistHandle {
+    /// Fetch playlist metadata and walk its continuations until the requested range is filled.
+    async fn extract_playlist(
+        &self,
+        playlist_id: &PlaylistId,
+        options: &YtPlaylistOptions,
+    ) -> Result<YtPlaylist>;
+    fn parse_playlist_entry(&self, renderer: &Value, position: u64) -> Option<YtPlaylistEntry>;
+    fn extract_playlist_metadata(&self, playlist_id: &PlaylistId, response: &Value) -> YtPlaylist;
+}
+
+impl ExtractorPlaylistHandle for YtExtractor {
+    async fn extract_playlist(
+        &self,
+        playlist_id: &PlaylistId,
+        options: &YtPlaylistOptions,
+    ) -> Result<YtPlaylist> {
+        #[cfg(feature = "logging")]
+        log::info!("{}: Downloading playlist", playlist_id.as_str());
+
+        let mut response = self
+            .browse(Some(&playlist_id.browse_id()), None, None)
+            .await?;
+
+        if self
+            .find_renderers(&response, "playlistMetadataRenderer")
+            .is_empty()
+        {
+            bail!(
+                "Could not extract playlist \"{}\": {}",
+                playlist_id.as_str(),
+                self.extract_alert(&response)
+                    .unwrap_or("YouTube did not return any playlist metadata.".into())
+            )
+        }
+
+        let mut playlist = self.extract_playlist_metadata(playlist_id, &response);
+        let last_index = options.last_index();
+        let mut position = 0;
+        let mut seen_tokens = HashSet::new();
+
+        'pages: loop {
+            for renderer in self.find_renderers(&response, "playlistVideoRenderer") {
+                position += 1;
+
+                let Some(entry) = self.parse_playlist_entry(renderer, position) else {
+                    continue;
+                };
+
+                if last_index.is_some_and(|last| entry.index > last) {
+                    break 'pages;
+                }
+
+                if options.contains(entry.index) {
+                    playlist.entries.push(entry);
+                }
+            }
+
+            let Some(token) = self.find_continuation_token(&response) else {
+                break;
+            };
+            // YouTube sometimes hands the same continuation back, which would loop forever.
+            if !seen_tokens.insert(token.clone()) {
+                #[cfg(feature = "logging")]
+                log::warn!(
+                    "{}: Playlist continuation repeated after {} entries, stopping",
+                    playlist_id.as_str(),
+                    position
+                );
+                break;
+            }
+
+            #[cfg(feature = "logging")]
+            log::info!(
+                "{}: Downloading playlist continuation after {} entries",
+                playlist_id.as_str(),
+                position
+            );
+
+            response = self.browse(None, None, Some(&token)).await?;
+        }
+
+        Ok(playlist)
+    }
+
+    fn parse_playlist_entry(&self, renderer: &Value, position: u64) -> Option<YtPlaylistEntry> {
+        let video_id = renderer
+            .get("videoId")
+            .and_then(|id| id.as_str())
+            .and_then(|id| VideoId::new(id).ok())?;
+
+        let duration = renderer
+            .get("lengthSeconds")
+            .and_then(|l| l.as_str())
+            .and_then(|l| l.parse().ok())
+            .or_else(|| {
+                self.get_text(renderer, Some(vec![vec!["lengthText"]]), None)
+                    .and_then(|l| parse_duration(&l))
+            });
+
+        let index = self
+            .get_text(renderer, Some(vec![vec!["index"]]), None)
+            .and_then(|i| i.trim().parse().ok())
+            .unwrap_or(position);
+
+        Some(YtPlaylistEntry {
+            video_id,
+            title: self
+                .get_text(renderer, Some(vec![vec!["title"]]), None)
+                .unwrap_or_default(),
+            duration,
+            channel: renderer
+                .get("shortBylineText")
+                .and_then(|byline| self.extract_channel_from_runs(byline)),
+            index,
+            thumbnails: renderer
+                .get("thumbnail")
+                .map(|t| self.parse_thumbnails(t))
+                .unwrap_or_default(),
+        })
+    }
+
+    fn extract_playlist_metadata(&self, playlist_id: &PlaylistId, response: &Value) -> YtPlaylist {
+        let metadata = self
+            .find_renderers(response, "playlistMetadataRenderer")
+            .first()
+            .copied()
+            .unwrap_or(&Value::Null);
+        let header = self
+            .find_renderers(response, "playlistHeaderRenderer")
+            .first()
+            .copied()
+            .unwrap_or(&Value::Null);
+        let stats: Vec<String> = self
+            .find_renderers(response, "playlistSidebarPrimaryInfoRenderer")
+            .first()
+            .and_then(|info| info.get("stats"))
+            .and_then(|s| s.as_array())
+            .map(|stats| {
+                stats
+                    .iter()
+                    .filter_map(|stat| self.get_text(stat, None, None))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let channel = self
+            .find_renderers(response, "videoOwnerRenderer")
+            .first()
+            .and_then(|owner| owner.get("title"))
+            .and_then(|title| self.extract_channel_from_runs(title))
+            .or_else(|| {
+                header
+                    .get("ownerText")
+                    .and_then(|owner| self.extract_channel_from_runs(owner))
+            });
+
+        let video_count = stats
+            .iter()
+            .find(|s| s.contains("video"))
+            .or_else(|| stats.first())
+            .and_then(|s| parse_count(s))
+            .or_else(|| {
+                self.get_text(header, Some(vec![vec!["numVideosText"]]), None)
+                    .and_then(|s| parse_count(&s))
+            });
+
+        let view_count = stats
+            .iter()
+            .find(|s| s.contains("view"))
+            .and_then(|s| parse_count(s));
+
+        let thumbnails = response
+            .get("microformat")
+            .and_then(|mf| mf.get("microformatDataRenderer"))
+            .and_then(|mdr| mdr.get("thumbnail"))
+            .map(|t| self.parse_thumbnails(t))
+            .unwrap_or_default();
+
+        YtPlaylist {
+            id: playlist_id.clone(),
+            title: metadata
+                .get("title")
+                .and_then(|t| t.as_str())
+                .map(String::from)
+                .or_else(|| self.get_text(header, Some(vec![vec!["title"]]), None))
+                .unwrap_or_default(),
+            description: metadata
+                .get("description")
+                .and_then(|d| d.as_str())
+                .filter(|d| !d.is_empty())
+                .map(String::from),
+            channel,
+            video_count,
+            view_count,
+            thumbnails,
+            entries: vec![],
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use anyhow::{Result, anyhow, bail};
+use fancy_regex::Regex;
+use maplit::hashmap;
+use reqwest::Url;
+use serde_json::Value;
+
+use crate::{
+    extractor::{
+        api::ExtractorApiHandle,
+        channel::ExtractorChannelHandle,
+        download::ExtractorDownloadHandle,
+        extract::{InfoExtractor, YtExtractor},
+        json::ExtractorJsonHandle,
+    },
+    yt_interface::{
+        PlaylistId, VideoId, YT_URL, YT_VIDEO_HOSTS, YtChannel, YtClient, YtClip, YtEndpoint,
+        YtResolvedUrl, YtVideoUrl,
+    },
+};
+
+pub trait ExtractorResolveHandle {
+    /// Classify a YouTube URL, asking InnerTube for handles, custom channel URLs and clips.
+    async fn resolve_url(&self, url: &str) -> Result<YtResolvedUrl>;
+    /// Resolve a `UC…` ID, `@handle`, `/c/name`, `/user/name` or `/channel/` URL to a channel with its name.
+    async fn resolve_channel(&self, url: &str) -> Result<YtChannel>;
+    /// Resolve through the `navigation/resolve_url` endpoint, `None` if it doesn't know the URL.
+    async fn call_resolve_url(&self, url: &Url) -> Result<Option<YtResolvedUrl>>;
+    /// Read the channel ID and name from the channel page itself.
+    async fn extract_channel_from_page(&self, url: &Url) -> Result<YtChannel>;
+    /// Read the clipped section from a clip page's initial data.
+    async fn extract_clip(&self, url: &Url, clip_id: &str, video_id: VideoId) -> Result<YtClip>;
+    fn parse_resolved_endpoint(&self, response: &Value) -> Option<YtResolvedUrl>;
+}
+
+/// Turn `@handle`, `youtube.com/...` and other scheme-less inputs into a full URL.
+fn normalize_youtube_url(input: &str) -> Result<Url> {
+    let input = input.trim();
+    let full_url = if input.starts_with('@') {
+        format!("{}/{}", YT_URL, input)
+    } else if input.contains("://") {
+        input.to_string()
+    } else {
+        format!("https://{}", input)
+    };
+
+    let url =
+        Url::parse(&full_url).map_err(|e| anyhow!("invalid YouTube URL \"{}\": {}", input, e))?;
+    let host = url.host_str().unwrap_or_default().to_lowercase();
+
+    if !YT_VIDEO_HOSTS.contains(&host.as_str()) {
+        bail!("\"{}\" is not a YouTube URL", input)
+    }
+
+    Ok(url)
+}
+
+impl ExtractorResolveHandle for YtExtractor {
+    async fn resolve_url(&self, url: &str) -> Result<YtResolvedUrl> {
+        if let Ok(video_url) = YtVideoUrl::parse(url) {
+            return Ok(YtResolvedUrl::Video(video_url));
+        }
+
+        if let Ok(channel) = YtChannel::new(url.trim(), None) {
+            return Ok(YtResolvedUrl::Channel(channel));
+        }
+
+        let page_url = normalize_youtube_url(url)?;
+        let segments: Vec<&str> = page_url
+            .path_segments()
+            .into_iter()
+            .flatten()
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        match segments.as_slice() {
+            ["playlist", ..] => {
+                return Ok(YtResolvedUrl::Playlist(PlaylistId::from_url(
+                    page_url.as_str(),
+                )?));
+            }
+            ["channel", channel_id, ..] => {
+                return Ok(YtResolvedUrl::Channel(YtChannel::new(*channel_id, None)?));
+            }
+            [] => bail!("\"{}\" does not point to a video, playlist or channel", url),
+            _ => {}
+        }
+
+        if let Some(resolved) = self.call_resolve_url(&page_url).await? {
+            return match (resolved, segments.as_slice()) {
+                (YtResolvedUrl::Video(video_url), ["clip", clip_id, ..]) => {
+                    Ok(YtResolvedUrl::Clip(
+                        self.extract_clip(&page_url, clip_id, video_url.video_id)
+                            .await?,
+                    ))
+                }
+                (resolved, _) => Ok(resolved),
+            };
+        }
+
+        #[cfg(feature = "logging")]
+        log::info!(
+            "InnerTube could not resolve {}, falling back to the channel page.",
+            page_url
+        );
+
+        Ok(YtResolvedUrl::Channel(
+            self.extract_channel_from_page(&page_url).await?,
+        ))
+    }
+
+    async fn resolve_channel(&self, url: &str) -> Result<YtChannel> {
+        let channel = match self.resolve_url(url).await? {
+            YtResolvedUrl::Channel(channel) => channel,
+            _ => bail!("\"{}\" does not point to a channel", url),
+        };
+
+        if channel.get_name().is_some() {
+            return Ok(channel);
+        }
+
+        Ok(self.extract_channel_info(&channel).await?.channel)
+    }
+
+    async fn call_resolve_url(&self, url: &Url) -> Result<Option<YtResolvedUrl>> {
+        #[cfg(feature = "logging")]
+        log::info!("Resolving URL {}", url);
+
+        let response = self
+            .call_api(
+                YtEndpoint::ResolveUrl,
+                hashmap! { "url".into() => url.as_str().into() },
+                None,
+                None,
+                None,
+                None,
+            )
+            .await?;
+
+        Ok(self.parse_resolved_endpoint(&Value::Object(response.into_iter().collect())))
+    }
+
+    fn parse_resolved_endpoint(&self, response: &Value) -> Option<YtResolvedUrl> {
+        let endpoint = response.get("endpoint")?;
+
+        if let Some(browse_id) = endpoint
+            .get("browseEndpoint")
+            .and_then(|be| be.get("browseId"))
+            .and_then(|id| id.as_str())
+        {
+            if let Some(playlist_id) = browse_id.strip_prefix("VL") {
+                return PlaylistId::new(playlist_id)
+                    .ok()
+                    .map(YtResolvedUrl::Playlist);
+            }
+
+            return YtChannel::new(browse_id, None)
+                .ok()
+                .map(YtResolvedUrl::Channel);
+        }
+
+        let watch_endpoint = endpoint.get("watchEndpoint")?;
+        let video_id = watch_endpoint
+            .get("videoId")
+            .and_then(|id| id.as_str())
+            .and_then(|id| VideoId::new(id).ok())?;
+
+        Some(YtResolvedUrl::Video(YtVideoUrl {
+            video_id,
+            start_time: watch_endpoint
+                .get("startTimeSeconds")
+                .and_then(|s| s.as_u64()),
+            playlist_id: watch_endpoint
+                .get("playlistId")
+                .and_then(|p| p.as_str())
+                .and_then(|p| PlaylistId::new(p).ok()),
+        }))
+    }
+
+    async fn extract_channel_from_page(&self, url: &Url) -> Result<YtChannel> {
+        let webpage = self.download_page(url.clone(), &YtClient::Web).await?;
+
+        if let Ok(initial_data) = self.extract_yt_initial_data(&webpage) {
+            let initial_data = Value::Object(initial_data.into_iter().collect());
+            let metadata = self
+                .find_renderers(&initial_data, "channelMetadataRenderer")
+                .first()
+                .copied()
+                .unwrap_or(&Value::Null);
+
+            if let Some(channel_id) = metadata.get("externalId").and_then(|id| id.as_str()) {
+                let name = metadata
+                    .get("title")
+                    .and_then(|t| t.as_str())
+                    .map(String::from);
+
+                return YtChannel::new(channel_id, name);
+            }
+        }
+
+        let channel_id_re = Regex::new(
+            r#"(?:<meta itemprop="(?:identifier|channelId)" content="|"externalId":"|"channelId":")(?P<id>UC[\w-]{22})"#,
+        )?;
+
+        match channel_id_re
+            .captures(&webpage)?
+            .and_then(|caps| caps.name("id"))
+        {
+            Some(channel_id) => YtChannel::new(channel_id.as_str(), None),
+            None => bail!("Could not find a channel ID on {}", url),
+        }
+    }
+
+    async fn extract_clip(&self, url: &Url, clip_id: &str, video_id: VideoId) -> Result<YtClip> {
+        let webpage = self.download_page(url.clone(), &YtClient::Web).await?;
+        let initial_data = Value::Object(
+            self.extract_yt_initial_data(&webpage)?
+                .into_iter()
+                .collect(),
+        );
+
+        let loop_command = self
+            .find_renderers(&initial_data, "loopCommand")
+            .first()
+            .copied()
+            .unwrap_or(&Value::Null);
+        let parse_ms = |key: &str| {
+            loop_command
+                .get(key)
+                .and_then(|ms| {
+                    ms.as_str()
+                        .and_then(|s| s.parse::<f64>().ok())
+                        .or(ms.as_f64())
+                })
+                .map(|ms| ms / 1000.0)
+        };
+
+        Ok(YtClip {
+            id: clip_id.to_string(),
+            video_id,
+            start_time: parse_ms("startTimeMs"),
+            end_time: parse_ms("endTimeMs"),
+        })
+    }
+}
@@ -0,0 +1,401 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::{
+    extractor::{
+        api::ExtractorApiHandle, channel::ExtractorChannelHandle, extract::YtExtractor,
+        json::ExtractorJsonHandle,
+    },
+    utils::{base64_encode, parse_count, parse_duration},
+    yt_interface::{
+        PlaylistId, VideoId, YtChannel, YtEndpoint, YtSearchChannel, YtSearchDuration,
+        YtSearchFeature, YtSearchFilters, YtSearchPlaylist, YtSearchResponse, YtSearchResult,
+        YtSearchSort, YtSearchType, YtSearchUploadDate, YtSearchVideo,
+    },
+};
+
+pub trait ExtractorSearchHandle {
+    /// Fetch the first page of results for `query`.
+    async fn extract_search(
+        &self,
+        query: &str,
+        filters: &YtSearchFilters,
+    ) -> Result<YtSearchResponse>;
+    /// Fetch the page of results behind a continuation token from a previous page.
+    async fn extract_search_continuation(&self, continuation: &str) -> Result<YtSearchResponse>;
+    fn parse_search_response(&self, response: &Value) -> YtSearchResponse;
+    fn parse_search_video(&self, renderer: &Value) -> Option<YtSearchVideo>;
+    fn parse_search_channel(&self, renderer: &Value) -> Option<YtSearchChannel>;
+    fn parse_search_playlist(&self, renderer: &Value) -> Option<YtSearchPlaylist>;
+    fn parse_search_lockup(&self, lockup: &Value) -> Option<YtSearchPlaylist>;
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn write_varint_field(buf: &mut Vec<u8>, field: u64, value: u64) {
+    write_varint(buf, field << 3);
+    write_varint(buf, value);
+}
+
+/// Encode the filters into the protobuf message the search page puts in its `sp` parameter.
+///
+/// Field 1 is the sort order, field 2 a nested message holding the upload date (1), result
+/// type (2), duration (3) and one boolean field per feature.
+fn encode_search_params(filters: &YtSearchFilters) -> Option<String> {
+    let mut filter_message = Vec::new();
+
+    if let Some(upload_date) = filters.upload_date {
+        let value = match upload_date {
+            YtSearchUploadDate::LastHour => 1,
+            YtSearchUploadDate::Today => 2,
+            YtSearchUploadDate::ThisWeek => 3,
+            YtSearchUploadDate::ThisMonth => 4,
+            YtSearchUploadDate::ThisYear => 5,
+        };
+        write_varint_field(&mut filter_message, 1, value);
+    }
+
+    if let Some(result_type) = filters.result_type {
+        let value = match result_type {
+            YtSearchType::Video => 1,
+            YtSearchType::Channel => 2,
+            YtSearchType::Playlist => 3,
+            YtSearchType::Movie => 4,
+            YtSearchType::Short => 9,
+        };
+        write_varint_field(&mut filter_message, 2, value);
+    }
+
+    if let Some(duration) = filters.duration {
+        let value = match duration {
+            YtSearchDuration::Short => 1,
+            YtSearchDuration::Long => 2,
+            YtSearchDuration::Medium => 3,
+        };
+        write_varint_field(&mut filter_message, 3, value);
+    }
+
+    let mut feature_fields: Vec<u64> = filters
+        .features
+        .iter()
+        .map(|feature| match feature {
+            YtSearchFeature::Hd => 4,
+            YtSearchFeature::Subtitles => 5,
+            YtSearchFeature::CreativeCommons => 6,
+            YtSearchFeature::ThreeD => 7,
+            YtSearchFeature::Live => 8,
+            YtSearchFeature::Purchased => 9,
+            YtSearchFeature::FourK => 14,
+            YtSearchFeature::ThreeSixty => 15,
+            YtSearchFeature::Location => 23,
+            YtSearchFeature::Hdr => 25,
+            YtSearchFeature::Vr180 => 26,
+        })
+        .collect();
+    feature_fields.sort_unstable();
+    feature_fields.dedup();
+
+    for field in feature_fields {
+        write_varint_field(&mut filter_message, field, 1);
+    }
+
+    let mut params = Vec::new();
+
+    let sort = match filters.sort {
+        YtSearchSort::Relevance => 0,
+        YtSearchSort::Rating => 1,
+        YtSearchSort::UploadDate => 2,
+        YtSearchSort::ViewCount => 3,
+    };
+    if sort != 0 {
+        write_varint_field(&mut params, 1, sort);
+    }
+
+    if !filter_message.is_empty() {
+        write_varint(&mut params, 2 << 3 | 2);
+        write_varint(&mut params, filter_message.len() as u64);
+        params.extend(filter_message);
+    }
+
+    match params.is_empty() {
+        true => None,
+        false => Some(base64_encode(&params)),
+    }
+}
+
+impl ExtractorSearchHandle for YtExtractor {
+    async fn extract_search(
+        &self,
+        query: &str,
+        filters: &YtSearchFilters,
+    ) -> Result<YtSearchResponse> {
+        #[cfg(feature = "logging")]
+        log::info!("Searching for \"{}\"", query);
+
+        let mut search_query: HashMap<String, Value> = HashMap::new();
+        search_query.insert("query".into(), query.into());
+
+        if let Some(params) = encode_search_params(filters) {
+            search_query.insert("params".into(), params.into());
+        }
+
+        let response = self
+            .call_api(YtEndpoint::Search, search_query, None, None, None, None)
+            .await?;
+
+        Ok(self.parse_search_response(&Value::Object(response.into_iter().collect())))
+    }
+
+    async fn extract_search_continuation(&self, continuation: &str) -> Result<YtSearchResponse> {
+        #[cfg(feature = "logging")]
+        log::info!("Downloading search results continuation");
+
+        let mut search_query: HashMap<String, Value> = HashMap::new();
+        search_query.insert("continuation".into(), continuation.into());
+
+        let response = self
+            .call_api(YtEndpoint::Search, search_query, None, None, None, None)
+            .await?;
+
+        Ok(self.parse_search_response(&Value::Object(response.into_iter().collect())))
+    }
+
+    fn parse_search_response(&self, response: &Value) -> YtSearchResponse {
+        let mut results = Vec::new();
+
+        for section in self.find_renderers(response, "itemSectionRenderer") {
+            let items = section
+                .get("contents")
+                .and_then(|c| c.as_array())
+                .into_iter()
+                .flatten();
+
+            for item in items {
+                if let Some(renderer) = item.get("videoRenderer") {
+                    results.extend(self.parse_search_video(renderer).map(YtSearchResult::Video));
+                } else if let Some(renderer) = item.get("channelRenderer") {
+                    results.extend(
+                        self.parse_search_channel(renderer)
+                            .map(YtSearchResult::Channel),
+                    );
+                } else if let Some(renderer) = item.get("playlistRenderer") {
+                    results.extend(
+                        self.parse_search_playlist(renderer)
+                            .map(YtSearchResult::Playlist),
+                    );
+                } else if let Some(lockup) = item.get("lockupViewModel") {
+                    results.extend(
+                        self.parse_search_lockup(lockup)
+                            .map(YtSearchResult::Playlist),
+                    );
+                } else if item.get("reelShelfRenderer").is_some()
+                    || item.get("gridShelfViewModel").is_some()
+                {
+                    let shorts = self
+                        .find_renderers(item, "shortsLockupViewModel")
+                        .into_iter()
+                        .chain(self.find_renderers(item, "reelItemRenderer"))
+                        .filter_map(|r| self.parse_short_renderer(r))
+                        .map(|entry| {
+                            YtSearchResult::Short(YtSearchVideo {
+                                video_id: entry.video_id,
+                                title: entry.title,
+                                duration: entry.duration,
+                                view_count: entry.view_count,
+                                published_time_text: entry.published_time_text,
+                                channel: None,
+                                description_snippet: None,
+                                is_live: false,
+                                thumbnails: entry.thumbnails,
+                            })
+                        });
+                    results.extend(shorts);
+                }
+            }
+        }
+
+        YtSearchResponse {
+            results,
+            estimated_results: response
+                .get("estimatedResults")
+                .and_then(|e| e.as_str())
+                .and_then(|e| e.parse().ok()),
+            continuation: self.find_continuation_token(response),
+        }
+    }
+
+    fn parse_search_video(&self, renderer: &Value) -> Option<YtSearchVideo> {
+        let video_id = renderer
+            .get("videoId")
+            .and_then(|id| id.as_str())
+            .and_then(|id| VideoId::new(id).ok())?;
+
+        let is_live = renderer
+            .get("badges")
+            .into_iter()
+            .chain(renderer.get("thumbnailOverlays"))
+            .any(|badges| {
+                self.find_key(badges, "style")
+                    .is_some_and(|style| style.contains("LIVE"))
+            });
+
+        let description_snippet = self
+            .get_text(renderer, Some(vec![vec!["descriptionSnippet"]]), None)
+            .or_else(|| {
+                self.find_renderers(renderer, "detailedMetadataSnippets")
+                    .first()
+                    .and_then(|snippets| {
+                        self.find_renderers(snippets, "snippetText")
+                            .first()
+                            .copied()
+                    })
+                    .and_then(|snippet| self.get_text(snippet, None, None))
+            });
+
+        Some(YtSearchVideo {
+            video_id,
+            title: self
+                .get_text(renderer, Some(vec![vec!["title"]]), None)
+                .unwrap_or_default(),
+            duration: self
+                .get_text(renderer, Some(vec![vec!["lengthText"]]), None)
+                .and_then(|l| parse_duration(&l)),
+            view_count: self
+                .get_text(renderer, Some(vec![vec!["viewCountText"]]), None)
+                .and_then(|v| parse_count(&v)),
+            published_time_text: self.get_text(
+                renderer,
+                Some(vec![vec!["publishedTimeText"]]),
+                None,
+            ),
+            channel: renderer
+                .get("ownerText")
+                .or_else(|| renderer.get("longBylineText"))
+                .and_then(|byline| self.extract_channel_from_runs(byline)),
+            description_snippet,
+            is_live,
+            thumbnails: renderer
+                .get("thumbnail")
+                .map(|t| self.parse_thumbnails(t))
+                .unwrap_or_default(),
+        })
+    }
+
+    fn parse_search_channel(&self, renderer: &Value) -> Option<YtSearchChannel> {
+        let channel_id = renderer.get("channelId").and_then(|id| id.as_str())?;
+        let name = self.get_text(renderer, Some(vec![vec!["title"]]), None);
+
+        // YouTube shows the handle where the subscriber count used to be and moves the
+        // subscriber count into `videoCountText`, so look through both.
+        let texts: Vec<String> = ["subscriberCountText", "videoCountText"]
+            .into_iter()
+            .filter_map(|key| self.get_text(renderer, Some(vec![vec![key]]), None))
+            .collect();
+
+        Some(YtSearchChannel {
+            channel: YtChannel::new(channel_id, name).ok()?,
+            handle: texts.iter().find(|t| t.starts_with('@')).cloned(),
+            subscriber_count_text: texts.iter().find(|t| t.contains("subscriber")).cloned(),
+            description_snippet: self.get_text(
+                renderer,
+                Some(vec![vec!["descriptionSnippet"]]),
+                None,
+            ),
+            thumbnails: renderer
+                .get("thumbnail")
+                .map(|t| self.parse_thumbnails(t))
+                .unwrap_or_default(),
+        })
+    }
+
+    fn parse_search_playlist(&self, renderer: &Value) -> Option<YtSearchPlaylist> {
+        let playlist_id = renderer
+            .get("playlistId")
+            .and_then(|id| id.as_str())
+            .and_then(|id| PlaylistId::new(id).ok())?;
+
+        Some(YtSearchPlaylist {
+            playlist_id,
+            title: self
+                .get_text(renderer, Some(vec![vec!["title"]]), None)
+                .unwrap_or_default(),
+            video_count: renderer
+                .get("videoCount")
+                .and_then(|c| c.as_str())
+                .and_then(parse_count),
+            channel: renderer
+                .get("shortBylineText")
+                .or_else(|| renderer.get("longBylineText"))
+                .and_then(|byline| self.extract_channel_from_runs(byline)),
+            thumbnails: renderer
+                .get("thumbnails")
+                .and_then(|t| t.as_array())
+                .and_then(|t| t.first())
+                .map(|t| self.parse_thumbnails(t))
+                .unwrap_or_default(),
+        })
+    }
+
+    fn parse_search_lockup(&self, lockup: &Value) -> Option<YtSearchPlaylist> {
+        if lockup.get("contentType").and_then(|t| t.as_str())
+            != Some("LOCKUP_CONTENT_TYPE_PLAYLIST")
+        {
+            return None;
+        }
+
+        let playlist_id = lockup
+            .get("contentId")
+            .and_then(|id| id.as_str())
+            .and_then(|id| PlaylistId::new(id).ok())?;
+        let metadata = self
+            .find_renderers(lockup, "lockupMetadataViewModel")
+            .first()
+            .copied()
+            .unwrap_or(&Value::Null);
+
+        let channel = self
+            .find_renderers(metadata, "commandRuns")
+            .into_iter()
+            .filter_map(|runs| runs.as_array())
+            .flatten()
+            .find_map(|run| {
+                let channel_id = self
+                    .find_renderers(run, "browseEndpoint")
+                    .first()
+                    .and_then(|be| be.get("browseId"))
+                    .and_then(|id| id.as_str())?;
+
+                YtChannel::new(channel_id, None).ok()
+            });
+
+        Some(YtSearchPlaylist {
+            playlist_id,
+            title: metadata
+                .get("title")
+                .and_then(|t| t.get("content"))
+                .and_then(|t| t.as_str())
+                .map(String::from)
+                .unwrap_or_default(),
+            video_count: self
+                .find_renderers(lockup, "thumbnailBadgeViewModel")
+                .into_iter()
+                .filter_map(|badge| badge.get("text").and_then(|t| t.as_str()))
+                .find_map(parse_count),
+            channel,
+            thumbnails: self
+                .find_renderers(lockup, "primaryThumbnail")
+                .first()
+                .and_then(|t| self.find_renderers(t, "image").first().copied())
+                .map(|image| self.parse_thumbnails(image))
+                .unwrap_or_default(),
+        })
+    }
+}
@@ -0,0 +1,375 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Result, anyhow, bail};
+use fancy_regex::Regex;
+use reqwest::Url;
+use serde_json::Value;
+
+use crate::{
+    extractor::{
+        download::ExtractorDownloadHandle, extract::YtExtractor, json::ExtractorJsonHandle,
+    },
+    utils::{escape_xml, unescape_xml},
+    yt_interface::{
+        YT_URL, YtCaptionKind, YtCaptionLanguage, YtCaptionTrack, YtClient, YtSubtitle,
+        YtSubtitleCue, YtSubtitleFormat, YtSubtitleOptions, YtSubtitles,
+    },
+};
+
+pub trait ExtractorSubtitlesHandle {
+    /// Collect the caption tracks from every player response, skipping tracks an earlier client already returned.
+    fn extract_subtitles(&self, player_responses: &[HashMap<String, Value>]) -> YtSubtitles;
+    /// Download a caption track and convert it to the requested format.
+    async fn download_subtitle(
+        &self,
+        track: &YtCaptionTrack,
+        options: &YtSubtitleOptions,
+    ) -> Result<YtSubtitle>;
+}
+
+impl ExtractorSubtitlesHandle for YtExtractor {
+    fn extract_subtitles(&self, player_responses: &[HashMap<String, Value>]) -> YtSubtitles {
+        let mut subtitles = YtSubtitles::default();
+        let mut seen_tracks = HashSet::new();
+
+        for player_response in player_responses {
+            let Some(renderer) = player_response
+                .get("captions")
+                .and_then(|c| c.get("playerCaptionsTracklistRenderer"))
+            else {
+                continue;
+            };
+
+            let caption_tracks = renderer
+                .get("captionTracks")
+                .and_then(|t| t.as_array())
+                .into_iter()
+                .flatten();
+
+            for caption_track in caption_tracks {
+                let (Some(base_url), Some(language_code)) = (
+                    caption_track.get("baseUrl").and_then(|u| u.as_str()),
+                    caption_track.get("languageCode").and_then(|l| l.as_str()),
+                ) else {
+                    continue;
+                };
+
+                let kind = match caption_track.get("kind").and_then(|k| k.as_str()) {
+                    Some("asr") => YtCaptionKind::AutoGenerated,
+                    _ => YtCaptionKind::Manual,
+                };
+                let vss_id = caption_track
+                    .get("vssId")
+                    .and_then(|v| v.as_str())
+                    .map(String::from)
+                    .unwrap_or_else(|| match kind {
+                        YtCaptionKind::AutoGenerated => format!("a.{}", language_code),
+                        YtCaptionKind::Manual => format!(".{}", language_code),
+                    });
+
+                if !seen_tracks.insert(vss_id.clone()) {
+                    continue;
+                }
+
+                subtitles.tracks.push(YtCaptionTrack {
+                    language_code: language_code.to_string(),
+                    name: self
+                        .get_text(caption_track, Some(vec![vec!["name"]]), None)
+                        .unwrap_or_else(|| language_code.to_string()),
+                    kind,
+                    vss_id,
+                    is_translatable: caption_track
+                        .get("isTranslatable")
+                        .and_then(|t| t.as_bool())
+                        .unwrap_or_default(),
+                    base_url: match base_url.starts_with('/') {
+                        true => format!("{}{}", YT_URL, base_url),
+                        false => base_url.to_string(),
+                    },
+                });
+            }
+
+            if subtitles.translation_languages.is_empty() {
+                subtitles.translation_languages = renderer
+                    .get("translationLanguages")
+                    .and_then(|t| t.as_array())
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|language| {
+                        let language_code = language.get("languageCode")?.as_str()?;
+
+                        Some(YtCaptionLanguage {
+                            language_code: language_code.to_string(),
+                            name: self
+                                .get_text(language, Some(vec![vec!["languageName"]]), None)
+                                .unwrap_or_else(|| language_code.to_string()),
+                        })
+                    })
+                    .collect();
+            }
+        }
+
+        subtitles
+    }
+
+    async fn download_subtitle(
+        &self,
+        track: &YtCaptionTrack,
+        options: &YtSubtitleOptions,
+    ) -> Result<YtSubtitle> {
+        let source_format = match options.format {
+            YtSubtitleFormat::Srv3 => "srv3",
+            _ => "json3",
+        };
+
+        let mut subtitle_url = Url::parse(&track.base_url)?;
+        let query: Vec<(String, String)> = subtitle_url
+            .query_pairs()
+            .filter(|(key, _)| key != "fmt" && key != "tlang")
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect();
+        {
+            let mut query_pairs = subtitle_url.query_pairs_mut();
+            query_pairs.clear().extend_pairs(query);
+            query_pairs.append_pair("fmt", source_format);
+
+            if let Some(translate_to) = &options.translate_to {
+                query_pairs.append_pair("tlang", translate_to);
+            }
+        }
+
+        #[cfg(feature = "logging")]
+        log::info!("Downloading {} subtitles ({})", track.vss_id, source_format);
+
+        let raw = self.download_page(subtitle_url, &YtClient::Web).await?;
+
+        if raw.trim().is_empty() {
+            bail!(
+                "YouTube returned empty subtitles for track \"{}\". The track may require a PO token.",
+                track.vss_id
+            )
+        }
+
+        let cues = match options.format {
+            YtSubtitleFormat::Srv3 => parse_srv3(&raw)?,
+            _ => parse_json3(&raw)?,
+        };
+        let language_code = options
+            .translate_to
+            .clone()
+            .unwrap_or_else(|| track.language_code.clone());
+
+        let content = match options.format {
+            YtSubtitleFormat::Json3 | YtSubtitleFormat::Srv3 => raw,
+            YtSubtitleFormat::Srt => render_srt(&cues),
+            YtSubtitleFormat::Vtt => render_vtt(&cues),
+            YtSubtitleFormat::Ttml => render_ttml(&cues, &language_code),
+            YtSubtitleFormat::Txt => render_txt(&cues),
+        };
+
+        Ok(YtSubtitle {
+            language_code,
+            format: options.format,
+            cues,
+            content,
+        })
+    }
+}
+
+fn parse_json3(raw: &str) -> Result<Vec<YtSubtitleCue>> {
+    let timed_text: Value =
+        serde_json::from_str(raw).map_err(|e| anyhow!("Invalid json3 subtitles: {}", e))?;
+
+    let cues = timed_text
+        .get("events")
+        .and_then(|e| e.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|event| {
+            let start_ms = event.get("tStartMs")?.as_f64()?;
+            let duration_ms = event
+                .get("dDurationMs")
+                .and_then(|d| d.as_f64())
+                .unwrap_or_default();
+            let text: String = event
+                .get("segs")?
+                .as_array()?
+                .iter()
+                .filter_map(|seg| seg.get("utf8").and_then(|t| t.as_str()))
+                .collect();
+            let text = text.trim();
+
+            if text.is_empty() {
+                return None;
+            }
+
+            Some(YtSubtitleCue {
+                start: start_ms / 1000.0,
+                end: (start_ms + duration_ms) / 1000.0,
+                text: text.to_string(),
+            })
+        })
+        .collect();
+
+    Ok(cues)
+}
+
+fn parse_srv3(raw: &str) -> Result<Vec<YtSubtitleCue>> {
+    let paragraph_re = Regex::new(r#"(?s)<p\b(?P<attrs>[^>]*)>(?P<text>.*?)</p>"#)?;
+    let start_re = Regex::new(r#"\bt="(?P<t>\d+)""#)?;
+    let duration_re = Regex::new(r#"\bd="(?P<d>\d+)""#)?;
+    let line_break_re = Regex::new(r"(?i)<br\s*/?>")?;
+    let tag_re = Regex::new(r"<[^>]+>")?;
+
+    let mut cues = vec![];
+
+    for caps in paragraph_re.captures_iter(raw) {
+        let caps = caps?;
+        let attrs = caps.name("attrs").map(|a| a.as_str()).unwrap_or_default();
+        let read_ms = |re: &Regex, name: &str| -> Result<Option<f64>> {
+            Ok(re
+                .captures(attrs)?
+                .and_then(|c| c.name(name))
+                .and_then(|m| m.as_str().parse::<f64>().ok()))
+        };
+
+        let Some(start_ms) = read_ms(&start_re, "t")? else {
+            continue;
+        };
+        let duration_ms = read_ms(&duration_re, "d")?.unwrap_or_default();
+
+        let text = caps.name("text").map(|t| t.as_str()).unwrap_or_default();
+        let text = line_break_re.replace_all(text, "\n");
+        let text = tag_re.replace_all(&text, "");
+        let text = unescape_xml(&text)?;
+        let text = text.trim();
+
+        if text.is_empty() {
+            continue;
+        }
+
+        cues.push(YtSubtitleCue {
+            start: start_ms / 1000.0,
+            end: (start_ms + duration_ms) / 1000.0,
+            text: text.to_string(),
+        });
+    }
+
+    Ok(cues)
+}
+
+/// Format seconds as `HH:MM:SS` followed by `separator` and milliseconds.
+fn format_timestamp(seconds: f64, separator: char) -> String {
+    let total_ms = (seconds * 1000.0).round() as u64;
+
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        total_ms / 3_600_000,
+        total_ms / 60_000 % 60,
+        total_ms / 1000 % 60,
+        separator,
+        total_ms % 1000
+    )
+}
+
+/// Drop the blank lines of a cue's text, which would end the cue early in SRT and WebVTT.
+fn without_blank_lines(text: &str) -> String {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_srt(cues: &[YtSubtitleCue]) -> String {
+    cues.iter()
+        .enumerate()
+        .map(|(i, cue)| {
+            format!(
+                "{}\n{} --> {}\n{}\n\n",
+                i + 1,
+                format_timestamp(cue.start, ','),
+                format_timestamp(cue.end, ','),
+                without_blank_lines(&cue.text)
+            )
+        })
+        .collect()
+}
+
+fn render_vtt(cues: &[YtSubtitleCue]) -> String {
+    let mut vtt = String::from("WEBVTT\n\n");
+
+    for cue in cues {
+        vtt.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            format_timestamp(cue.start, '.'),
+            format_timestamp(cue.end, '.'),
+            without_blank_lines(&cue.text.replace('&', "&amp;").replace('<', "&lt;"))
+        ));
+    }
+
+    vtt
+}
+
+fn render_ttml(cues: &[YtSubtitleCue], language_code: &str) -> String {
+    let mut ttml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<tt xmlns=\"http://www.w3.org/ns/ttml\" xml:lang=\"{}\">\n<body>\n<div>\n",
+        escape_xml(language_code)
+    );
+
+    for cue in cues {
+        let lines: Vec<String> = cue.text.lines().map(escape_xml).collect();
+
+        ttml.push_str(&format!(
+            "<p begin=\"{}\" end=\"{}\">{}</p>\n",
+            format_timestamp(cue.start, '.'),
+            format_timestamp(cue.end, '.'),
+            lines.join("<br/>")
+        ));
+    }
+
+    ttml.push_str("</div>\n</body>\n</tt>\n");
+    ttml
+}
+
+fn render_txt(cues: &[YtSubtitleCue]) -> String {
+    cues.iter().map(|cue| format!("{}\n", cue.text)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cues() -> Vec<YtSubtitleCue> {
+        vec![
+            YtSubtitleCue {
+                start: 1.5,
+                end: 4.0,
+                text: "First paragraph\n\nSecond <paragraph>\n \nThird & last".to_string(),
+            },
+            YtSubtitleCue {
+                start: 3661.25,
+                end: 3662.0,
+                text: "Next cue".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn renders_multi_paragraph_srt_cues() {
+        assert_eq!(
+            render_srt(&cues()),
+            "1\n00:00:01,500 --> 00:00:04,000\nFirst paragraph\nSecond <paragraph>\nThird & last\n\n\
+             2\n01:01:01,250 --> 01:01:02,000\nNext cue\n\n"
+        );
+    }
+
+    #[test]
+    fn renders_multi_paragraph_vtt_cues() {
+        assert_eq!(
+            render_vtt(&cues()),
+            "WEBVTT\n\n\
+             00:00:01.500 --> 00:00:04.000\nFirst paragraph\nSecond &lt;paragraph>\nThird &amp; last\n\n\
+             01:01:01.250 --> 01:01:02.000\nNext cue\n\n"
+        );
+    }
+}
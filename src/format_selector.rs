@@ -0,0 +1,770 @@
+//! yt-dlp style format selection, like `bv*[height<=1080][ext=mp4]+ba[ext=m4a]/b`.
+//!
+//! An expression is a list of alternatives separated by `/`, the first one that matches wins.
+//! Each alternative is either a single format or a video and an audio format joined by `+`.
+//! Several expressions can be separated by `,` to select more than one format, `,` binding
+//! looser than `/`, which binds looser than `+`.
+//!
+//! Formats are picked with `b`/`best`, `w`/`worst`, `bv`/`bestvideo`, `wv`/`worstvideo`,
+//! `ba`/`bestaudio` and `wa`/`worstaudio`. Plain `b`/`w` only pick formats with both video and
+//! audio, `bv`/`ba` only video-only and audio-only ones, and a trailing `*` (`b*`, `bv*`, `ba*`)
+//! accepts any format that has the video or audio. A format can also be picked by its itag
+//! (`137`) or by its extension (`mp4`, `webm`, `m4a`).
+//!
+//! Filters are written in brackets after a format:
+//!
+//! - Numbers: `height`, `width`, `fps`, `tbr` (in kbps), `asr`, `filesize` and `filesize_approx`,
+//!   compared with `<`, `<=`, `>`, `>=`, `=` or `!=`. File sizes accept `K`, `M`, `G` and `KiB`, `MiB`, `GiB` suffixes.
+//! - Strings: `ext`, `vcodec`, `acodec`, `language`, `client` and `format_id`, compared with `=`,
+//!   `^=` (starts with), `$=` (ends with), `*=` (contains) or `~=` (regex), any of them negated with `!`.
+//! - `drc`: `[drc=true]` or `[drc=false]` for YouTube's dynamic range compressed audio.
+//!
+//! Formats missing a compared field don't match unless the operator ends with `?`, like `[height<=?1080]`.
+//! Which format is the best or the worst is decided by a `FormatSort`, the default one unless
+//! `FormatSelector::select_sorted` is used. DRM protected formats are never selected.
+
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow, bail};
+use fancy_regex::Regex;
+
+use crate::{
+    format_sort::FormatSort,
+    yt_interface::{Ext, StreamKind, YtFormatSelection, YtStream},
+};
+
+/// A parsed format selector expression.
+///
+/// ```
+/// use tydle::format_selector::FormatSelector;
+///
+/// let selector: FormatSelector = "bv*[height<=1080][ext=mp4]+ba[ext=m4a]/b".parse().unwrap();
+/// assert!(FormatSelector::parse("bv[height<=]").is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct FormatSelector {
+    /// The `,` separated expressions, each a list of `/` separated alternatives.
+    selections: Vec<Vec<FormatChoice>>,
+}
+
+#[derive(Debug, Clone)]
+enum FormatChoice {
+    Single(FormatSpec),
+    Merge(FormatSpec, FormatSpec),
+}
+
+#[derive(Debug, Clone)]
+struct FormatSpec {
+    target: FormatTarget,
+    filters: Vec<FormatFilter>,
+}
+
+#[derive(Debug, Clone)]
+enum FormatTarget {
+    Best(MediaKind),
+    Worst(MediaKind),
+    FormatId(String),
+    Ext(String),
+}
+
+/// What a format has to contain to be picked.
+#[derive(Debug, Clone, Copy)]
+enum MediaKind {
+    /// Both video and audio.
+    Muxed,
+    VideoOnly,
+    AudioOnly,
+    /// Video, with or without audio.
+    AnyVideo,
+    /// Audio, with or without video.
+    AnyAudio,
+    Any,
+}
+
+#[derive(Debug, Clone)]
+struct FormatFilter {
+    condition: FilterCondition,
+    /// Whether formats missing the field pass the filter.
+    allow_missing: bool,
+}
+
+#[derive(Debug, Clone)]
+enum FilterCondition {
+    Number(NumberKey, NumberOp, f64),
+    Text(TextKey, TextOp, bool),
+    Drc(bool),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum NumberKey {
+    Height,
+    Width,
+    Fps,
+    Tbr,
+    Asr,
+    FileSize,
+    FileSizeApprox,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum NumberOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum TextKey {
+    Ext,
+    VCodec,
+    ACodec,
+    Language,
+    Client,
+    FormatId,
+}
+
+#[derive(Debug, Clone)]
+enum TextOp {
+    Eq(String),
+    StartsWith(String),
+    EndsWith(String),
+    Contains(String),
+    Matches(Regex),
+}
+
+/// The video codec like yt-dlp reports it, `none` when the codecs are known and there's no video.
+fn video_codec(stream: &YtStream) -> Option<String> {
+    stream
+        .vcodec
+        .clone()
+        .or_else(|| stream.codecs.as_ref().map(|_| "none".to_string()))
+}
+
+/// The audio codec like yt-dlp reports it, `none` when the codecs are known and there's no audio.
+fn audio_codec(stream: &YtStream) -> Option<String> {
+    stream
+        .acodec
+        .clone()
+        .or_else(|| stream.codecs.as_ref().map(|_| "none".to_string()))
+}
+
+/// The extension as yt-dlp names it.
+fn ext_name(ext: Ext) -> String {
+    match ext {
+        Ext::ThreeGp => "3gp".to_string(),
+        ext => format!("{:?}", ext).to_lowercase(),
+    }
+}
+
+/// yt-dlp's format ID, the itag with a `-drc` suffix for DRC audio.
+fn format_id(stream: &YtStream) -> Option<String> {
+    let itag = stream.itag.as_ref()?;
+
+    Some(match stream.is_drc {
+        true => format!("{}-drc", itag),
+        false => itag.clone(),
+    })
+}
+
+/// The container a stream can be merged into, streams of different ones can't be merged.
+fn container(ext: Ext) -> Option<Ext> {
+    match ext {
+        Ext::Mp4 | Ext::M4a | Ext::M4v | Ext::Mov => Some(Ext::Mp4),
+        Ext::Webm | Ext::Mkv | Ext::Mka => Some(Ext::Webm),
+        _ => None,
+    }
+}
+
+fn parse_size(value: &str) -> Result<f64> {
+    let split_at = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, suffix) = value.split_at(split_at);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| anyhow!("Invalid number \"{}\" in format filter.", value))?;
+    let multiplier = match suffix.trim().to_lowercase().as_str() {
+        "" | "b" => 1.0,
+        "k" | "kb" => 1e3,
+        "m" | "mb" => 1e6,
+        "g" | "gb" => 1e9,
+        "t" | "tb" => 1e12,
+        "kib" => 1024.0,
+        "mib" => 1024.0 * 1024.0,
+        "gib" => 1024.0 * 1024.0 * 1024.0,
+        "tib" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => bail!("Invalid size suffix \"{}\" in format filter.", suffix),
+    };
+
+    Ok(number * multiplier)
+}
+
+impl FormatFilter {
+    fn parse(filter: &str) -> Result<Self> {
+        let filter = filter.trim();
+        let key_len = filter
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .ok_or_else(|| anyhow!("Format filter \"{}\" has no operator.", filter))?;
+        let (key, rest) = filter.split_at(key_len);
+        let rest = rest.trim_start();
+
+        let op_len = rest
+            .find(|c: char| !"!<>=^$*~".contains(c))
+            .unwrap_or(rest.len());
+        let (op, value) = rest.split_at(op_len);
+        let (allow_missing, value) = match value.strip_prefix('?') {
+            Some(value) => (true, value),
+            None => (false, value),
+        };
+        let value = value.trim();
+        let value = match value.len() >= 2
+            && (value.starts_with('"') && value.ends_with('"')
+                || value.starts_with('\'') && value.ends_with('\''))
+        {
+            true => &value[1..value.len() - 1],
+            false => value,
+        };
+
+        if value.is_empty() {
+            bail!("Format filter \"{}\" has no value.", filter)
+        }
+
+        let number_key = match key {
+            "height" => Some(NumberKey::Height),
+            "width" => Some(NumberKey::Width),
+            "fps" => Some(NumberKey::Fps),
+            "tbr" => Some(NumberKey::Tbr),
+            "asr" => Some(NumberKey::Asr),
+            "filesize" => Some(NumberKey::FileSize),
+            "filesize_approx" => Some(NumberKey::FileSizeApprox),
+            _ => None,
+        };
+        let text_key = match key {
+            "ext" => Some(TextKey::Ext),
+            "vcodec" => Some(TextKey::VCodec),
+            "acodec" => Some(TextKey::ACodec),
+            "language" => Some(TextKey::Language),
+            "client" => Some(TextKey::Client),
+            "format_id" => Some(TextKey::FormatId),
+            _ => None,
+        };
+
+        let condition = match (number_key, text_key, key) {
+            (Some(number_key), _, _) => {
+                let op = match op {
+                    "<" => NumberOp::Lt,
+                    "<=" => NumberOp::Le,
+                    ">" => NumberOp::Gt,
+                    ">=" => NumberOp::Ge,
+                    "=" => NumberOp::Eq,
+                    "!=" => NumberOp::Ne,
+                    _ => bail!("Invalid operator \"{}\" for \"{}\".", op, key),
+                };
+
+                FilterCondition::Number(number_key, op, parse_size(value)?)
+            }
+            (_, Some(text_key), _) => {
+                let (negate, op) = match op.strip_prefix('!') {
+                    Some(op) => (true, op),
+                    None => (false, op),
+                };
+                let value = value.to_string();
+                let op = match op {
+                    "=" => TextOp::Eq(value),
+                    "^=" => TextOp::StartsWith(value),
+                    "$=" => TextOp::EndsWith(value),
+                    "*=" => TextOp::Contains(value),
+                    "~=" => TextOp::Matches(Regex::new(&value)?),
+                    _ => bail!("Invalid operator \"{}\" for \"{}\".", op, key),
+                };
+
+                FilterCondition::Text(text_key, op, negate)
+            }
+            (_, _, "drc") => {
+                let expected = match value {
+                    "true" | "yes" | "1" => true,
+                    "false" | "no" | "0" => false,
+                    _ => bail!("\"drc\" can only be compared with true or false."),
+                };
+
+                match op {
+                    "=" => FilterCondition::Drc(expected),
+                    "!=" => FilterCondition::Drc(!expected),
+                    _ => bail!("Invalid operator \"{}\" for \"drc\".", op),
+                }
+            }
+            _ => bail!("Unknown format filter field \"{}\".", key),
+        };
+
+        Ok(Self {
+            condition,
+            allow_missing,
+        })
+    }
+
+    fn matches(&self, stream: &YtStream) -> bool {
+        match &self.condition {
+            FilterCondition::Number(key, op, expected) => {
+                let actual = match key {
+                    NumberKey::Height => stream.height.map(|h| h as f64),
+                    NumberKey::Width => stream.width.map(|w| w as f64),
+                    NumberKey::Fps => stream.fps,
+                    // Streams keep it in bps, yt-dlp compares it in kbps.
+                    NumberKey::Tbr => Some(stream.tbr / 1000.0),
+                    NumberKey::Asr => stream.asr.map(|a| a as f64),
+                    NumberKey::FileSize => stream.file_size.map(|s| s as f64),
+                    NumberKey::FileSizeApprox => stream
+                        .file_size
+                        .map(|s| s as f64)
+                        .or(Some(stream.file_size_approx).filter(|s| *s > 0.0)),
+                };
+                let Some(actual) = actual else {
+                    return self.allow_missing;
+                };
+
+                match op {
+                    NumberOp::Lt => actual < *expected,
+                    NumberOp::Le => actual <= *expected,
+                    NumberOp::Gt => actual > *expected,
+                    NumberOp::Ge => actual >= *expected,
+                    NumberOp::Eq => actual == *expected,
+                    NumberOp::Ne => actual != *expected,
+                }
+            }
+            FilterCondition::Text(key, op, negate) => {
+                let actual = match key {
+                    TextKey::Ext => Some(ext_name(stream.ext)).filter(|e| e != "unknown"),
+                    TextKey::VCodec => video_codec(stream),
+                    TextKey::ACodec => audio_codec(stream),
+                    TextKey::Language => stream.audio_track.language.clone(),
+                    TextKey::Client => Some(stream.client.as_str().to_string()),
+                    TextKey::FormatId => format_id(stream),
+                };
+                let Some(actual) = actual else {
+                    return self.allow_missing;
+                };
+
+                let is_match = match op {
+                    TextOp::Eq(expected) => actual == *expected,
+                    TextOp::StartsWith(expected) => actual.starts_with(expected.as_str()),
+                    TextOp::EndsWith(expected) => actual.ends_with(expected.as_str()),
+                    TextOp::Contains(expected) => actual.contains(expected.as_str()),
+                    TextOp::Matches(re) => re.is_match(&actual).unwrap_or_default(),
+                };
+
+                is_match != *negate
+            }
+            FilterCondition::Drc(expected) => stream.is_drc == *expected,
+        }
+    }
+}
+
+impl FormatSpec {
+    fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        let target_end = spec.find('[').unwrap_or(spec.len());
+        let (target, mut filters_str) = spec.split_at(target_end);
+
+        let target = match target.trim() {
+            "b" | "best" => FormatTarget::Best(MediaKind::Muxed),
+            "b*" | "best*" => FormatTarget::Best(MediaKind::Any),
+            "w" | "worst" => FormatTarget::Worst(MediaKind::Muxed),
+            "w*" | "worst*" => FormatTarget::Worst(MediaKind::Any),
+            "bv" | "bestvideo" => FormatTarget::Best(MediaKind::VideoOnly),
+            "bv*" | "bestvideo*" => FormatTarget::Best(MediaKind::AnyVideo),
+            "wv" | "worstvideo" => FormatTarget::Worst(MediaKind::VideoOnly),
+            "wv*" | "worstvideo*" => FormatTarget::Worst(MediaKind::AnyVideo),
+            "ba" | "bestaudio" => FormatTarget::Best(MediaKind::AudioOnly),
+            "ba*" | "bestaudio*" => FormatTarget::Best(MediaKind::AnyAudio),
+            "wa" | "worstaudio" => FormatTarget::Worst(MediaKind::AudioOnly),
+            "wa*" | "worstaudio*" => FormatTarget::Worst(MediaKind::AnyAudio),
+            "3gp" | "aac" | "flv" | "m4a" | "mp3" | "mp4" | "ogg" | "wav" | "webm" => {
+                FormatTarget::Ext(target.trim().to_string())
+            }
+            // Without a target, the filters apply to the best format of any kind.
+            "" if !filters_str.is_empty() => FormatTarget::Best(MediaKind::Any),
+            id if !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') => {
+                FormatTarget::FormatId(id.to_string())
+            }
+            _ => bail!("Invalid format specification \"{}\".", spec),
+        };
+
+        let mut filters = vec![];
+
+        while !filters_str.is_empty() {
+            let Some(rest) = filters_str.strip_prefix('[') else {
+                bail!("Unexpected \"{}\" in format specification.", filters_str)
+            };
+            let end = rest
+                .find(']')
+                .ok_or_else(|| anyhow!("Unclosed \"[\" in format specification \"{}\".", spec))?;
+
+            filters.push(FormatFilter::parse(&rest[..end])?);
+            filters_str = rest[end + 1..].trim_start();
+        }
+
+        Ok(Self { target, filters })
+    }
+
+    /// The streams this spec accepts, from best to worst, or worst to best for `worst` targets.
+    fn candidates<'a>(&self, streams: &'a [YtStream], sort: &FormatSort) -> Vec<&'a YtStream> {
+        let kind = match &self.target {
+            FormatTarget::Best(kind) | FormatTarget::Worst(kind) => *kind,
+            FormatTarget::FormatId(_) | FormatTarget::Ext(_) => MediaKind::Any,
+        };
+
+        let mut candidates: Vec<&YtStream> = streams
+            .iter()
+            .filter(|stream| !stream.has_drm)
+            .filter(|stream| match kind {
+                MediaKind::Muxed => stream.kind() == StreamKind::Muxed,
+                MediaKind::VideoOnly => stream.kind() == StreamKind::VideoOnly,
+                MediaKind::AudioOnly => stream.kind() == StreamKind::AudioOnly,
+                MediaKind::AnyVideo => stream.kind() != StreamKind::AudioOnly,
+                MediaKind::AnyAudio => stream.kind() != StreamKind::VideoOnly,
+                MediaKind::Any => true,
+            })
+            .filter(|stream| match &self.target {
+                FormatTarget::FormatId(id) => {
+                    stream.itag.as_ref() == Some(id) || format_id(stream).as_ref() == Some(id)
+                }
+                FormatTarget::Ext(ext) => ext_name(stream.ext) == *ext,
+                _ => true,
+            })
+            .filter(|stream| self.filters.iter().all(|filter| filter.matches(stream)))
+            .collect();
+
+        candidates.sort_by(|a, b| match &self.target {
+            FormatTarget::Worst(_) => sort.compare(a, b),
+            _ => sort.compare(b, a),
+        });
+
+        candidates
+    }
+}
+
+/// Split on `separator` outside of brackets and quotes.
+fn split_top_level(expression: &str, separator: char) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+
+    for (i, c) in expression.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') if depth > 0 => quote = Some(c),
+            (None, '[') => depth += 1,
+            (None, ']') => depth -= 1,
+            (None, c) if c == separator && depth == 0 => {
+                parts.push(&expression[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+
+    parts.push(&expression[start..]);
+    parts
+}
+
+impl FormatSelector {
+    /// Parse a format selector expression.
+    pub fn parse(expression: &str) -> Result<Self> {
+        if expression.trim().is_empty() {
+            bail!("The format selector is empty.")
+        }
+
+        let selections = split_top_level(expression, ',')
+            .into_iter()
+            .map(|selection| {
+                split_top_level(selection, '/')
+                    .into_iter()
+                    .map(
+                        |alternative| match split_top_level(alternative, '+').as_slice() {
+                            [single] => Ok(FormatChoice::Single(FormatSpec::parse(single)?)),
+                            [video, audio] => Ok(FormatChoice::Merge(
+                                FormatSpec::parse(video)?,
+                                FormatSpec::parse(audio)?,
+                            )),
+                            _ => bail!(
+                                "Only one video and one audio format can be merged, got \"{}\".",
+                                alternative.trim()
+                            ),
+                        },
+                    )
+                    .collect::<Result<_>>()
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { selections })
+    }
+
+    /// Pick the first alternative that matches, `None` if none of them do.
+    /// With `,` separated expressions, the first of them that matches is picked, see `select_all`.
+    ///
+    /// Merged alternatives only match a video and an audio format that share a container,
+    /// MP4 with M4A or WebM with WebM, so they can be passed to `muxer::mux` as they are.
+    pub fn select(&self, streams: &[YtStream]) -> Option<YtFormatSelection> {
+        self.select_sorted(streams, &FormatSort::default())
+    }
+
+    /// Like `select`, with `sort` deciding which formats are the best.
+    pub fn select_sorted(
+        &self,
+        streams: &[YtStream],
+        sort: &FormatSort,
+    ) -> Option<YtFormatSelection> {
+        self.selections
+            .iter()
+            .find_map(|alternatives| Self::select_alternative(alternatives, streams, sort))
+    }
+
+    /// Pick a format for each of the `,` separated expressions, leaving out the ones that match nothing.
+    pub fn select_all(&self, streams: &[YtStream]) -> Vec<YtFormatSelection> {
+        self.select_all_sorted(streams, &FormatSort::default())
+    }
+
+    /// Like `select_all`, with `sort` deciding which formats are the best.
+    pub fn select_all_sorted(
+        &self,
+        streams: &[YtStream],
+        sort: &FormatSort,
+    ) -> Vec<YtFormatSelection> {
+        self.selections
+            .iter()
+            .filter_map(|alternatives| Self::select_alternative(alternatives, streams, sort))
+            .collect()
+    }
+
+    fn select_alternative(
+        alternatives: &[FormatChoice],
+        streams: &[YtStream],
+        sort: &FormatSort,
+    ) -> Option<YtFormatSelection> {
+        alternatives
+            .iter()
+            .find_map(|alternative| match alternative {
+                FormatChoice::Single(spec) => spec
+                    .candidates(streams, sort)
+                    .first()
+                    .map(|stream| YtFormatSelection::Single(Box::new((*stream).clone()))),
+                FormatChoice::Merge(video_spec, audio_spec) => {
+                    let videos = video_spec.candidates(streams, sort);
+                    let audios = audio_spec.candidates(streams, sort);
+
+                    videos.iter().find_map(|video| {
+                        let video_container = container(video.ext)?;
+
+                        audios
+                            .iter()
+                            .find(|audio| container(audio.ext) == Some(video_container))
+                            .map(|audio| YtFormatSelection::Merged {
+                                video: Box::new((*video).clone()),
+                                audio: Box::new((*audio).clone()),
+                            })
+                    })
+                }
+            })
+    }
+}
+
+impl FromStr for FormatSelector {
+    type Err = anyhow::Error;
+
+    fn from_str(expression: &str) -> Result<Self> {
+        Self::parse(expression)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yt_interface::{AudioTrackInfo, YtClient, YtStreamSource};
+
+    fn stream(
+        itag: &str,
+        ext: Ext,
+        height: Option<u64>,
+        vcodec: Option<&str>,
+        acodec: Option<&str>,
+        tbr: f64,
+    ) -> YtStream {
+        let codecs: Vec<&str> = vcodec.into_iter().chain(acodec).collect();
+
+        YtStream {
+            asr: acodec.map(|_| 48000),
+            audio_channels: acodec.map(|_| 2),
+            file_size: None,
+            file_size_approx: 0.0,
+            height,
+            width: height.map(|h| h * 16 / 9),
+            fps: vcodec.map(|_| 30.0),
+            format_duration: 0.0,
+            has_drm: false,
+            itag: Some(itag.to_string()),
+            source: YtStreamSource::URL(format!("https://example.com/{}", itag)),
+            tbr,
+            audio_track: AudioTrackInfo {
+                display_name: None,
+                language: None,
+                is_default: true,
+            },
+            quality_label: String::new(),
+            is_drc: false,
+            projection: None,
+            spatial_audio: None,
+            client: YtClient::Web,
+            clients: vec![YtClient::Web],
+            ext,
+            is_live: false,
+            mime_type: None,
+            codecs: Some(codecs.join(", ")),
+            vcodec: vcodec.map(str::to_string),
+            acodec: acodec.map(str::to_string),
+            loudness_db: None,
+            color_info: None,
+            dynamic_range: None,
+            init_range: None,
+            index_range: None,
+            last_modified: None,
+        }
+    }
+
+    fn streams() -> Vec<YtStream> {
+        let mut streams = vec![
+            stream(
+                "18",
+                Ext::Mp4,
+                Some(360),
+                Some("avc1.42001E"),
+                Some("mp4a.40.2"),
+                500_000.0,
+            ),
+            stream(
+                "22",
+                Ext::Mp4,
+                Some(720),
+                Some("avc1.64001F"),
+                Some("mp4a.40.2"),
+                1_500_000.0,
+            ),
+            stream(
+                "136",
+                Ext::Mp4,
+                Some(720),
+                Some("avc1.4d401f"),
+                None,
+                2_000_000.0,
+            ),
+            stream(
+                "137",
+                Ext::Mp4,
+                Some(1080),
+                Some("avc1.640028"),
+                None,
+                4_000_000.0,
+            ),
+            stream(
+                "248",
+                Ext::Webm,
+                Some(1080),
+                Some("vp09.00.40.08"),
+                None,
+                3_500_000.0,
+            ),
+            stream("140", Ext::M4a, None, None, Some("mp4a.40.2"), 130_000.0),
+            stream("249", Ext::Webm, None, None, Some("opus"), 50_000.0),
+            stream("251", Ext::Webm, None, None, Some("opus"), 140_000.0),
+        ];
+        streams[5].file_size = Some(3_000_000);
+        streams
+    }
+
+    /// The itags of a selection, the video's first for merged ones.
+    fn itags(selection: &YtFormatSelection) -> Vec<&str> {
+        match selection {
+            YtFormatSelection::Single(stream) => vec![stream.itag.as_deref().unwrap()],
+            YtFormatSelection::Merged { video, audio } => vec![
+                video.itag.as_deref().unwrap(),
+                audio.itag.as_deref().unwrap(),
+            ],
+        }
+    }
+
+    fn select(expression: &str) -> Option<Vec<String>> {
+        let selection = FormatSelector::parse(expression)
+            .unwrap()
+            .select(&streams())?;
+
+        Some(itags(&selection).into_iter().map(str::to_string).collect())
+    }
+
+    #[test]
+    fn selects_best_and_worst_forms() {
+        assert_eq!(select("b").unwrap(), ["22"]);
+        assert_eq!(select("best").unwrap(), ["22"]);
+        assert_eq!(select("w").unwrap(), ["18"]);
+        assert_eq!(select("bv").unwrap(), ["248"]);
+        assert_eq!(select("wv").unwrap(), ["136"]);
+        assert_eq!(select("ba").unwrap(), ["251"]);
+        // The codec ranks before the bitrate, AAC below Opus.
+        assert_eq!(select("worstaudio").unwrap(), ["140"]);
+        assert_eq!(select("wa[ext=webm]").unwrap(), ["249"]);
+        // Starred forms also accept muxed formats.
+        assert_eq!(select("bv*[height<=720]").unwrap(), ["22"]);
+        assert_eq!(select("wv*").unwrap(), ["18"]);
+        assert_eq!(select("137").unwrap(), ["137"]);
+        assert_eq!(select("m4a").unwrap(), ["140"]);
+    }
+
+    #[test]
+    fn binds_plus_tighter_than_slash_and_slash_tighter_than_comma() {
+        assert_eq!(select("bv[ext=mp4]+ba[ext=m4a]/b").unwrap(), ["137", "140"]);
+        assert_eq!(select("bv[height>1080]+ba/b").unwrap(), ["22"]);
+        // Only formats sharing a container are merged.
+        assert_eq!(select("bv[ext=webm]+ba[ext=m4a]"), None);
+        assert_eq!(select("bv+ba").unwrap(), ["248", "251"]);
+
+        let selector = FormatSelector::parse("bv[height>1080]+ba/w,ba").unwrap();
+        let selected = selector.select_all(&streams());
+        let selections: Vec<Vec<&str>> = selected.iter().map(itags).collect();
+        assert_eq!(selections, [["18"], ["251"]]);
+        assert_eq!(select("bv[height>1080],ba").unwrap(), ["251"]);
+    }
+
+    #[test]
+    fn applies_filters() {
+        // `tbr` is in kbps.
+        assert_eq!(select("b[tbr<=1000]").unwrap(), ["18"]);
+        assert_eq!(select("b[tbr>500]").unwrap(), ["22"]);
+        assert_eq!(select("bv[height<=720]").unwrap(), ["136"]);
+        assert_eq!(select("bv[height=1080][vcodec!^=vp09]").unwrap(), ["137"]);
+        assert_eq!(select("ba[acodec^=mp4a]").unwrap(), ["140"]);
+        assert_eq!(select("ba[filesize<5M]").unwrap(), ["140"]);
+        assert_eq!(select("ba[filesize<?5M]").unwrap(), ["251"]);
+        assert_eq!(select("ba[filesize>5MiB]"), None);
+        // Separators inside brackets and quotes don't split the expression.
+        assert_eq!(select("ba[format_id~='^(140|x/y,z)$']").unwrap(), ["140"]);
+        assert_eq!(select("[height=720]").unwrap(), ["22"]);
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        for expression in [
+            "",
+            "bv[height<=]",
+            "bv[height<=1080",
+            "bv[height~=1080]",
+            "bv[bitrate>1]",
+            "bv+ba+ba",
+            "b[drc=maybe]",
+        ] {
+            assert!(FormatSelector::parse(expression).is_err(), "{}", expression);
+        }
+    }
+}
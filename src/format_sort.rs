@@ -0,0 +1,581 @@
+//! yt-dlp style format sorting, like `-S res:1080,fps,+size`.
+//!
+//! A sort is an ordered list of fields, each one only breaks the ties of the fields before it.
+//! By default a field prefers larger values, a leading `+` makes it prefer smaller ones.
+//! A field can also carry a preferred value:
+//!
+//! - `field:value` prefers the largest value that isn't larger than `value` (smallest that
+//!   isn't smaller with `+`), then falls back to the values past it.
+//! - `field~value` prefers the value closest to `value`.
+//!
+//! The fields are `res`, `fps`, `vcodec`, `acodec`, `hdr`, `channels`, `asr`, `size`, `br` (in kbps),
+//! `proto`, `client` and `lang`. Codecs, HDR and protocols compare by preference, so `vcodec:h264`
+//! prefers H.264 over every codec ranked above it. `client` and `lang` take a name to prefer,
+//! without one they prefer the clients of `FormatSort::client_priority` and the default audio track.
+//! Formats missing a field always sort after the ones that have it.
+
+use std::{cmp::Ordering, str::FromStr};
+
+use anyhow::{Result, anyhow, bail};
+
+use crate::{
+    extractor::client::client_rank,
+    yt_interface::{YtClient, YtDynamicRange, YtStream, YtStreamSource},
+};
+
+/// A field streams can be sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    /// The smaller dimension of the video, so vertical videos compare like horizontal ones.
+    Resolution,
+    Fps,
+    /// Video codec preference, `av01` > `vp9.2` > `vp9` > `h265` > `h264` > `vp8`.
+    VideoCodec,
+    /// Audio codec preference, `flac` > `opus` > `vorbis` > `aac` > `mp3` > `eac3` > `ac3`.
+    AudioCodec,
+    /// Dynamic range, `dv` > `12` > `10+` > `10` > `hlg` > `sdr`. The default sort stops at `12`.
+    Hdr,
+    AudioChannels,
+    Asr,
+    FileSize,
+    Bitrate,
+    /// Protocol preference, `https` > `m3u8` > `dash`.
+    Protocol,
+    Client,
+    Language,
+}
+
+/// The value a field is compared against.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortPreference {
+    /// Prefer values up to this one, or from this one on when the field is reversed.
+    Limit(f64),
+    /// Prefer values closest to this one.
+    Closest(f64),
+    /// Prefer formats with exactly this client or language.
+    Exactly(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortKey {
+    pub field: SortField,
+    /// Prefer smaller values instead of larger ones.
+    pub reverse: bool,
+    pub preference: Option<SortPreference>,
+}
+
+/// An ordered list of fields to sort streams by.
+///
+/// ```
+/// use tydle::format_sort::{FormatSort, SortField};
+///
+/// // Prefer 1080p or less, then the smallest file.
+/// let sort: FormatSort = "res:1080,+size".parse().unwrap();
+/// assert_eq!(sort.keys[1].field, SortField::FileSize);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatSort {
+    pub keys: Vec<SortKey>,
+    /// Clients `client` prefers when it isn't given one, most preferred first.
+    /// Uses the priority of each client when empty, like `TydleOptions::client_priority`.
+    pub client_priority: Vec<YtClient>,
+}
+
+const VIDEO_CODEC_PREFERENCE: [(&str, f64); 12] = [
+    ("av01", 7.0),
+    ("dav1", 7.0),
+    ("vp09.02", 6.0),
+    ("vp9.2", 6.0),
+    ("vp09", 5.0),
+    ("vp9", 5.0),
+    ("hev1", 4.0),
+    ("hvc1", 4.0),
+    ("dvh1", 4.0),
+    ("dvhe", 4.0),
+    ("avc", 3.0),
+    ("vp8", 2.0),
+];
+const AUDIO_CODEC_PREFERENCE: [(&str, f64); 8] = [
+    ("flac", 7.0),
+    ("alac", 7.0),
+    ("opus", 6.0),
+    ("vorbis", 5.0),
+    ("mp4a", 4.0),
+    ("mp3", 3.0),
+    ("ec-3", 2.0),
+    ("ac-3", 1.0),
+];
+
+/// Codec names as written in a sort, mapped to their preference.
+fn codec_rank(field: SortField, name: &str) -> Option<f64> {
+    let rank = match (field, name) {
+        (SortField::VideoCodec, "av01" | "av1") => 7.0,
+        (SortField::VideoCodec, "vp9.2" | "vp09.02") => 6.0,
+        (SortField::VideoCodec, "vp9" | "vp09") => 5.0,
+        (SortField::VideoCodec, "h265" | "hevc" | "hev1" | "hvc1") => 4.0,
+        (SortField::VideoCodec, "h264" | "avc" | "avc1") => 3.0,
+        (SortField::VideoCodec, "vp8") => 2.0,
+        (SortField::AudioCodec, "flac") => 7.0,
+        (SortField::AudioCodec, "opus") => 6.0,
+        (SortField::AudioCodec, "vorbis") => 5.0,
+        (SortField::AudioCodec, "aac" | "mp4a") => 4.0,
+        (SortField::AudioCodec, "mp3") => 3.0,
+        (SortField::AudioCodec, "eac3" | "ec-3") => 2.0,
+        (SortField::AudioCodec, "ac3" | "ac-3") => 1.0,
+        (SortField::Hdr, "sdr") => 0.0,
+        (SortField::Hdr, "hlg") => 1.0,
+        (SortField::Hdr, "10" | "hdr10") => 2.0,
+        (SortField::Hdr, "10+" | "hdr10+") => 3.0,
+        (SortField::Hdr, "12" | "hdr12") => 4.0,
+        (SortField::Hdr, "dv") => 5.0,
+        (SortField::Protocol, "dash") => 1.0,
+        (SortField::Protocol, "m3u8" | "hls") => 2.0,
+        (SortField::Protocol, "https" | "http") => 3.0,
+        _ => return None,
+    };
+
+    Some(rank)
+}
+
+fn codec_preference(codec: Option<&str>, preference: &[(&str, f64)]) -> Option<f64> {
+    let codec = codec?;
+
+    preference
+        .iter()
+        .find(|(prefix, _)| codec.starts_with(prefix))
+        .map(|(_, rank)| *rank)
+}
+
+fn hdr_rank(dynamic_range: YtDynamicRange) -> f64 {
+    match dynamic_range {
+        YtDynamicRange::Sdr => 0.0,
+        YtDynamicRange::Hlg => 1.0,
+        YtDynamicRange::Hdr10 => 2.0,
+        YtDynamicRange::Hdr12 => 4.0,
+        YtDynamicRange::DolbyVision => 5.0,
+    }
+}
+
+fn parse_number(value: &str) -> Result<f64> {
+    let split_at = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, suffix) = value.split_at(split_at);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| anyhow!("Invalid number \"{}\" in format sort.", value))?;
+    let multiplier = match suffix.to_lowercase().as_str() {
+        "" | "p" | "b" => 1.0,
+        "k" | "kb" => 1e3,
+        "m" | "mb" => 1e6,
+        "g" | "gb" => 1e9,
+        "kib" => 1024.0,
+        "mib" => 1024.0 * 1024.0,
+        "gib" => 1024.0 * 1024.0 * 1024.0,
+        _ => bail!("Invalid suffix \"{}\" in format sort.", suffix),
+    };
+
+    Ok(number * multiplier)
+}
+
+impl SortKey {
+    pub fn new(field: SortField) -> Self {
+        Self {
+            field,
+            reverse: false,
+            preference: None,
+        }
+    }
+
+    fn parse(key: &str) -> Result<Self> {
+        let key = key.trim();
+        let (reverse, key) = match key.strip_prefix('+') {
+            Some(key) => (true, key),
+            None => (false, key),
+        };
+        let (name, preference) = match key.find([':', '~']) {
+            Some(at) => (&key[..at], Some((&key[at..at + 1], &key[at + 1..]))),
+            None => (key, None),
+        };
+
+        let field = match name {
+            "res" => SortField::Resolution,
+            "fps" => SortField::Fps,
+            "vcodec" => SortField::VideoCodec,
+            "acodec" => SortField::AudioCodec,
+            "hdr" => SortField::Hdr,
+            "channels" => SortField::AudioChannels,
+            "asr" => SortField::Asr,
+            "size" | "filesize" => SortField::FileSize,
+            "br" | "tbr" => SortField::Bitrate,
+            "proto" => SortField::Protocol,
+            "client" => SortField::Client,
+            "lang" => SortField::Language,
+            _ => bail!("Unknown format sort field \"{}\".", name),
+        };
+
+        let preference = match preference {
+            None => None,
+            Some((_, "")) => bail!("Format sort field \"{}\" has no value.", name),
+            Some((separator, value)) => {
+                let value = value.to_lowercase();
+                let rank = match field {
+                    SortField::Client | SortField::Language => None,
+                    SortField::VideoCodec
+                    | SortField::AudioCodec
+                    | SortField::Hdr
+                    | SortField::Protocol => Some(codec_rank(field, &value).ok_or_else(|| {
+                        anyhow!(
+                            "Unknown value \"{}\" for format sort field \"{}\".",
+                            value,
+                            name
+                        )
+                    })?),
+                    _ => Some(parse_number(&value)?),
+                };
+
+                Some(match (rank, separator) {
+                    (None, ":") => SortPreference::Exactly(value),
+                    (None, _) => bail!("\"{}\" can't be sorted by closeness.", name),
+                    (Some(rank), ":") => SortPreference::Limit(rank),
+                    (Some(rank), _) => SortPreference::Closest(rank),
+                })
+            }
+        };
+
+        Ok(Self {
+            field,
+            reverse,
+            preference,
+        })
+    }
+
+    fn value(&self, stream: &YtStream, client_priority: &[YtClient]) -> Option<f64> {
+        match self.field {
+            SortField::Resolution => match (stream.width, stream.height) {
+                (Some(width), Some(height)) => Some(width.min(height) as f64),
+                (width, height) => height.or(width).map(|r| r as f64),
+            },
+            SortField::Fps => stream.fps,
+            SortField::VideoCodec => {
+                codec_preference(stream.vcodec.as_deref(), &VIDEO_CODEC_PREFERENCE)
+            }
+            SortField::AudioCodec => {
+                codec_preference(stream.acodec.as_deref(), &AUDIO_CODEC_PREFERENCE)
+            }
+            SortField::Hdr => stream.dynamic_range.map(hdr_rank),
+            SortField::AudioChannels => stream.audio_channels.map(|c| c as f64),
+            SortField::Asr => stream.asr.map(|a| a as f64),
+            SortField::FileSize => stream
+                .file_size
+                .map(|s| s as f64)
+                .or(Some(stream.file_size_approx).filter(|s| *s > 0.0)),
+            // Streams keep it in bps, yt-dlp sorts it in kbps.
+            SortField::Bitrate => Some(stream.tbr / 1000.0),
+            SortField::Protocol => Some(match stream.source {
+                YtStreamSource::URL(_) | YtStreamSource::Signature(_) => 3.0,
+                YtStreamSource::Hls(_) => 2.0,
+                YtStreamSource::Dash(_) => 1.0,
+            }),
+            SortField::Client => Some(match &self.preference {
+                Some(SortPreference::Exactly(client)) => {
+                    (stream.client.as_str() == client) as u8 as f64
+                }
+                _ => -client_rank(&stream.client, client_priority) as f64,
+            }),
+            SortField::Language => match &self.preference {
+                Some(SortPreference::Exactly(language)) => {
+                    let track_language = stream.audio_track.language.as_ref()?.to_lowercase();
+
+                    // `en` also matches regional variants like `en-US`.
+                    Some(
+                        (track_language == *language
+                            || track_language.starts_with(&format!("{}-", language)))
+                            as u8 as f64,
+                    )
+                }
+                _ => Some(stream.audio_track.is_default as u8 as f64),
+            },
+        }
+    }
+
+    /// `Greater` when `a` is the better stream.
+    fn compare(&self, a: &YtStream, b: &YtStream, client_priority: &[YtClient]) -> Ordering {
+        let (a, b) = match (
+            self.value(a, client_priority),
+            self.value(b, client_priority),
+        ) {
+            (Some(a), Some(b)) => (a, b),
+            (Some(_), None) => return Ordering::Greater,
+            (None, Some(_)) => return Ordering::Less,
+            (None, None) => return Ordering::Equal,
+        };
+        // Without a preference, larger is better unless the field is reversed.
+        let by_direction = match self.reverse {
+            true => b.total_cmp(&a),
+            false => a.total_cmp(&b),
+        };
+
+        match &self.preference {
+            Some(SortPreference::Limit(limit)) => {
+                let within = |value: f64| match self.reverse {
+                    true => value >= *limit,
+                    false => value <= *limit,
+                };
+
+                match (within(a), within(b)) {
+                    (true, false) => Ordering::Greater,
+                    (false, true) => Ordering::Less,
+                    (true, true) => by_direction,
+                    // Past the limit, the values nearest to it are the better ones.
+                    (false, false) => by_direction.reverse(),
+                }
+            }
+            Some(SortPreference::Closest(target)) => (b - target)
+                .abs()
+                .total_cmp(&(a - target).abs())
+                .then(by_direction),
+            Some(SortPreference::Exactly(_)) | None => by_direction,
+        }
+    }
+}
+
+impl FormatSort {
+    /// Parse a comma separated sort, like `res:1080,fps,+size`.
+    ///
+    /// Like yt-dlp, the default fields that aren't mentioned still break the remaining ties.
+    pub fn parse(sort: &str) -> Result<Self> {
+        let keys = sort
+            .split(',')
+            .filter(|key| !key.trim().is_empty())
+            .map(SortKey::parse)
+            .collect::<Result<_>>()?;
+
+        Ok(Self::with_priority(keys))
+    }
+
+    /// Compare two streams field by field, `Greater` when `a` is the better stream.
+    pub fn compare(&self, a: &YtStream, b: &YtStream) -> Ordering {
+        self.keys
+            .iter()
+            .map(|key| key.compare(a, b, &self.client_priority))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+
+    /// Put the given keys first and keep the remaining default ones as tie breakers.
+    pub fn with_priority(keys: Vec<SortKey>) -> Self {
+        let mut sort = Self::default();
+        sort.keys
+            .retain(|key| !keys.iter().any(|k| k.field == key.field));
+        sort.keys.splice(0..0, keys);
+
+        sort
+    }
+
+    /// Rank the clients `client` prefers by `client_priority` instead of their own priority.
+    pub fn with_client_priority(mut self, client_priority: Vec<YtClient>) -> Self {
+        self.client_priority = client_priority;
+        self
+    }
+}
+
+impl Default for FormatSort {
+    /// The default audio track, then the sharpest and smoothest video up to HDR12 in the best
+    /// codec, then the audio quality, and bitrate and protocol as the last resort.
+    fn default() -> Self {
+        Self {
+            keys: [
+                SortField::Language,
+                SortField::Resolution,
+                SortField::Fps,
+                SortField::Hdr,
+                SortField::VideoCodec,
+                SortField::AudioChannels,
+                SortField::AudioCodec,
+                SortField::FileSize,
+                SortField::Bitrate,
+                SortField::Asr,
+                SortField::Protocol,
+                SortField::Client,
+            ]
+            .into_iter()
+            .map(|field| match field {
+                // Like yt-dlp's `hdr:12`, Dolby Vision is only preferred when asked for since
+                // few players can decode it.
+                SortField::Hdr => SortKey {
+                    preference: Some(SortPreference::Limit(hdr_rank(YtDynamicRange::Hdr12))),
+                    ..SortKey::new(field)
+                },
+                field => SortKey::new(field),
+            })
+            .collect(),
+            client_priority: vec![],
+        }
+    }
+}
+
+impl FromStr for FormatSort {
+    type Err = anyhow::Error;
+
+    fn from_str(sort: &str) -> Result<Self> {
+        Self::parse(sort)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yt_interface::{AudioTrackInfo, Ext};
+
+    fn stream(itag: &str, height: u64, tbr: f64) -> YtStream {
+        YtStream {
+            asr: None,
+            audio_channels: None,
+            file_size: None,
+            file_size_approx: 0.0,
+            height: Some(height),
+            width: Some(height * 16 / 9),
+            fps: Some(30.0),
+            format_duration: 0.0,
+            has_drm: false,
+            itag: Some(itag.to_string()),
+            source: YtStreamSource::URL(format!("https://example.com/{}", itag)),
+            tbr,
+            audio_track: AudioTrackInfo {
+                display_name: None,
+                language: None,
+                is_default: true,
+            },
+            quality_label: String::new(),
+            is_drc: false,
+            projection: None,
+            spatial_audio: None,
+            client: YtClient::Web,
+            clients: vec![YtClient::Web],
+            ext: Ext::Mp4,
+            is_live: false,
+            mime_type: Some("video/mp4".to_string()),
+            codecs: Some("avc1.640028".to_string()),
+            vcodec: Some("avc1.640028".to_string()),
+            acodec: None,
+            loudness_db: None,
+            color_info: None,
+            dynamic_range: Some(YtDynamicRange::Sdr),
+            init_range: None,
+            index_range: None,
+            last_modified: None,
+        }
+    }
+
+    /// The itags from best to worst.
+    fn sorted(sort: &str, mut streams: Vec<YtStream>) -> Vec<String> {
+        let sort = FormatSort::parse(sort).unwrap();
+        streams.sort_by(|a, b| sort.compare(b, a));
+
+        streams.into_iter().map(|s| s.itag.unwrap()).collect()
+    }
+
+    fn resolutions() -> Vec<YtStream> {
+        vec![
+            stream("480", 480, 1_000_000.0),
+            stream("1440", 1440, 9_000_000.0),
+            stream("720", 720, 2_000_000.0),
+            stream("1080", 1080, 4_000_000.0),
+        ]
+    }
+
+    #[test]
+    fn parses_keys_before_the_default_ones() {
+        let sort = FormatSort::parse("+res:1080, vcodec~vp9,client:IOS,size:50MiB").unwrap();
+
+        assert_eq!(
+            sort.keys[..4],
+            [
+                SortKey {
+                    field: SortField::Resolution,
+                    reverse: true,
+                    preference: Some(SortPreference::Limit(1080.0)),
+                },
+                SortKey {
+                    field: SortField::VideoCodec,
+                    reverse: false,
+                    preference: Some(SortPreference::Closest(5.0)),
+                },
+                SortKey {
+                    field: SortField::Client,
+                    reverse: false,
+                    preference: Some(SortPreference::Exactly("ios".to_string())),
+                },
+                SortKey {
+                    field: SortField::FileSize,
+                    reverse: false,
+                    preference: Some(SortPreference::Limit(50.0 * 1024.0 * 1024.0)),
+                },
+            ]
+        );
+        // The default fields left break the remaining ties, in their default order.
+        assert_eq!(sort.keys.len(), FormatSort::default().keys.len());
+        assert_eq!(sort.keys[4].field, SortField::Language);
+    }
+
+    #[test]
+    fn rejects_invalid_keys() {
+        for sort in ["height", "res:", "res:10q", "vcodec:mpeg2", "client~ios"] {
+            assert!(FormatSort::parse(sort).is_err(), "{}", sort);
+        }
+    }
+
+    #[test]
+    fn sorts_by_direction() {
+        assert_eq!(sorted("res", resolutions()), ["1440", "1080", "720", "480"]);
+        assert_eq!(
+            sorted("+res", resolutions()),
+            ["480", "720", "1080", "1440"]
+        );
+    }
+
+    #[test]
+    fn prefers_values_within_limits() {
+        // Up to the limit first, then the values nearest past it.
+        assert_eq!(
+            sorted("res:720", resolutions()),
+            ["720", "480", "1080", "1440"]
+        );
+        assert_eq!(
+            sorted("+res:720", resolutions()),
+            ["720", "1080", "1440", "480"]
+        );
+        assert_eq!(
+            sorted("res~1000", resolutions()),
+            ["1080", "720", "1440", "480"]
+        );
+    }
+
+    #[test]
+    fn compares_bitrates_in_kbps() {
+        let streams = vec![
+            stream("a", 720, 500_000.0),
+            stream("b", 720, 1_500_000.0),
+            stream("c", 720, 900_000.0),
+        ];
+
+        assert_eq!(sorted("br:1000", streams.clone()), ["c", "a", "b"]);
+        assert_eq!(sorted("+br", streams), ["a", "c", "b"]);
+    }
+
+    #[test]
+    fn falls_back_to_approximate_file_sizes() {
+        let mut streams = resolutions();
+        streams[0].file_size = Some(30_000_000);
+        streams[1].file_size_approx = 10_000_000.0;
+        streams[2].file_size_approx = 20_000_000.0;
+
+        // Formats without any size sort last either way.
+        assert_eq!(
+            sorted("+size", streams.clone()),
+            ["1440", "720", "480", "1080"]
+        );
+        assert_eq!(sorted("size", streams), ["480", "720", "1440", "1080"]);
+    }
+}
@@ -4,8 +4,14 @@ mod extractor;
 mod utils;
 
 pub mod cookies;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod downloader;
+pub mod format_selector;
+pub mod format_sort;
 #[cfg(feature = "logging")]
 pub mod logger;
+pub mod muxer;
+pub mod solver;
 pub mod tydle;
 pub mod yt_interface;
 
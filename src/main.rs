@@ -17,6 +17,7 @@ struct TydleArgs {
     /// Use an unencrypted connection to retrieve information about the video.
     #[arg(long)]
     prefer_insecure: bool,
+    /// Video ID or any YouTube video URL.
     video_id: String,
 }
 
@@ -45,9 +46,10 @@ async fn run() -> Result<()> {
         auth_cookies,
         prefer_insecure: args.prefer_insecure,
         source_address: args.source_ip.unwrap_or_default(),
+        ..Default::default()
     })?;
 
-    let video_id = VideoId::new(args.video_id)?;
+    let video_id = VideoId::from_url(&args.video_id)?;
     let streams = tydle.get_streams(&video_id).await?;
 
     println!("{:#?}", streams);
@@ -0,0 +1,47 @@
+//! Merge a video-only and an audio-only stream into one file without external tools.
+//!
+//! YouTube serves its adaptive formats as fragmented MP4/M4A or WebM, these are rewritten
+//! in memory so the result plays anywhere, including in the browser through wasm.
+
+mod mp4;
+mod webm;
+
+use anyhow::{Result, bail};
+
+pub use crate::muxer::mp4::mux_mp4;
+pub use crate::muxer::webm::mux_webm;
+use crate::yt_interface::Ext;
+
+/// Guess the container of a downloaded stream from its first bytes.
+pub fn detect_container(data: &[u8]) -> Option<Ext> {
+    match data {
+        [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some(Ext::Mp4),
+        [0x1A, 0x45, 0xDF, 0xA3, ..] => Some(Ext::Webm),
+        _ => None,
+    }
+}
+
+/// Merge a video-only and an audio-only stream, both must use the same container.
+///
+/// MP4 video with M4A audio gives an MP4, WebM video with WebM (Opus or Vorbis) audio gives a WebM.
+///
+/// ```no_run
+/// use tydle::muxer::mux;
+///
+/// let video = std::fs::read("video.mp4").unwrap();
+/// let audio = std::fs::read("audio.m4a").unwrap();
+/// std::fs::write("merged.mp4", mux(&video, &audio).unwrap()).unwrap();
+/// ```
+pub fn mux(video: &[u8], audio: &[u8]) -> Result<Vec<u8>> {
+    match (detect_container(video), detect_container(audio)) {
+        (Some(Ext::Mp4), Some(Ext::Mp4)) => mux_mp4(video, audio),
+        (Some(Ext::Webm), Some(Ext::Webm)) => mux_webm(video, audio),
+        (None, _) => bail!("The video is neither an MP4 nor a WebM file."),
+        (_, None) => bail!("The audio is neither an M4A nor a WebM file."),
+        (Some(video_ext), Some(audio_ext)) => bail!(
+            "Can't merge {:?} video with {:?} audio, both streams must use the same container.",
+            video_ext,
+            audio_ext
+        ),
+    }
+}
@@ -0,0 +1,790 @@
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow, bail};
+
+/// A box inside an ISO BMFF file, `start..end` spans its header and payload.
+#[derive(Clone, Copy)]
+struct Mp4Box {
+    box_type: [u8; 4],
+    start: usize,
+    payload_start: usize,
+    end: usize,
+}
+
+/// A `moof` with the `mdat` boxes that follow it.
+struct Fragment<'a> {
+    data: &'a [u8],
+    /// Where the `moof` started in its source file.
+    source_offset: usize,
+    moof: Mp4Box,
+    mdats: Vec<Mp4Box>,
+    /// `baseMediaDecodeTime` in seconds, used to interleave the two tracks.
+    decode_time: f64,
+    is_audio: bool,
+}
+
+/// The boxes of one input file that the merge needs.
+struct Mp4File<'a> {
+    data: &'a [u8],
+    ftyp: Option<Mp4Box>,
+    moov: Mp4Box,
+    fragments: Vec<(Mp4Box, Vec<Mp4Box>)>,
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| anyhow!("Truncated MP4 box."))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    data.get(offset..offset + 8)
+        .map(|b| u64::from_be_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]))
+        .ok_or_else(|| anyhow!("Truncated MP4 box."))
+}
+
+fn write_u32(data: &mut [u8], offset: usize, value: u32) -> Result<()> {
+    data.get_mut(offset..offset + 4)
+        .ok_or_else(|| anyhow!("Truncated MP4 box."))?
+        .copy_from_slice(&value.to_be_bytes());
+    Ok(())
+}
+
+fn write_u64(data: &mut [u8], offset: usize, value: u64) -> Result<()> {
+    data.get_mut(offset..offset + 8)
+        .ok_or_else(|| anyhow!("Truncated MP4 box."))?
+        .copy_from_slice(&value.to_be_bytes());
+    Ok(())
+}
+
+/// Read the boxes between `start` and `end`.
+fn read_boxes(data: &[u8], start: usize, end: usize) -> Result<Vec<Mp4Box>> {
+    let mut boxes = vec![];
+    let mut offset = start;
+
+    while offset + 8 <= end {
+        let size = read_u32(data, offset)? as u64;
+        let mut box_type = [0; 4];
+        box_type.copy_from_slice(&data[offset + 4..offset + 8]);
+
+        let (size, header_len) = match size {
+            // The box runs to the end of its parent.
+            0 => ((end - offset) as u64, 8),
+            1 => (read_u64(data, offset + 8)?, 16),
+            size => (size, 8),
+        };
+        let box_end = offset
+            .checked_add(usize::try_from(size)?)
+            .filter(|box_end| *box_end <= end && size >= header_len as u64)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Invalid size of MP4 box \"{}\".",
+                    String::from_utf8_lossy(&box_type)
+                )
+            })?;
+
+        boxes.push(Mp4Box {
+            box_type,
+            start: offset,
+            payload_start: offset + header_len,
+            end: box_end,
+        });
+        offset = box_end;
+    }
+
+    Ok(boxes)
+}
+
+/// The version byte of a full box.
+fn version(data: &[u8], full_box: &Mp4Box) -> u8 {
+    data.get(full_box.payload_start)
+        .copied()
+        .unwrap_or_default()
+}
+
+fn children(data: &[u8], parent: &Mp4Box) -> Result<Vec<Mp4Box>> {
+    read_boxes(data, parent.payload_start, parent.end)
+}
+
+fn find_child(data: &[u8], parent: &Mp4Box, box_type: &[u8; 4]) -> Result<Option<Mp4Box>> {
+    Ok(children(data, parent)?
+        .into_iter()
+        .find(|b| &b.box_type == box_type))
+}
+
+fn make_box(box_type: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(payload.len() + 16);
+
+    match u32::try_from(payload.len() + 8) {
+        Ok(size) => {
+            encoded.extend_from_slice(&size.to_be_bytes());
+            encoded.extend_from_slice(box_type);
+        }
+        // Boxes past 4 GiB carry their size in a 64-bit field after the type.
+        Err(_) => {
+            encoded.extend_from_slice(&1u32.to_be_bytes());
+            encoded.extend_from_slice(box_type);
+            encoded.extend_from_slice(&((payload.len() + 16) as u64).to_be_bytes());
+        }
+    }
+    encoded.extend_from_slice(payload);
+
+    encoded
+}
+
+impl<'a> Mp4File<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self> {
+        let mut ftyp = None;
+        let mut moov = None;
+        let mut fragments: Vec<(Mp4Box, Vec<Mp4Box>)> = vec![];
+
+        for top_level in read_boxes(data, 0, data.len())? {
+            match &top_level.box_type {
+                b"ftyp" => ftyp = Some(top_level),
+                b"moov" => moov = Some(top_level),
+                b"moof" => fragments.push((top_level, vec![])),
+                b"mdat" => match fragments.last_mut() {
+                    Some((_, mdats)) => mdats.push(top_level),
+                    None => bail!(
+                        "Only fragmented MP4 files can be merged, this one keeps its samples outside of fragments."
+                    ),
+                },
+                // Segment indexes only describe one track, and the rest isn't needed for playback.
+                _ => {}
+            }
+        }
+
+        let moov = moov.ok_or_else(|| anyhow!("The MP4 file has no \"moov\" box."))?;
+
+        if find_child(data, &moov, b"mvex")?.is_none() || fragments.is_empty() {
+            bail!("Only fragmented MP4 files can be merged.")
+        }
+
+        Ok(Self {
+            data,
+            ftyp,
+            moov,
+            fragments,
+        })
+    }
+
+    fn traks(&self) -> Result<Vec<Mp4Box>> {
+        Ok(children(self.data, &self.moov)?
+            .into_iter()
+            .filter(|b| &b.box_type == b"trak")
+            .collect())
+    }
+
+    /// The movie timescale from `mvhd`.
+    fn movie_timescale(&self) -> Result<u32> {
+        let mvhd = find_child(self.data, &self.moov, b"mvhd")?
+            .ok_or_else(|| anyhow!("The MP4 file has no \"mvhd\" box."))?;
+        let offset = match version(self.data, &mvhd) {
+            1 => 20,
+            _ => 12,
+        };
+
+        read_u32(self.data, mvhd.payload_start + offset)
+    }
+
+    /// Track IDs mapped to the media timescale of their `mdhd`.
+    fn track_timescales(&self) -> Result<HashMap<u32, u32>> {
+        let mut timescales = HashMap::new();
+
+        for trak in self.traks()? {
+            let track_id = track_id(self.data, &trak)?;
+            let mdhd = find_child(self.data, &trak, b"mdia")?
+                .map(|mdia| find_child(self.data, &mdia, b"mdhd"))
+                .transpose()?
+                .flatten()
+                .ok_or_else(|| anyhow!("Track {} has no \"mdhd\" box.", track_id))?;
+            let offset = match version(self.data, &mdhd) {
+                1 => 20,
+                _ => 12,
+            };
+
+            timescales.insert(track_id, read_u32(self.data, mdhd.payload_start + offset)?);
+        }
+
+        Ok(timescales)
+    }
+
+    /// `fragment_duration` of `mehd` in the movie timescale.
+    fn fragment_duration(&self) -> Result<Option<u64>> {
+        let Some(mehd) = find_child(self.data, &self.moov, b"mvex")?
+            .map(|mvex| find_child(self.data, &mvex, b"mehd"))
+            .transpose()?
+            .flatten()
+        else {
+            return Ok(None);
+        };
+
+        match version(self.data, &mehd) {
+            1 => read_u64(self.data, mehd.payload_start + 4).map(Some),
+            _ => read_u32(self.data, mehd.payload_start + 4).map(|d| Some(d as u64)),
+        }
+    }
+
+    fn into_fragments(self, is_audio: bool) -> Result<Vec<Fragment<'a>>> {
+        let timescales = self.track_timescales()?;
+        let mut last_decode_time = 0.0;
+        let mut fragments = vec![];
+
+        for (moof, mdats) in self.fragments {
+            let mut decode_time = None;
+
+            for traf in children(self.data, &moof)?
+                .into_iter()
+                .filter(|b| &b.box_type == b"traf")
+            {
+                let (Some(tfhd), Some(tfdt)) = (
+                    find_child(self.data, &traf, b"tfhd")?,
+                    find_child(self.data, &traf, b"tfdt")?,
+                ) else {
+                    continue;
+                };
+                let timescale = timescales
+                    .get(&read_u32(self.data, tfhd.payload_start + 4)?)
+                    .copied()
+                    .unwrap_or(1)
+                    .max(1);
+                let base_media_decode_time = match version(self.data, &tfdt) {
+                    1 => read_u64(self.data, tfdt.payload_start + 4)?,
+                    _ => read_u32(self.data, tfdt.payload_start + 4)? as u64,
+                };
+
+                decode_time = Some(base_media_decode_time as f64 / timescale as f64);
+                break;
+            }
+
+            // Fragments without a decode time stay right after the previous one.
+            last_decode_time = decode_time.unwrap_or(last_decode_time);
+
+            fragments.push(Fragment {
+                data: self.data,
+                source_offset: moof.start,
+                moof,
+                mdats,
+                decode_time: last_decode_time,
+                is_audio,
+            });
+        }
+
+        Ok(fragments)
+    }
+}
+
+fn track_id(data: &[u8], trak: &Mp4Box) -> Result<u32> {
+    let tkhd = find_child(data, trak, b"tkhd")?
+        .ok_or_else(|| anyhow!("The MP4 track has no \"tkhd\" box."))?;
+    let offset = match version(data, &tkhd) {
+        1 => 20,
+        _ => 12,
+    };
+
+    read_u32(data, tkhd.payload_start + offset)
+}
+
+/// Scale a duration from one timescale to another.
+fn rescale(value: u64, from: u32, to: u32) -> u64 {
+    (value as u128 * to as u128 / from.max(1) as u128) as u64
+}
+
+/// Copy an audio `trak` with its new track ID and its movie-timescale durations converted.
+fn rewrite_trak(
+    data: &[u8],
+    trak: &Mp4Box,
+    new_track_id: u32,
+    from_timescale: u32,
+    to_timescale: u32,
+) -> Result<Vec<u8>> {
+    let mut payload = vec![];
+
+    for child in children(data, trak)? {
+        let mut child_bytes = data[child.start..child.end].to_vec();
+        let payload_offset = child.payload_start - child.start;
+
+        match &child.box_type {
+            b"tkhd" => {
+                let (id_offset, duration_offset, is_v1) = match version(data, &child) {
+                    1 => (20, 28, true),
+                    _ => (12, 20, false),
+                };
+
+                write_u32(&mut child_bytes, payload_offset + id_offset, new_track_id)?;
+
+                match is_v1 {
+                    true => {
+                        let duration = read_u64(&child_bytes, payload_offset + duration_offset)?;
+                        write_u64(
+                            &mut child_bytes,
+                            payload_offset + duration_offset,
+                            rescale(duration, from_timescale, to_timescale),
+                        )?;
+                    }
+                    false => {
+                        let duration = read_u32(&child_bytes, payload_offset + duration_offset)?;
+                        // All ones means the duration is unknown.
+                        if duration != u32::MAX {
+                            write_u32(
+                                &mut child_bytes,
+                                payload_offset + duration_offset,
+                                rescale(duration as u64, from_timescale, to_timescale)
+                                    .min(u32::MAX as u64 - 1)
+                                    as u32,
+                            )?;
+                        }
+                    }
+                }
+            }
+            b"edts" => {
+                for elst in children(data, &child)?
+                    .into_iter()
+                    .filter(|b| &b.box_type == b"elst")
+                {
+                    let elst_offset = elst.payload_start - child.start;
+                    let is_v1 = version(data, &elst) == 1;
+                    let entry_count = read_u32(&child_bytes, elst_offset + 4)? as usize;
+                    let entry_len = match is_v1 {
+                        true => 20,
+                        false => 12,
+                    };
+
+                    // Segment durations are in the movie timescale, media times in the track's own.
+                    for entry in 0..entry_count {
+                        let entry_offset = elst_offset + 8 + entry * entry_len;
+
+                        match is_v1 {
+                            true => {
+                                let duration = read_u64(&child_bytes, entry_offset)?;
+                                write_u64(
+                                    &mut child_bytes,
+                                    entry_offset,
+                                    rescale(duration, from_timescale, to_timescale),
+                                )?;
+                            }
+                            false => {
+                                let duration = read_u32(&child_bytes, entry_offset)?;
+                                write_u32(
+                                    &mut child_bytes,
+                                    entry_offset,
+                                    rescale(duration as u64, from_timescale, to_timescale)
+                                        .min(u32::MAX as u64)
+                                        as u32,
+                                )?;
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        payload.extend_from_slice(&child_bytes);
+    }
+
+    Ok(make_box(b"trak", &payload))
+}
+
+/// Merge a fragmented MP4 video and a fragmented M4A audio file into one fragmented MP4.
+pub fn mux_mp4(video: &[u8], audio: &[u8]) -> Result<Vec<u8>> {
+    let video_file = Mp4File::parse(video)?;
+    let audio_file = Mp4File::parse(audio)?;
+
+    let video_timescale = video_file.movie_timescale()?;
+    let audio_timescale = audio_file.movie_timescale()?;
+
+    let video_track_ids: Vec<u32> = video_file
+        .traks()?
+        .iter()
+        .map(|trak| track_id(video, trak))
+        .collect::<Result<_>>()?;
+    let mut next_track_id = video_track_ids.iter().copied().max().unwrap_or_default() + 1;
+
+    // Audio track IDs are moved past the video ones.
+    let mut audio_track_ids = HashMap::new();
+    let mut audio_traks = vec![];
+
+    for trak in audio_file.traks()? {
+        audio_track_ids.insert(track_id(audio, &trak)?, next_track_id);
+        audio_traks.push(rewrite_trak(
+            audio,
+            &trak,
+            next_track_id,
+            audio_timescale,
+            video_timescale,
+        )?);
+        next_track_id += 1;
+    }
+
+    let fragment_duration = match (
+        video_file.fragment_duration()?,
+        audio_file.fragment_duration()?,
+    ) {
+        (Some(video_duration), Some(audio_duration)) => {
+            Some(video_duration.max(rescale(audio_duration, audio_timescale, video_timescale)))
+        }
+        (duration, None) => duration,
+        (None, Some(audio_duration)) => {
+            Some(rescale(audio_duration, audio_timescale, video_timescale))
+        }
+    };
+    let audio_trexes: Vec<Vec<u8>> = match find_child(audio, &audio_file.moov, b"mvex")? {
+        Some(mvex) => children(audio, &mvex)?
+            .into_iter()
+            .filter(|b| &b.box_type == b"trex")
+            .map(|trex| {
+                let mut trex_bytes = audio[trex.start..trex.end].to_vec();
+                let id_offset = trex.payload_start - trex.start + 4;
+                let old_id = read_u32(&trex_bytes, id_offset)?;
+                let new_id = audio_track_ids.get(&old_id).copied().unwrap_or(old_id);
+
+                write_u32(&mut trex_bytes, id_offset, new_id)?;
+                Ok(trex_bytes)
+            })
+            .collect::<Result<_>>()?,
+        None => vec![],
+    };
+
+    // Rebuild `moov` with the audio tracks after the video ones.
+    let mut moov_payload = vec![];
+
+    for child in children(video, &video_file.moov)? {
+        let mut child_bytes = video[child.start..child.end].to_vec();
+        let payload_offset = child.payload_start - child.start;
+
+        match &child.box_type {
+            b"mvhd" => {
+                let (duration_offset, is_v1) = match version(video, &child) {
+                    1 => (24, true),
+                    _ => (16, false),
+                };
+                let audio_mvhd = find_child(audio, &audio_file.moov, b"mvhd")?
+                    .ok_or_else(|| anyhow!("The MP4 file has no \"mvhd\" box."))?;
+                let audio_duration = match version(audio, &audio_mvhd) {
+                    1 => read_u64(audio, audio_mvhd.payload_start + 24)?,
+                    _ => read_u32(audio, audio_mvhd.payload_start + 16)? as u64,
+                };
+                let audio_duration = rescale(audio_duration, audio_timescale, video_timescale);
+
+                match is_v1 {
+                    true => {
+                        let duration = read_u64(&child_bytes, payload_offset + duration_offset)?;
+                        write_u64(
+                            &mut child_bytes,
+                            payload_offset + duration_offset,
+                            duration.max(audio_duration),
+                        )?;
+                    }
+                    false => {
+                        let duration = read_u32(&child_bytes, payload_offset + duration_offset)?;
+                        write_u32(
+                            &mut child_bytes,
+                            payload_offset + duration_offset,
+                            (duration as u64).max(audio_duration).min(u32::MAX as u64) as u32,
+                        )?;
+                    }
+                }
+
+                // `next_track_ID` closes the box.
+                let next_id_offset = child_bytes.len() - 4;
+                write_u32(&mut child_bytes, next_id_offset, next_track_id)?;
+                moov_payload.extend_from_slice(&child_bytes);
+            }
+            b"mvex" => {
+                let mut mvex_payload = vec![];
+
+                for mvex_child in children(video, &child)? {
+                    let mut mvex_child_bytes = video[mvex_child.start..mvex_child.end].to_vec();
+
+                    if &mvex_child.box_type == b"mehd"
+                        && let Some(fragment_duration) = fragment_duration
+                    {
+                        let offset = mvex_child.payload_start - mvex_child.start;
+
+                        match version(video, &mvex_child) {
+                            1 => write_u64(&mut mvex_child_bytes, offset + 4, fragment_duration)?,
+                            _ => write_u32(
+                                &mut mvex_child_bytes,
+                                offset + 4,
+                                fragment_duration.min(u32::MAX as u64) as u32,
+                            )?,
+                        }
+                    }
+
+                    mvex_payload.extend_from_slice(&mvex_child_bytes);
+                }
+
+                for trex in &audio_trexes {
+                    mvex_payload.extend_from_slice(trex);
+                }
+
+                moov_payload.extend_from_slice(&make_box(b"mvex", &mvex_payload));
+            }
+            _ => moov_payload.extend_from_slice(&child_bytes),
+        }
+    }
+
+    // Tracks go before `mvex`, which players expect last, but after the video tracks.
+    let moov_children = read_boxes(&moov_payload, 0, moov_payload.len())?;
+    let insert_at = moov_children
+        .iter()
+        .rev()
+        .find(|b| &b.box_type == b"trak")
+        .map(|b| b.end)
+        .unwrap_or(moov_payload.len());
+    let moov_payload = [
+        &moov_payload[..insert_at],
+        &audio_traks.concat(),
+        &moov_payload[insert_at..],
+    ]
+    .concat();
+
+    let mut output = vec![];
+
+    if let Some(ftyp) = video_file.ftyp {
+        output.extend_from_slice(&video[ftyp.start..ftyp.end]);
+    }
+    output.extend_from_slice(&make_box(b"moov", &moov_payload));
+
+    // Interleave the fragments by decode time, video first when they start together.
+    let mut fragments = video_file.into_fragments(false)?;
+    fragments.extend(audio_file.into_fragments(true)?);
+    fragments.sort_by(|a, b| {
+        a.decode_time
+            .total_cmp(&b.decode_time)
+            .then(a.is_audio.cmp(&b.is_audio))
+    });
+
+    for (sequence_number, fragment) in fragments.iter().enumerate() {
+        let moof_position = output.len();
+        let mut moof_bytes = fragment.data[fragment.moof.start..fragment.moof.end].to_vec();
+        let moof_offset = fragment.moof.start;
+
+        for moof_child in children(fragment.data, &fragment.moof)? {
+            let child_offset = moof_child.payload_start - moof_offset;
+
+            match &moof_child.box_type {
+                b"mfhd" => write_u32(
+                    &mut moof_bytes,
+                    child_offset + 4,
+                    sequence_number as u32 + 1,
+                )?,
+                b"traf" => {
+                    let Some(tfhd) = find_child(fragment.data, &moof_child, b"tfhd")? else {
+                        continue;
+                    };
+                    let tfhd_offset = tfhd.payload_start - moof_offset;
+                    let flags = read_u32(&moof_bytes, tfhd_offset)? & 0x00ff_ffff;
+
+                    if fragment.is_audio {
+                        let old_id = read_u32(&moof_bytes, tfhd_offset + 4)?;
+                        let new_id = audio_track_ids.get(&old_id).copied().unwrap_or(old_id);
+                        write_u32(&mut moof_bytes, tfhd_offset + 4, new_id)?;
+                    }
+
+                    // An explicit base data offset points into the source file and has to move with the fragment.
+                    if flags & 0x1 != 0 {
+                        let base_data_offset = read_u64(&moof_bytes, tfhd_offset + 8)?;
+                        let moved = (base_data_offset as i128 + moof_position as i128
+                            - fragment.source_offset as i128)
+                            .max(0) as u64;
+                        write_u64(&mut moof_bytes, tfhd_offset + 8, moved)?;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        output.extend_from_slice(&moof_bytes);
+
+        for mdat in &fragment.mdats {
+            output.extend_from_slice(&fragment.data[mdat.start..mdat.end]);
+        }
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn full_box(box_type: &[u8; 4], version: u8, fields: &[&[u8]]) -> Vec<u8> {
+        let mut payload = vec![version, 0, 0, 0];
+        for field in fields {
+            payload.extend_from_slice(field);
+        }
+
+        make_box(box_type, &payload)
+    }
+
+    /// An init segment and two one-sample fragments of a single track.
+    fn fragmented_file(
+        movie_timescale: u32,
+        duration: u32,
+        media_timescale: u32,
+        decode_times: [u64; 2],
+    ) -> Vec<u8> {
+        let mvhd = full_box(
+            b"mvhd",
+            0,
+            &[
+                &[0; 8],
+                &movie_timescale.to_be_bytes(),
+                &duration.to_be_bytes(),
+                &[0; 76],
+                &2u32.to_be_bytes(),
+            ],
+        );
+        let tkhd = full_box(
+            b"tkhd",
+            0,
+            &[
+                &[0; 8],
+                &1u32.to_be_bytes(),
+                &[0; 4],
+                &duration.to_be_bytes(),
+                &[0; 60],
+            ],
+        );
+        let elst = full_box(
+            b"elst",
+            0,
+            &[
+                &1u32.to_be_bytes(),
+                &duration.to_be_bytes(),
+                &[0; 4],
+                &[0, 1, 0, 0],
+            ],
+        );
+        let mdhd = full_box(
+            b"mdhd",
+            0,
+            &[&[0; 8], &media_timescale.to_be_bytes(), &[0; 8]],
+        );
+        let trak = make_box(
+            b"trak",
+            &[tkhd, make_box(b"edts", &elst), make_box(b"mdia", &mdhd)].concat(),
+        );
+        let mvex = make_box(
+            b"mvex",
+            &[
+                full_box(b"mehd", 0, &[&duration.to_be_bytes()]),
+                full_box(b"trex", 0, &[&1u32.to_be_bytes(), &[0; 16]]),
+            ]
+            .concat(),
+        );
+
+        let mut file = make_box(b"ftyp", b"iso6\0\0\0\0");
+        file.extend(make_box(b"moov", &[mvhd, trak, mvex].concat()));
+
+        for (sequence_number, decode_time) in decode_times.into_iter().enumerate() {
+            let traf = make_box(
+                b"traf",
+                &[
+                    full_box(b"tfhd", 0, &[&1u32.to_be_bytes()]),
+                    full_box(b"tfdt", 1, &[&decode_time.to_be_bytes()]),
+                ]
+                .concat(),
+            );
+            let mfhd = full_box(b"mfhd", 0, &[&(sequence_number as u32 + 1).to_be_bytes()]);
+
+            file.extend(make_box(b"moof", &[mfhd, traf].concat()));
+            file.extend(make_box(b"mdat", &[0xAB; 4]));
+        }
+
+        file
+    }
+
+    fn child(data: &[u8], parent: &Mp4Box, path: &[&[u8; 4]]) -> Mp4Box {
+        path.iter().fold(*parent, |parent, box_type| {
+            find_child(data, &parent, box_type).unwrap().unwrap()
+        })
+    }
+
+    #[test]
+    fn merges_tracks_into_one_moov() {
+        // 2 s of video at 1000 units per second and 2.5 s of audio at 44100.
+        let video = fragmented_file(1000, 2000, 90_000, [0, 90_000]);
+        let audio = fragmented_file(44_100, 110_250, 44_100, [0, 22_050]);
+        let merged = mux_mp4(&video, &audio).unwrap();
+
+        let top_level = read_boxes(&merged, 0, merged.len()).unwrap();
+        let moov = top_level.iter().find(|b| &b.box_type == b"moov").unwrap();
+        let traks: Vec<Mp4Box> = children(&merged, moov)
+            .unwrap()
+            .into_iter()
+            .filter(|b| &b.box_type == b"trak")
+            .collect();
+        let track_ids: Vec<u32> = traks
+            .iter()
+            .map(|trak| track_id(&merged, trak).unwrap())
+            .collect();
+        assert_eq!(track_ids, [1, 2]);
+
+        let mvhd = child(&merged, moov, &[b"mvhd"]);
+        assert_eq!(read_u32(&merged, mvhd.payload_start + 16).unwrap(), 2500);
+        assert_eq!(read_u32(&merged, mvhd.end - 4).unwrap(), 3);
+
+        let mehd = child(&merged, moov, &[b"mvex", b"mehd"]);
+        assert_eq!(read_u32(&merged, mehd.payload_start + 4).unwrap(), 2500);
+
+        let trex_ids: Vec<u32> = children(&merged, &child(&merged, moov, &[b"mvex"]))
+            .unwrap()
+            .iter()
+            .filter(|b| &b.box_type == b"trex")
+            .map(|trex| read_u32(&merged, trex.payload_start + 4).unwrap())
+            .collect();
+        assert_eq!(trex_ids, [1, 2]);
+
+        // The audio durations are moved to the video's movie timescale.
+        let audio_tkhd = child(&merged, &traks[1], &[b"tkhd"]);
+        assert_eq!(
+            read_u32(&merged, audio_tkhd.payload_start + 20).unwrap(),
+            2500
+        );
+        let audio_elst = child(&merged, &traks[1], &[b"edts", b"elst"]);
+        assert_eq!(
+            read_u32(&merged, audio_elst.payload_start + 8).unwrap(),
+            2500
+        );
+        let video_elst = child(&merged, &traks[0], &[b"edts", b"elst"]);
+        assert_eq!(
+            read_u32(&merged, video_elst.payload_start + 8).unwrap(),
+            2000
+        );
+    }
+
+    #[test]
+    fn interleaves_fragments_by_decode_time() {
+        let video = fragmented_file(1000, 2000, 90_000, [0, 90_000]);
+        let audio = fragmented_file(44_100, 110_250, 44_100, [0, 22_050]);
+        let merged = mux_mp4(&video, &audio).unwrap();
+
+        let fragments: Vec<(u32, u32)> = read_boxes(&merged, 0, merged.len())
+            .unwrap()
+            .iter()
+            .filter(|b| &b.box_type == b"moof")
+            .map(|moof| {
+                let mfhd = child(&merged, moof, &[b"mfhd"]);
+                let tfhd = child(&merged, moof, &[b"traf", b"tfhd"]);
+
+                (
+                    read_u32(&merged, mfhd.payload_start + 4).unwrap(),
+                    read_u32(&merged, tfhd.payload_start + 4).unwrap(),
+                )
+            })
+            .collect();
+
+        assert_eq!(fragments, [(1, 1), (2, 2), (3, 2), (4, 1)]);
+    }
+
+    #[test]
+    fn rejects_unfragmented_files() {
+        let mut video = make_box(b"ftyp", b"iso6\0\0\0\0");
+        video.extend(make_box(b"mdat", &[0; 4]));
+
+        assert!(mux_mp4(&video, &video).is_err());
+    }
+}
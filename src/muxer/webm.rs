@@ -0,0 +1,632 @@
+use anyhow::{Result, anyhow, bail};
+
+const EBML_HEADER: u32 = 0x1A45_DFA3;
+const SEGMENT: u32 = 0x1853_8067;
+const SEEK_HEAD: u32 = 0x114D_9B74;
+const SEEK: u32 = 0x4DBB;
+const SEEK_ID: u32 = 0x53AB;
+const SEEK_POSITION: u32 = 0x53AC;
+const INFO: u32 = 0x1549_A966;
+const TIMECODE_SCALE: u32 = 0x2A_D7B1;
+const DURATION: u32 = 0x4489;
+const TRACKS: u32 = 0x1654_AE6B;
+const TRACK_ENTRY: u32 = 0xAE;
+const TRACK_NUMBER: u32 = 0xD7;
+const TRACK_UID: u32 = 0x73C5;
+const CLUSTER: u32 = 0x1F43_B675;
+const TIMECODE: u32 = 0xE7;
+const SIMPLE_BLOCK: u32 = 0xA3;
+const BLOCK_GROUP: u32 = 0xA0;
+const BLOCK: u32 = 0xA1;
+const CUES: u32 = 0x1C53_BB6B;
+const CUE_POINT: u32 = 0xBB;
+const CUE_TIME: u32 = 0xB3;
+const CUE_TRACK_POSITIONS: u32 = 0xB7;
+const CUE_TRACK: u32 = 0xF7;
+const CUE_CLUSTER_POSITION: u32 = 0xF1;
+
+/// Elements that sit directly in a `Segment`, which end a parent of unknown size.
+const SEGMENT_CHILDREN: [u32; 8] = [
+    SEEK_HEAD,
+    INFO,
+    TRACKS,
+    CLUSTER,
+    CUES,
+    0x1941_A469,
+    0x1043_A770,
+    0x1254_C367,
+];
+
+/// An EBML element, `start..end` spans its header and data.
+#[derive(Clone, Copy)]
+struct Element {
+    id: u32,
+    start: usize,
+    data_start: usize,
+    end: usize,
+}
+
+/// The parts of one input file that the merge needs.
+struct WebmFile<'a> {
+    data: &'a [u8],
+    ebml_header: Element,
+    info: Element,
+    tracks: Element,
+    clusters: Vec<Element>,
+    timecode_scale: u64,
+    /// In `timecode_scale` units.
+    duration: Option<f64>,
+}
+
+/// Read an element ID, keeping its length marker like the constants above.
+fn read_id(data: &[u8], offset: usize) -> Result<(u32, usize)> {
+    let first = *data
+        .get(offset)
+        .ok_or_else(|| anyhow!("Truncated EBML element."))?;
+    let len = first.leading_zeros() as usize + 1;
+
+    if len > 4 {
+        bail!("Invalid EBML element ID at byte {}.", offset)
+    }
+
+    let bytes = data
+        .get(offset..offset + len)
+        .ok_or_else(|| anyhow!("Truncated EBML element."))?;
+
+    Ok((bytes.iter().fold(0, |id, b| id << 8 | *b as u32), len))
+}
+
+/// Read a variable length integer, `None` if all of its value bits are set.
+fn read_vint(data: &[u8], offset: usize) -> Result<(Option<u64>, usize)> {
+    let first = *data
+        .get(offset)
+        .ok_or_else(|| anyhow!("Truncated EBML element."))?;
+    let len = first.leading_zeros() as usize + 1;
+
+    if len > 8 {
+        bail!("Invalid EBML size at byte {}.", offset)
+    }
+
+    let bytes = data
+        .get(offset..offset + len)
+        .ok_or_else(|| anyhow!("Truncated EBML element."))?;
+    let mask = (1u64 << (7 * len)) - 1;
+    let value = bytes.iter().fold(0u64, |v, b| v << 8 | *b as u64) & mask;
+
+    Ok(((value != mask).then_some(value), len))
+}
+
+fn read_element(data: &[u8], offset: usize, parent_end: usize) -> Result<Element> {
+    let (id, id_len) = read_id(data, offset)?;
+    let (size, size_len) = read_vint(data, offset + id_len)?;
+    let data_start = offset + id_len + size_len;
+
+    let end = match size {
+        Some(size) => data_start
+            .checked_add(usize::try_from(size)?)
+            .filter(|end| *end <= parent_end)
+            .ok_or_else(|| anyhow!("EBML element {:X} runs past its parent.", id))?,
+        // Elements of unknown size end where the next element of their level starts.
+        None => {
+            let mut child_offset = data_start;
+
+            while child_offset < parent_end {
+                let (child_id, _) = read_id(data, child_offset)?;
+
+                if child_id == id || SEGMENT_CHILDREN.contains(&child_id) {
+                    break;
+                }
+
+                child_offset = read_element(data, child_offset, parent_end)?.end;
+            }
+
+            child_offset
+        }
+    };
+
+    Ok(Element {
+        id,
+        start: offset,
+        data_start,
+        end,
+    })
+}
+
+fn read_children(data: &[u8], start: usize, end: usize) -> Result<Vec<Element>> {
+    let mut elements = vec![];
+    let mut offset = start;
+
+    while offset < end {
+        let element = read_element(data, offset, end)?;
+        offset = element.end;
+        elements.push(element);
+    }
+
+    Ok(elements)
+}
+
+fn read_uint(data: &[u8], element: &Element) -> u64 {
+    data[element.data_start..element.end]
+        .iter()
+        .fold(0, |v, b| v << 8 | *b as u64)
+}
+
+fn read_float(data: &[u8], element: &Element) -> Option<f64> {
+    let bytes = &data[element.data_start..element.end];
+
+    match bytes.len() {
+        4 => Some(f32::from_be_bytes(bytes.try_into().ok()?) as f64),
+        8 => Some(f64::from_be_bytes(bytes.try_into().ok()?)),
+        _ => None,
+    }
+}
+
+fn encode_id(id: u32) -> Vec<u8> {
+    let bytes = id.to_be_bytes();
+    let skip = bytes.iter().take_while(|b| **b == 0).count().min(3);
+
+    bytes[skip..].to_vec()
+}
+
+/// Encode a number as a variable length integer of at least `min_len` bytes.
+fn encode_vint(value: u64, min_len: usize) -> Vec<u8> {
+    // All value bits set is reserved for "unknown", hence the `- 1`.
+    let len = (1..=8)
+        .find(|len| *len >= min_len && value < (1u64 << (7 * len)) - 1)
+        .unwrap_or(8);
+    let marked = value | 1u64 << (7 * len);
+
+    marked.to_be_bytes()[8 - len..].to_vec()
+}
+
+fn encode_uint(value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = bytes.iter().take_while(|b| **b == 0).count().min(7);
+
+    bytes[skip..].to_vec()
+}
+
+fn make_element(id: u32, payload: &[u8]) -> Vec<u8> {
+    let mut encoded = encode_id(id);
+    encoded.extend_from_slice(&encode_vint(payload.len() as u64, 1));
+    encoded.extend_from_slice(payload);
+
+    encoded
+}
+
+impl<'a> WebmFile<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self> {
+        let top_level = read_children(data, 0, data.len())?;
+        let ebml_header = top_level
+            .iter()
+            .find(|e| e.id == EBML_HEADER)
+            .copied()
+            .ok_or_else(|| anyhow!("The WebM file has no EBML header."))?;
+        let segment = top_level
+            .iter()
+            .find(|e| e.id == SEGMENT)
+            .ok_or_else(|| anyhow!("The WebM file has no segment."))?;
+
+        let mut info = None;
+        let mut tracks = None;
+        let mut clusters = vec![];
+
+        for element in read_children(data, segment.data_start, segment.end)? {
+            match element.id {
+                INFO => info = Some(element),
+                TRACKS => tracks = Some(element),
+                CLUSTER => clusters.push(element),
+                // Seek heads and cues point into this file, they're rebuilt for the merged one.
+                _ => {}
+            }
+        }
+
+        let info = info.ok_or_else(|| anyhow!("The WebM file has no segment info."))?;
+        let tracks = tracks.ok_or_else(|| anyhow!("The WebM file has no tracks."))?;
+
+        let info_children = read_children(data, info.data_start, info.end)?;
+        let timecode_scale = info_children
+            .iter()
+            .find(|e| e.id == TIMECODE_SCALE)
+            .map(|e| read_uint(data, e))
+            .unwrap_or(1_000_000);
+        let duration = info_children
+            .iter()
+            .find(|e| e.id == DURATION)
+            .and_then(|e| read_float(data, e));
+
+        Ok(Self {
+            data,
+            ebml_header,
+            info,
+            tracks,
+            clusters,
+            timecode_scale,
+            duration,
+        })
+    }
+
+    fn track_entries(&self) -> Result<Vec<Element>> {
+        Ok(
+            read_children(self.data, self.tracks.data_start, self.tracks.end)?
+                .into_iter()
+                .filter(|e| e.id == TRACK_ENTRY)
+                .collect(),
+        )
+    }
+
+    fn cluster_timecode(&self, cluster: &Element) -> Result<u64> {
+        Ok(read_children(self.data, cluster.data_start, cluster.end)?
+            .iter()
+            .find(|e| e.id == TIMECODE)
+            .map(|e| read_uint(self.data, e))
+            .unwrap_or_default())
+    }
+}
+
+/// Replace the track number a `SimpleBlock` or `Block` starts with.
+fn renumber_block(block: &[u8], track_numbers: &[(u64, u64)]) -> Result<Vec<u8>> {
+    let (track_number, len) = read_vint(block, 0)?;
+    let track_number = track_number.ok_or_else(|| anyhow!("Invalid block track number."))?;
+    let new_number = track_numbers
+        .iter()
+        .find(|(old, _)| *old == track_number)
+        .map(|(_, new)| *new)
+        .unwrap_or(track_number);
+
+    Ok([encode_vint(new_number, 1).as_slice(), &block[len..]].concat())
+}
+
+/// Merge a WebM video and a WebM audio file into one WebM.
+pub fn mux_webm(video: &[u8], audio: &[u8]) -> Result<Vec<u8>> {
+    let video_file = WebmFile::parse(video)?;
+    let audio_file = WebmFile::parse(audio)?;
+
+    // Block timecodes are 16-bit offsets in the timecode scale, they can't be rescaled safely.
+    if video_file.timecode_scale != audio_file.timecode_scale {
+        bail!(
+            "The video and audio use different timecode scales ({} and {}).",
+            video_file.timecode_scale,
+            audio_file.timecode_scale
+        )
+    }
+
+    let video_entries = video_file.track_entries()?;
+    let audio_entries = audio_file.track_entries()?;
+
+    let entry_value = |data: &[u8], entry: &Element, id: u32| -> Result<Option<u64>> {
+        Ok(read_children(data, entry.data_start, entry.end)?
+            .iter()
+            .find(|e| e.id == id)
+            .map(|e| read_uint(data, e)))
+    };
+
+    let mut video_track_numbers = vec![];
+    let mut used_uids = vec![];
+
+    for entry in &video_entries {
+        video_track_numbers.push(entry_value(video, entry, TRACK_NUMBER)?.unwrap_or(1));
+        used_uids.extend(entry_value(video, entry, TRACK_UID)?);
+    }
+
+    let first_audio_track_number = video_track_numbers
+        .iter()
+        .copied()
+        .max()
+        .unwrap_or_default()
+        + 1;
+    let mut audio_track_numbers: Vec<(u64, u64)> = vec![];
+    let mut tracks_payload: Vec<u8> = video_entries
+        .iter()
+        .flat_map(|entry| video[entry.start..entry.end].to_vec())
+        .collect();
+
+    for (index, entry) in audio_entries.iter().enumerate() {
+        let track_number = first_audio_track_number + index as u64;
+        let mut entry_payload = vec![];
+
+        for child in read_children(audio, entry.data_start, entry.end)? {
+            match child.id {
+                TRACK_NUMBER => {
+                    audio_track_numbers.push((read_uint(audio, &child), track_number));
+                    entry_payload.extend(make_element(TRACK_NUMBER, &encode_uint(track_number)));
+                }
+                TRACK_UID => {
+                    let mut uid = read_uint(audio, &child);
+
+                    while used_uids.contains(&uid) {
+                        uid = uid.wrapping_add(1).max(1);
+                    }
+                    used_uids.push(uid);
+                    entry_payload.extend(make_element(TRACK_UID, &encode_uint(uid)));
+                }
+                _ => entry_payload.extend_from_slice(&audio[child.start..child.end]),
+            }
+        }
+
+        tracks_payload.extend(make_element(TRACK_ENTRY, &entry_payload));
+    }
+
+    let mut info_payload = vec![];
+    for child in read_children(video, video_file.info.data_start, video_file.info.end)? {
+        if child.id != DURATION {
+            info_payload.extend_from_slice(&video[child.start..child.end]);
+        }
+    }
+    if let Some(duration) = match (video_file.duration, audio_file.duration) {
+        (Some(v), Some(a)) => Some(v.max(a)),
+        (duration, None) | (None, duration) => duration,
+    } {
+        info_payload.extend(make_element(DURATION, &duration.to_be_bytes()));
+    }
+
+    let info = make_element(INFO, &info_payload);
+    let tracks = make_element(TRACKS, &tracks_payload);
+
+    // Clusters of both files interleaved by timecode, video first when they start together.
+    let mut clusters: Vec<(u64, bool, Vec<u8>)> = vec![];
+
+    for cluster in &video_file.clusters {
+        clusters.push((
+            video_file.cluster_timecode(cluster)?,
+            false,
+            // Rebuilt so clusters of unknown size get a known one.
+            make_element(CLUSTER, &video[cluster.data_start..cluster.end]),
+        ));
+    }
+
+    for cluster in &audio_file.clusters {
+        let mut cluster_payload = vec![];
+
+        for child in read_children(audio, cluster.data_start, cluster.end)? {
+            match child.id {
+                SIMPLE_BLOCK => cluster_payload.extend(make_element(
+                    SIMPLE_BLOCK,
+                    &renumber_block(&audio[child.data_start..child.end], &audio_track_numbers)?,
+                )),
+                BLOCK_GROUP => {
+                    let mut group_payload = vec![];
+
+                    for group_child in read_children(audio, child.data_start, child.end)? {
+                        match group_child.id {
+                            BLOCK => group_payload.extend(make_element(
+                                BLOCK,
+                                &renumber_block(
+                                    &audio[group_child.data_start..group_child.end],
+                                    &audio_track_numbers,
+                                )?,
+                            )),
+                            _ => group_payload
+                                .extend_from_slice(&audio[group_child.start..group_child.end]),
+                        }
+                    }
+
+                    cluster_payload.extend(make_element(BLOCK_GROUP, &group_payload));
+                }
+                _ => cluster_payload.extend_from_slice(&audio[child.start..child.end]),
+            }
+        }
+
+        clusters.push((
+            audio_file.cluster_timecode(cluster)?,
+            true,
+            make_element(CLUSTER, &cluster_payload),
+        ));
+    }
+
+    clusters.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(&b.1)));
+
+    // The seek head only changes in value once positions are known, so its size is fixed up front.
+    let seek_head_len = build_seek_head(&[(INFO, 0), (TRACKS, 0), (CUES, 0)]).len();
+    let info_position = seek_head_len as u64;
+    let tracks_position = info_position + info.len() as u64;
+    let mut position = tracks_position + tracks.len() as u64;
+
+    let mut cue_points = vec![];
+    let cue_track = video_track_numbers.first().copied().unwrap_or(1);
+
+    for (timecode, is_audio, cluster) in &clusters {
+        if !is_audio {
+            let track_positions = [
+                make_element(CUE_TRACK, &encode_uint(cue_track)),
+                make_element(CUE_CLUSTER_POSITION, &encode_uint(position)),
+            ]
+            .concat();
+            cue_points.extend(make_element(
+                CUE_POINT,
+                &[
+                    make_element(CUE_TIME, &encode_uint(*timecode)),
+                    make_element(CUE_TRACK_POSITIONS, &track_positions),
+                ]
+                .concat(),
+            ));
+        }
+
+        position += cluster.len() as u64;
+    }
+
+    let cues = make_element(CUES, &cue_points);
+    let seek_head = build_seek_head(&[
+        (INFO, info_position),
+        (TRACKS, tracks_position),
+        (CUES, position),
+    ]);
+
+    let mut segment_payload = seek_head;
+    segment_payload.extend(info);
+    segment_payload.extend(tracks);
+    for (_, _, cluster) in clusters {
+        segment_payload.extend(cluster);
+    }
+    segment_payload.extend(cues);
+
+    let mut output = video[video_file.ebml_header.start..video_file.ebml_header.end].to_vec();
+    output.extend(make_element(SEGMENT, &segment_payload));
+
+    Ok(output)
+}
+
+/// A seek head with fixed 8-byte positions, relative to the start of the segment's data.
+fn build_seek_head(entries: &[(u32, u64)]) -> Vec<u8> {
+    let seeks: Vec<u8> = entries
+        .iter()
+        .flat_map(|(id, position)| {
+            make_element(
+                SEEK,
+                &[
+                    make_element(SEEK_ID, &encode_id(*id)),
+                    make_element(SEEK_POSITION, &position.to_be_bytes()),
+                ]
+                .concat(),
+            )
+        })
+        .collect();
+
+    make_element(SEEK_HEAD, &seeks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A WebM file with one track and a one-block cluster at each timecode.
+    fn webm_file(duration: f64, timecodes: &[u64]) -> Vec<u8> {
+        let info = make_element(
+            INFO,
+            &[
+                make_element(TIMECODE_SCALE, &encode_uint(1_000_000)),
+                make_element(DURATION, &duration.to_be_bytes()),
+            ]
+            .concat(),
+        );
+        let tracks = make_element(
+            TRACKS,
+            &make_element(
+                TRACK_ENTRY,
+                &[
+                    make_element(TRACK_NUMBER, &encode_uint(1)),
+                    make_element(TRACK_UID, &encode_uint(1)),
+                ]
+                .concat(),
+            ),
+        );
+
+        let mut segment_payload = [info, tracks].concat();
+        for timecode in timecodes {
+            segment_payload.extend(make_element(
+                CLUSTER,
+                &[
+                    make_element(TIMECODE, &encode_uint(*timecode)),
+                    // Track 1, a zero relative timecode, keyframe flags and a byte of data.
+                    make_element(SIMPLE_BLOCK, &[0x81, 0, 0, 0x80, 0xAB]),
+                ]
+                .concat(),
+            ));
+        }
+
+        let mut file = make_element(EBML_HEADER, &make_element(0x4282, b"webm"));
+        file.extend(make_element(SEGMENT, &segment_payload));
+
+        file
+    }
+
+    fn segment_children(data: &[u8]) -> Vec<Element> {
+        let segment = read_children(data, 0, data.len())
+            .unwrap()
+            .into_iter()
+            .find(|e| e.id == SEGMENT)
+            .unwrap();
+
+        read_children(data, segment.data_start, segment.end).unwrap()
+    }
+
+    fn child_uint(data: &[u8], parent: &Element, id: u32) -> u64 {
+        read_children(data, parent.data_start, parent.end)
+            .unwrap()
+            .iter()
+            .find(|e| e.id == id)
+            .map(|e| read_uint(data, e))
+            .unwrap()
+    }
+
+    #[test]
+    fn merges_tracks() {
+        let video = webm_file(2000.0, &[0, 1000]);
+        let audio = webm_file(2500.0, &[0, 500]);
+        let merged = mux_webm(&video, &audio).unwrap();
+        let elements = segment_children(&merged);
+
+        let tracks = elements.iter().find(|e| e.id == TRACKS).unwrap();
+        let entries: Vec<(u64, u64)> = read_children(&merged, tracks.data_start, tracks.end)
+            .unwrap()
+            .iter()
+            .filter(|e| e.id == TRACK_ENTRY)
+            .map(|entry| {
+                (
+                    child_uint(&merged, entry, TRACK_NUMBER),
+                    child_uint(&merged, entry, TRACK_UID),
+                )
+            })
+            .collect();
+        assert_eq!(entries, [(1, 1), (2, 2)]);
+
+        let info = elements.iter().find(|e| e.id == INFO).unwrap();
+        let duration = read_children(&merged, info.data_start, info.end)
+            .unwrap()
+            .into_iter()
+            .find(|e| e.id == DURATION)
+            .and_then(|e| read_float(&merged, &e));
+        assert_eq!(duration, Some(2500.0));
+    }
+
+    #[test]
+    fn interleaves_clusters_by_timecode() {
+        let video = webm_file(2000.0, &[0, 1000]);
+        let audio = webm_file(2500.0, &[0, 500]);
+        let merged = mux_webm(&video, &audio).unwrap();
+        let elements = segment_children(&merged);
+
+        let clusters: Vec<(u64, u64)> = elements
+            .iter()
+            .filter(|e| e.id == CLUSTER)
+            .map(|cluster| {
+                let block = read_children(&merged, cluster.data_start, cluster.end)
+                    .unwrap()
+                    .into_iter()
+                    .find(|e| e.id == SIMPLE_BLOCK)
+                    .unwrap();
+                let (track_number, _) = read_vint(&merged, block.data_start).unwrap();
+
+                (
+                    child_uint(&merged, cluster, TIMECODE),
+                    track_number.unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(clusters, [(0, 1), (0, 2), (500, 2), (1000, 1)]);
+
+        // Cue points only index the video clusters.
+        let cues = elements.iter().find(|e| e.id == CUES).unwrap();
+        let cue_times: Vec<u64> = read_children(&merged, cues.data_start, cues.end)
+            .unwrap()
+            .iter()
+            .map(|cue_point| child_uint(&merged, cue_point, CUE_TIME))
+            .collect();
+        assert_eq!(cue_times, [0, 1000]);
+    }
+
+    #[test]
+    fn rejects_different_timecode_scales() {
+        let video = webm_file(2000.0, &[0]);
+        let mut audio = webm_file(2000.0, &[0]);
+        // The scale is the only 3-byte uint in the file, swap 1 ms for 0.5 ms.
+        let scale = encode_uint(1_000_000);
+        let position = audio
+            .windows(scale.len())
+            .position(|w| w == scale.as_slice())
+            .unwrap();
+        audio[position..position + scale.len()].copy_from_slice(&encode_uint(500_000));
+
+        assert!(mux_webm(&video, &audio).is_err());
+    }
+}
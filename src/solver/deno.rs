@@ -0,0 +1,193 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        OnceLock,
+        mpsc::{self, RecvTimeoutError},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+use deno_core::JsRuntime;
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+use crate::solver::{ChallengeSolver, Solutions, SolveFut, SolveRequest, parse_jsc_output};
+
+/// How long the runtime of a player version is kept warm without being used.
+const SOLVER_IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+struct SolveJob {
+    /// The solver bundles and player code are only evaluated when the runtime of the player is created.
+    request: SolveRequest,
+    solver_env: String,
+    reply: oneshot::Sender<anyhow::Result<Solutions>>,
+}
+
+/// Solves challenges with Deno on dedicated threads, since `JsRuntime` is `!Send`.
+///
+/// Every player version gets its own thread holding a `JsRuntime` with the solver bundles and the preprocessed player
+/// already loaded, which is dropped after being idle for `SOLVER_IDLE_TIMEOUT`.
+/// V8 isolates sharing a thread have to be dropped in the reverse order of their creation, so runtimes aren't kept on the same one.
+pub struct DenoSolver {
+    sender: OnceLock<mpsc::Sender<SolveJob>>,
+    idle_timeout: Duration,
+}
+
+impl Default for DenoSolver {
+    fn default() -> Self {
+        Self::new(SOLVER_IDLE_TIMEOUT)
+    }
+}
+
+impl DenoSolver {
+    /// Runtimes of player versions unused for `idle_timeout` are dropped.
+    pub fn new(idle_timeout: Duration) -> Self {
+        Self {
+            sender: OnceLock::new(),
+            idle_timeout,
+        }
+    }
+}
+
+impl ChallengeSolver for DenoSolver {
+    fn solve<'a>(&'a self, request: SolveRequest) -> SolveFut<'a> {
+        Box::pin(async move {
+            let sender = self.sender.get_or_init(|| {
+                let (sender, receiver) = mpsc::channel();
+                let idle_timeout = self.idle_timeout;
+
+                thread::spawn(move || dispatch(receiver, idle_timeout));
+                sender
+            });
+
+            // Loaded on the caller's runtime, so the loader's future doesn't have to be `Send`.
+            let solver_env = request.solver_env().await?;
+            let (reply, response) = oneshot::channel();
+            sender
+                .send(SolveJob {
+                    request,
+                    solver_env,
+                    reply,
+                })
+                .map_err(|_| anyhow!("The signature solver worker has stopped."))?;
+
+            response
+                .await
+                .map_err(|_| anyhow!("The signature solver worker dropped the request."))?
+        })
+    }
+}
+
+/// Hands jobs to the runtime thread of their player version and evicts the idle ones.
+fn dispatch(receiver: mpsc::Receiver<SolveJob>, idle_timeout: Duration) {
+    let mut runtimes: HashMap<String, (mpsc::Sender<SolveJob>, Instant)> = HashMap::new();
+
+    loop {
+        match receiver.recv_timeout(idle_timeout) {
+            Ok(job) => {
+                let (sender, last_used) = runtimes
+                    .entry(job.request.player_key.clone())
+                    .or_insert_with(|| (spawn_runtime(), Instant::now()));
+                *last_used = Instant::now();
+
+                // Only fails if the runtime thread panicked, in which case it's replaced.
+                if let Err(mpsc::SendError(job)) = sender.send(job) {
+                    *sender = spawn_runtime();
+                    let _ = sender.send(job);
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        // Dropping the sender lets the runtime thread finish its queued jobs before it exits.
+        runtimes.retain(|_, (_, last_used)| last_used.elapsed() < idle_timeout);
+    }
+}
+
+fn spawn_runtime() -> mpsc::Sender<SolveJob> {
+    let (sender, receiver) = mpsc::channel::<SolveJob>();
+
+    thread::spawn(move || {
+        let Ok(tokio_runtime) = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        else {
+            return;
+        };
+        let mut warm_runtime = None;
+
+        for job in receiver {
+            let solved = tokio_runtime.block_on(solve_in_runtime(
+                &mut warm_runtime,
+                &job.solver_env,
+                &job.request,
+            ));
+
+            // A failed run might have left the runtime in a broken state.
+            if solved.is_err() {
+                warm_runtime = None;
+            }
+
+            let _ = job.reply.send(solved);
+        }
+    });
+
+    sender
+}
+
+// Taken from `youtube_explode_dart`'s implementation with `yt-dlp`'s ejs cipher library.
+// See: https://github.com/Hexer10/youtube_explode_dart/blob/a993b3d463713b0aabd945f07a7e6a1635bcf1e7/lib/src/reverse_engineering/challenges/ejs/ejs.dart
+async fn solve_in_runtime(
+    warm_runtime: &mut Option<JsRuntime>,
+    solver_env: &str,
+    request: &SolveRequest,
+) -> anyhow::Result<Solutions> {
+    let deno = match warm_runtime {
+        Some(deno) => deno,
+        None => {
+            let mut deno = JsRuntime::new(Default::default());
+            deno.execute_script("<setup_environment>", solver_env.to_string())?;
+            deno.execute_script(
+                "<set_player>",
+                format!(
+                    "globalThis.__player = {};",
+                    Value::from(request.player_code.as_str())
+                ),
+            )?;
+
+            warm_runtime.insert(deno)
+        }
+    };
+
+    deno.execute_script(
+        "<set_requests>",
+        format!("globalThis.__requests = {};", request.jsc_requests()),
+    )?;
+
+    // The player is only preprocessed on the first run, later ones reuse what `jsc` returned for it.
+    let js_call = r#"(function() {
+        var input = globalThis.__preprocessed
+            ? {"type": "preprocessed", "preprocessed_player": globalThis.__preprocessed, "requests": globalThis.__requests}
+            : {"type": "player", "player": globalThis.__player, "requests": globalThis.__requests, "output_preprocessed": true};
+        var res = jsc(input);
+        if (res.preprocessed_player) {
+            globalThis.__preprocessed = res.preprocessed_player;
+            delete globalThis.__player;
+            delete res.preprocessed_player;
+        }
+        return JSON.stringify(res);
+    })();"#;
+    let global_value = deno.execute_script("<parse_sig>", js_call)?;
+
+    deno.run_event_loop(Default::default()).await?;
+
+    let local_value = global_value.open(deno.v8_isolate());
+
+    let mut scope = deno.handle_scope();
+    let result_str = local_value.to_rust_string_lossy(&mut scope);
+
+    parse_jsc_output(&serde_json::from_str(&result_str)?)
+}
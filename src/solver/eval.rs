@@ -0,0 +1,37 @@
+use anyhow::anyhow;
+use js_sys::{Function, eval};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::solver::{ChallengeSolver, SolveFut, SolveRequest, parse_jsc_output};
+
+/// Solves challenges with the `eval()` of the JS context the WebAssembly module runs in,
+/// which a strict Content Security Policy blocks.
+pub struct EvalSolver;
+
+impl ChallengeSolver for EvalSolver {
+    fn solve<'a>(&'a self, request: SolveRequest) -> SolveFut<'a> {
+        Box::pin(async move {
+            eval(&request.solver_env().await?)
+                .map_err(|err| anyhow!("JS eval failed: {:?}", err))?;
+
+            let func = eval("jsc")
+                .map_err(|_| anyhow!("jsc not defined"))?
+                .dyn_into::<Function>()
+                .map_err(|_| anyhow!("Failed to defined `jsc` in the JS context."))?;
+
+            let js_input = serde_wasm_bindgen::to_value(&request.jsc_input()).map_err(|_| {
+                anyhow!("Signature deciphering failed due to the failure of serializing input for the JS context.")
+            })?;
+            let result_val = func
+                .call1(&JsValue::NULL, &js_input)
+                .map_err(|e| anyhow!("jsc() call failed: {:?}", e))?;
+
+            let result: Value = serde_wasm_bindgen::from_value(result_val).map_err(|_| {
+                anyhow!("Signature deciphering failed because the JS bridge returned an error.")
+            })?;
+
+            parse_jsc_output(&result)
+        })
+    }
+}
@@ -0,0 +1,201 @@
+//! JavaScript backends solving the signature and `n` challenges of YouTube's player with yt-dlp's ejs.
+//!
+//! `Tydle::new` picks the default backend of the build: `EvalSolver` in WebAssembly,
+//! otherwise `DenoSolver` with the `deno` feature or `QuickJsSolver` with the `quickjs` one.
+//! Use `Tydle::with_solver` to choose another backend or bring your own.
+//!
+//! With the `native-solver` feature, challenges are first solved in Rust from a static analysis
+//! of the player, and only go to the JS backend when that fails.
+
+use std::{collections::HashMap, fmt, future::Future, pin::Pin, sync::Arc};
+
+use anyhow::{Result, bail};
+use serde_json::{Value, json};
+
+pub use crate::cipher::decipher::SignatureType;
+
+#[cfg(all(feature = "deno", not(target_arch = "wasm32")))]
+mod deno;
+#[cfg(target_arch = "wasm32")]
+mod eval;
+#[cfg(feature = "native-solver")]
+mod native;
+#[cfg(all(feature = "quickjs", not(target_arch = "wasm32")))]
+mod quickjs;
+
+#[cfg(all(feature = "deno", not(target_arch = "wasm32")))]
+pub use deno::DenoSolver;
+#[cfg(target_arch = "wasm32")]
+pub use eval::EvalSolver;
+#[cfg(feature = "native-solver")]
+pub(crate) use native::NativeSolver;
+#[cfg(all(feature = "quickjs", not(target_arch = "wasm32")))]
+pub use quickjs::QuickJsSolver;
+
+/// The solution of every challenge, one map per request of `SolveRequest::challenges` and in the same order.
+pub type Solutions = Vec<HashMap<String, String>>;
+
+pub type SolveFut<'a> = Pin<Box<dyn Future<Output = Result<Solutions>> + 'a>>;
+
+pub type BundlesFut = Pin<Box<dyn Future<Output = Result<(String, String)>>>>;
+
+/// The `yt.solver.lib.min.js` and `yt.solver.core.min.js` bundles of ejs, only loaded once a JS
+/// backend asks for them, so challenges solved without one never download them.
+#[derive(Clone)]
+pub struct SolverBundles(Arc<dyn Fn() -> BundlesFut + Send + Sync>);
+
+impl SolverBundles {
+    /// Bundles returned by `load`, which runs every time a backend needs them.
+    pub fn new<F, Fut>(load: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(String, String)>> + 'static,
+    {
+        Self(Arc::new(move || Box::pin(load())))
+    }
+
+    /// Bundles that are already in memory.
+    pub fn from_code(lib_code: String, core_code: String) -> Self {
+        Self::new(move || {
+            let bundles = (lib_code.clone(), core_code.clone());
+            async move { Ok(bundles) }
+        })
+    }
+
+    /// The lib and core bundles, in that order.
+    pub async fn load(&self) -> Result<(String, String)> {
+        (self.0)().await
+    }
+}
+
+impl fmt::Debug for SolverBundles {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SolverBundles").finish_non_exhaustive()
+    }
+}
+
+/// Everything needed to run ejs' `jsc` over a player.
+#[derive(Debug, Clone)]
+pub struct SolveRequest {
+    /// Identifies the player version, for backends keeping state around per player.
+    pub player_key: String,
+    pub player_code: String,
+    pub bundles: SolverBundles,
+    /// The challenges to solve, grouped by their type.
+    pub challenges: Vec<(SignatureType, Vec<String>)>,
+}
+
+impl SolveRequest {
+    /// The script defining `jsc` in the global scope, loading the bundles it's made of.
+    pub async fn solver_env(&self) -> Result<String> {
+        let (lib_code, core_code) = self.bundles.load().await?;
+
+        Ok(format!(
+            "{}\nObject.assign(globalThis, lib);\n{}",
+            lib_code, core_code
+        ))
+    }
+
+    /// The `requests` of `jsc`'s input, one per challenge type.
+    pub fn jsc_requests(&self) -> Value {
+        self.challenges
+            .iter()
+            .map(|(signature_type, challenges)| {
+                json!({"type": signature_type.as_str(), "challenges": challenges})
+            })
+            .collect()
+    }
+
+    /// The complete input of `jsc` for the player.
+    pub fn jsc_input(&self) -> Value {
+        json!({
+            "type": "player",
+            "player": self.player_code,
+            "requests": self.jsc_requests(),
+        })
+    }
+}
+
+/// Runs the ejs solver to decipher signature and `n` challenges.
+///
+/// ```no_run
+/// use tydle::{Tydle, TydleOptions};
+/// use tydle::solver::{ChallengeSolver, SolveFut, SolveRequest, parse_jsc_output};
+/// use anyhow::Result;
+///
+/// struct NodeSolver;
+///
+/// impl ChallengeSolver for NodeSolver {
+///   fn solve<'a>(&'a self, request: SolveRequest) -> SolveFut<'a> {
+///     Box::pin(async move {
+///       let script = format!(
+///         "{}\nconsole.log(JSON.stringify(jsc({})));",
+///         request.solver_env().await?,
+///         request.jsc_input()
+///       );
+///       let output = std::process::Command::new("node").args(["-e", &script]).output()?;
+///
+///       parse_jsc_output(&serde_json::from_slice(&output.stdout)?)
+///     })
+///   }
+/// }
+///
+/// fn main() -> Result<()> {
+///   let ty = Tydle::with_solver(TydleOptions::default(), NodeSolver)?;
+///   Ok(())
+/// }
+/// ```
+pub trait ChallengeSolver: Send + Sync {
+    fn solve<'a>(&'a self, request: SolveRequest) -> SolveFut<'a>;
+}
+
+/// The backend `Tydle::new` uses, if the build has one.
+#[allow(unreachable_code)]
+pub fn default_solver() -> Option<Box<dyn ChallengeSolver>> {
+    #[cfg(target_arch = "wasm32")]
+    return Some(Box::new(EvalSolver));
+    #[cfg(all(feature = "deno", not(target_arch = "wasm32")))]
+    return Some(Box::new(DenoSolver::default()));
+    #[cfg(all(feature = "quickjs", not(target_arch = "wasm32")))]
+    return Some(Box::new(QuickJsSolver));
+
+    None
+}
+
+/// Maps each challenge to its solution from the output of `jsc`, in the order the requests were made.
+pub fn parse_jsc_output(output: &Value) -> Result<Solutions> {
+    let Some(responses) = output.get("responses").and_then(Value::as_array) else {
+        match output.get("error").and_then(Value::as_str) {
+            Some(error) => bail!("Signature deciphering failed: {}", error),
+            None => {
+                bail!("Signature deciphering failed because ytcore returned an invalid response.")
+            }
+        }
+    };
+
+    responses
+        .iter()
+        .map(|response| {
+            if response.get("type").and_then(Value::as_str) == Some("error") {
+                bail!(
+                    "Signature deciphering failed: {}",
+                    response
+                        .get("error")
+                        .and_then(Value::as_str)
+                        .unwrap_or("ytcore returned an error.")
+                )
+            }
+
+            let Some(data) = response.get("data").and_then(Value::as_object) else {
+                bail!("Signature deciphering failed because ytcore returned an invalid response.")
+            };
+
+            Ok(data
+                .iter()
+                .filter_map(|(challenge, solved)| {
+                    solved.as_str().map(|s| (challenge.clone(), s.to_string()))
+                })
+                .collect())
+        })
+        .collect()
+}
@@ -0,0 +1,822 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Result, anyhow, bail};
+use oxc_allocator::Allocator;
+use oxc_ast::ast::{
+    Argument, ArrayExpressionElement, AssignmentTarget, BinaryExpression, BinaryOperator,
+    BindingIdentifier, CallExpression, Expression, Function, IdentifierReference,
+    ObjectPropertyKind, Program, PropertyKey, Statement, TryStatement, UnaryOperator,
+    VariableDeclarator,
+};
+use oxc_ast_visit::{Visit, walk};
+use oxc_parser::Parser;
+use oxc_span::{GetSpan, SourceType, Span};
+use oxc_syntax::scope::ScopeFlags;
+
+use crate::solver::native::interp::{N_FUNCTION_NAME, run_n_program};
+
+/// Upper bound for the declarations pulled in alongside the n-function.
+const MAX_N_PROGRAM_LEN: usize = 1024 * 1024;
+
+/// How many of the largest n-function lookalikes get tried before giving up.
+const MAX_N_CANDIDATES: usize = 4;
+
+/// What static analysis could extract from a player, each `None` when it failed.
+pub struct PlayerTransforms {
+    /// The operations of the signature function, as serialized by `decipher_signature`'s format.
+    pub signature: Option<String>,
+    /// A standalone program defining the n-function as `N_FUNCTION_NAME`.
+    pub nsig: Option<String>,
+}
+
+pub fn analyse_player(code: &str) -> PlayerTransforms {
+    let allocator = Allocator::default();
+    let parsed = Parser::new(&allocator, code, SourceType::cjs()).parse();
+    if parsed.panicked {
+        #[cfg(feature = "logging")]
+        log::warn!("The player couldn't be parsed for static analysis.");
+
+        return PlayerTransforms {
+            signature: None,
+            nsig: None,
+        };
+    }
+
+    let mut collector = Collector::default();
+    collector.visit_program(&parsed.program);
+    collector.collect_candidates(&parsed.program);
+    let player = Player { code, collector };
+
+    let signature = player
+        .extract_signature_ops()
+        .inspect_err(|_err| {
+            #[cfg(feature = "logging")]
+            log::warn!("Couldn't extract the signature function: {}", _err);
+        })
+        .ok();
+    let nsig = player
+        .extract_n_program()
+        .inspect_err(|_err| {
+            #[cfg(feature = "logging")]
+            log::warn!("Couldn't extract the n-function: {}", _err);
+        })
+        .ok();
+
+    PlayerTransforms { signature, nsig }
+}
+
+/// Runs the operations extracted by `analyse_player` over a signature.
+///
+/// They're serialized as a comma-separated list of `r` (reverse), `s<n>` (remove the first `n`
+/// characters) and `w<n>` (swap the first character with the one at `n`).
+pub fn decipher_signature(ops: &str, signature: &str) -> Result<String> {
+    let mut chars: Vec<char> = signature.chars().collect();
+
+    for op in ops.split(',') {
+        let arg = || -> Result<usize> {
+            op[1..]
+                .parse()
+                .map_err(|_| anyhow!("Invalid signature operation `{}`.", op))
+        };
+
+        match op.chars().next() {
+            Some('r') => chars.reverse(),
+            Some('s') => {
+                let n = arg()?.min(chars.len());
+                chars.drain(..n);
+            }
+            Some('w') if !chars.is_empty() => {
+                let n = arg()? % chars.len();
+                chars.swap(0, n);
+            }
+            _ => bail!("Invalid signature operation `{}`.", op),
+        }
+    }
+
+    Ok(chars.into_iter().collect())
+}
+
+/// A top-level declaration of the player.
+#[derive(Clone, Copy)]
+struct Declaration {
+    /// The initializer of a `var`, or a whole function declaration.
+    span: Span,
+    is_function: bool,
+}
+
+#[derive(Default)]
+struct Collector {
+    /// How many functions deep the visitor is, the player's own code lives inside a wrapping IIFE.
+    depth: usize,
+    declarations: HashMap<String, Declaration>,
+    /// Arrays of strings the player indexes into to hide names and literals.
+    string_tables: HashMap<String, Vec<String>>,
+    signature_candidates: Vec<Span>,
+    n_candidates: Vec<Span>,
+}
+
+impl Collector {
+    /// Looks for the signature and n-functions once every string table is known.
+    fn collect_candidates(&mut self, program: &Program) {
+        let mut finder = CandidateFinder {
+            depth: 0,
+            string_tables: &self.string_tables,
+            signature_candidates: vec![],
+            n_candidates: vec![],
+        };
+        finder.visit_program(program);
+
+        self.signature_candidates = finder.signature_candidates;
+        self.n_candidates = finder.n_candidates;
+    }
+}
+
+impl<'a> Visit<'a> for Collector {
+    fn visit_function(&mut self, it: &Function<'a>, flags: ScopeFlags) {
+        if self.depth <= 1
+            && it.is_declaration()
+            && let Some(id) = &it.id
+        {
+            self.declarations
+                .entry(id.name.to_string())
+                .or_insert(Declaration {
+                    span: it.span,
+                    is_function: true,
+                });
+        }
+
+        self.depth += 1;
+        walk::walk_function(self, it, flags);
+        self.depth -= 1;
+    }
+
+    fn visit_arrow_function_expression(&mut self, it: &oxc_ast::ast::ArrowFunctionExpression<'a>) {
+        self.depth += 1;
+        walk::walk_arrow_function_expression(self, it);
+        self.depth -= 1;
+    }
+
+    fn visit_variable_declarator(&mut self, it: &VariableDeclarator<'a>) {
+        if self.depth <= 1
+            && let Some(name) = it.id.get_identifier_name()
+            && let Some(init) = &it.init
+        {
+            self.declarations
+                .entry(name.to_string())
+                .or_insert(Declaration {
+                    span: init.span(),
+                    is_function: false,
+                });
+
+            if let Some(table) = string_table(init) {
+                self.string_tables.entry(name.to_string()).or_insert(table);
+            }
+        }
+
+        walk::walk_variable_declarator(self, it);
+    }
+}
+
+/// Reads `["a", "b"]` or `"a;b".split(";")` as a table of strings.
+fn string_table(init: &Expression) -> Option<Vec<String>> {
+    match init.without_parentheses() {
+        Expression::ArrayExpression(array) => array
+            .elements
+            .iter()
+            .map(|element| match element {
+                ArrayExpressionElement::StringLiteral(literal) => Some(literal.value.to_string()),
+                _ => None,
+            })
+            .collect(),
+        Expression::CallExpression(call) => {
+            let Expression::StaticMemberExpression(callee) = &call.callee else {
+                return None;
+            };
+            let (Expression::StringLiteral(table), [Argument::StringLiteral(separator)]) =
+                (&callee.object, call.arguments.as_slice())
+            else {
+                return None;
+            };
+            if callee.property.name != "split" || separator.value.is_empty() {
+                return None;
+            }
+
+            Some(
+                table
+                    .value
+                    .split(separator.value.as_str())
+                    .map(String::from)
+                    .collect(),
+            )
+        }
+        _ => None,
+    }
+}
+
+/// Resolves an expression to a string if it's a literal, or an entry of a string table.
+fn static_string(
+    expression: &Expression,
+    string_tables: &HashMap<String, Vec<String>>,
+) -> Option<String> {
+    match expression.without_parentheses() {
+        Expression::StringLiteral(literal) => Some(literal.value.to_string()),
+        Expression::TemplateLiteral(template) if template.expressions.is_empty() => template
+            .quasis
+            .first()
+            .and_then(|quasi| quasi.value.cooked.as_ref())
+            .map(|cooked| cooked.to_string()),
+        Expression::ComputedMemberExpression(member) => {
+            let (Expression::Identifier(table), Expression::NumericLiteral(index)) =
+                (&member.object, member.expression.without_parentheses())
+            else {
+                return None;
+            };
+            string_tables
+                .get(table.name.as_str())?
+                .get(index.value as usize)
+                .cloned()
+        }
+        _ => None,
+    }
+}
+
+/// The name of the property a member expression reads.
+fn member_name(
+    expression: &Expression,
+    string_tables: &HashMap<String, Vec<String>>,
+) -> Option<String> {
+    match expression.without_parentheses() {
+        Expression::StaticMemberExpression(member) => Some(member.property.name.to_string()),
+        Expression::ComputedMemberExpression(member) => {
+            static_string(&member.expression, string_tables)
+        }
+        _ => None,
+    }
+}
+
+/// The object of a member expression.
+fn member_object<'b, 'a>(expression: &'b Expression<'a>) -> Option<&'b Expression<'a>> {
+    match expression.without_parentheses() {
+        Expression::StaticMemberExpression(member) => Some(&member.object),
+        Expression::ComputedMemberExpression(member) => Some(&member.object),
+        _ => None,
+    }
+}
+
+/// Whether `call` is `<param>.<method>(<string>)`.
+fn is_param_method_call(
+    call: &CallExpression,
+    param: &str,
+    method: &str,
+    arg: &str,
+    string_tables: &HashMap<String, Vec<String>>,
+) -> bool {
+    let is_param = member_object(&call.callee).is_some_and(|object| object.is_specific_id(param));
+    let has_arg = match call.arguments.as_slice() {
+        [arg_expression] => arg_expression
+            .as_expression()
+            .and_then(|arg_expression| static_string(arg_expression, string_tables))
+            .is_some_and(|value| value == arg),
+        _ => false,
+    };
+
+    is_param
+        && has_arg
+        && member_name(&call.callee, string_tables).is_some_and(|name| name == method)
+}
+
+/// The name of the only parameter of a function.
+fn single_param<'a>(function: &Function<'a>) -> Option<&'a str> {
+    match function.params.items.as_slice() {
+        [param] if function.params.rest.is_none() => param
+            .pattern
+            .get_identifier_name()
+            .map(|name| name.as_str()),
+        _ => None,
+    }
+}
+
+struct CandidateFinder<'t> {
+    depth: usize,
+    string_tables: &'t HashMap<String, Vec<String>>,
+    signature_candidates: Vec<Span>,
+    n_candidates: Vec<Span>,
+}
+
+impl CandidateFinder<'_> {
+    /// `a = a.split(""); ...; return a.join("")`
+    fn is_signature_like(&self, function: &Function, param: &str) -> bool {
+        let Some(body) = &function.body else {
+            return false;
+        };
+        let (Some(Statement::ExpressionStatement(first)), Some(Statement::ReturnStatement(last))) =
+            (body.statements.first(), body.statements.last())
+        else {
+            return false;
+        };
+
+        let splits = match &first.expression {
+            Expression::AssignmentExpression(assignment) => {
+                matches!(&assignment.left, AssignmentTarget::AssignmentTargetIdentifier(id) if id.name == param)
+                    && matches!(&assignment.right, Expression::CallExpression(call) if is_param_method_call(call, param, "split", "", self.string_tables))
+            }
+            _ => false,
+        };
+        let joins = matches!(&last.argument, Some(Expression::CallExpression(call)) if is_param_method_call(call, param, "join", "", self.string_tables));
+
+        splits && joins
+    }
+
+    /// Has a `catch` returning `<something> + <param>`, like every n-function does.
+    fn is_n_like(&self, function: &Function, param: &str) -> bool {
+        let mut finder = CatchReturnFinder {
+            param,
+            found: false,
+        };
+        if let Some(body) = &function.body {
+            finder.visit_function_body(body);
+        }
+
+        finder.found
+    }
+}
+
+impl<'a> Visit<'a> for CandidateFinder<'_> {
+    fn visit_function(&mut self, it: &Function<'a>, flags: ScopeFlags) {
+        if self.depth <= 1
+            && let Some(param) = single_param(it)
+        {
+            if self.is_signature_like(it, param) {
+                self.signature_candidates.push(it.span);
+            } else if self.is_n_like(it, param) {
+                self.n_candidates.push(it.span);
+            }
+        }
+
+        self.depth += 1;
+        walk::walk_function(self, it, flags);
+        self.depth -= 1;
+    }
+
+    fn visit_arrow_function_expression(&mut self, it: &oxc_ast::ast::ArrowFunctionExpression<'a>) {
+        self.depth += 1;
+        walk::walk_arrow_function_expression(self, it);
+        self.depth -= 1;
+    }
+}
+
+struct CatchReturnFinder<'p> {
+    param: &'p str,
+    found: bool,
+}
+
+impl<'a> Visit<'a> for CatchReturnFinder<'_> {
+    fn visit_try_statement(&mut self, it: &TryStatement<'a>) {
+        if let Some(handler) = &it.handler {
+            self.found |= handler.body.body.iter().any(|statement| {
+                matches!(
+                    statement,
+                    Statement::ReturnStatement(ret)
+                        if matches!(&ret.argument, Some(Expression::BinaryExpression(binary))
+                            if binary.operator == BinaryOperator::Addition && binary.right.is_specific_id(self.param))
+                )
+            });
+        }
+
+        walk::walk_try_statement(self, it);
+    }
+
+    // Nested functions have catches of their own.
+    fn visit_function(&mut self, _it: &Function<'a>, _flags: ScopeFlags) {}
+}
+
+/// Collects the identifiers a piece of code reads without declaring them itself.
+#[derive(Default)]
+struct FreeIdentifiers {
+    bound: HashSet<String>,
+    referenced: Vec<String>,
+}
+
+impl FreeIdentifiers {
+    fn of(source: &str) -> Result<Vec<String>> {
+        let allocator = Allocator::default();
+        let parsed = Parser::new(&allocator, source, SourceType::cjs()).parse();
+        if parsed.panicked || !parsed.errors.is_empty() {
+            bail!("A declaration of the player couldn't be parsed on its own.")
+        }
+
+        let mut free = Self::default();
+        free.visit_program(&parsed.program);
+
+        let mut seen = HashSet::new();
+        Ok(free
+            .referenced
+            .into_iter()
+            .filter(|name| !free.bound.contains(name) && seen.insert(name.clone()))
+            .collect())
+    }
+}
+
+impl<'a> Visit<'a> for FreeIdentifiers {
+    fn visit_binding_identifier(&mut self, it: &BindingIdentifier<'a>) {
+        self.bound.insert(it.name.to_string());
+    }
+
+    fn visit_identifier_reference(&mut self, it: &IdentifierReference<'a>) {
+        self.referenced.push(it.name.to_string());
+    }
+}
+
+/// Parses `source` as a single function expression.
+fn parse_function<'a>(allocator: &'a Allocator, source: &'a str) -> Result<&'a Function<'a>> {
+    let parsed = Parser::new(allocator, source, SourceType::cjs()).parse();
+    if parsed.panicked || !parsed.errors.is_empty() {
+        bail!("A function of the player couldn't be parsed on its own.")
+    }
+
+    let program = allocator.alloc(parsed.program);
+    match program.body.as_slice() {
+        [Statement::ExpressionStatement(statement)] => {
+            match statement.expression.without_parentheses() {
+                Expression::FunctionExpression(function) => Ok(function),
+                _ => bail!("Expected a function expression."),
+            }
+        }
+        _ => bail!("Expected a function expression."),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SignatureOp {
+    Reverse,
+    Splice,
+    Swap,
+}
+
+struct Player<'c> {
+    code: &'c str,
+    collector: Collector,
+}
+
+impl Player<'_> {
+    fn source(&self, span: Span) -> &str {
+        span.source_text(self.code)
+    }
+
+    fn extract_signature_ops(&self) -> Result<String> {
+        let mut last_err = anyhow!("No function of the player looks like the signature function.");
+
+        for span in &self.collector.signature_candidates {
+            match self.signature_ops(*span) {
+                Ok(ops) => return Ok(ops),
+                Err(err) => last_err = err,
+            }
+        }
+
+        Err(last_err)
+    }
+
+    fn signature_ops(&self, span: Span) -> Result<String> {
+        let string_tables = &self.collector.string_tables;
+        let allocator = Allocator::default();
+        let source = format!("({})", self.source(span));
+        let function = parse_function(&allocator, &source)?;
+
+        let param =
+            single_param(function).ok_or_else(|| anyhow!("Expected a single parameter."))?;
+        let statements = &function
+            .body
+            .as_ref()
+            .ok_or_else(|| anyhow!("Expected a function body."))?
+            .statements;
+
+        let mut calls = vec![];
+        for statement in &statements[1..statements.len() - 1] {
+            let Statement::ExpressionStatement(statement) = statement else {
+                bail!("Unexpected statement in the signature function.")
+            };
+            match statement.expression.without_parentheses() {
+                Expression::SequenceExpression(sequence) => {
+                    calls.extend(sequence.expressions.iter())
+                }
+                expression => calls.push(expression),
+            }
+        }
+
+        let mut helpers: HashMap<String, HashMap<String, SignatureOp>> = HashMap::new();
+        let mut ops = vec![];
+        for call in calls {
+            let Expression::CallExpression(call) = call.without_parentheses() else {
+                bail!("Unexpected expression in the signature function.")
+            };
+            let Some(Expression::Identifier(helper)) = member_object(&call.callee) else {
+                bail!("Expected a call to a helper object.")
+            };
+            let method = member_name(&call.callee, string_tables)
+                .ok_or_else(|| anyhow!("Couldn't resolve a helper method name."))?;
+            let n = match call.arguments.as_slice() {
+                [target, Argument::NumericLiteral(n)]
+                    if target
+                        .as_expression()
+                        .is_some_and(|target| target.is_specific_id(param)) =>
+                {
+                    n.value as usize
+                }
+                _ => bail!("Unexpected arguments to a helper method."),
+            };
+
+            let helper = helper.name.to_string();
+            if !helpers.contains_key(&helper) {
+                let methods = self.helper_methods(&helper)?;
+                helpers.insert(helper.clone(), methods);
+            }
+
+            ops.push(match helpers[&helper].get(&method) {
+                Some(SignatureOp::Reverse) => "r".to_string(),
+                Some(SignatureOp::Splice) => format!("s{}", n),
+                Some(SignatureOp::Swap) => format!("w{}", n),
+                None => bail!("The helper object has no `{}` method.", method),
+            });
+        }
+
+        if ops.is_empty() {
+            bail!("The signature function doesn't transform anything.")
+        }
+
+        Ok(ops.join(","))
+    }
+
+    /// Classifies the methods of the helper object the signature function calls.
+    fn helper_methods(&self, name: &str) -> Result<HashMap<String, SignatureOp>> {
+        let string_tables = &self.collector.string_tables;
+        let declaration = self
+            .collector
+            .declarations
+            .get(name)
+            .filter(|declaration| !declaration.is_function)
+            .ok_or_else(|| anyhow!("The helper object `{}` isn't declared.", name))?;
+
+        let allocator = Allocator::default();
+        let source = format!("({})", self.source(declaration.span));
+        let parsed = Parser::new(&allocator, &source, SourceType::cjs()).parse();
+        let Some(Statement::ExpressionStatement(statement)) = parsed.program.body.first() else {
+            bail!("The helper object `{}` couldn't be parsed.", name)
+        };
+        let Expression::ObjectExpression(object) = statement.expression.without_parentheses()
+        else {
+            bail!("The helper `{}` isn't an object literal.", name)
+        };
+
+        let mut methods = HashMap::new();
+        for property in &object.properties {
+            let ObjectPropertyKind::ObjectProperty(property) = property else {
+                continue;
+            };
+            let key = match &property.key {
+                PropertyKey::StaticIdentifier(id) => Some(id.name.to_string()),
+                key => key
+                    .as_expression()
+                    .and_then(|key| static_string(key, string_tables)),
+            };
+            let Expression::FunctionExpression(function) = property.value.without_parentheses()
+            else {
+                continue;
+            };
+
+            let mut classifier = OpClassifier {
+                string_tables,
+                calls: vec![],
+                has_modulo: false,
+            };
+            classifier.visit_function(function, ScopeFlags::Function);
+
+            let op = if classifier.calls.iter().any(|(name, _)| name == "reverse") {
+                SignatureOp::Reverse
+            } else if classifier.has_modulo
+                || classifier
+                    .calls
+                    .iter()
+                    .any(|(name, argc)| name == "splice" && *argc == 3)
+            {
+                SignatureOp::Swap
+            } else if classifier
+                .calls
+                .iter()
+                .any(|(name, argc)| name == "splice" && *argc == 2)
+            {
+                SignatureOp::Splice
+            } else {
+                continue;
+            };
+
+            if let Some(key) = key {
+                methods.insert(key, op);
+            }
+        }
+
+        Ok(methods)
+    }
+
+    fn extract_n_program(&self) -> Result<String> {
+        let mut candidates = self.collector.n_candidates.clone();
+        candidates.sort_by_key(|span| std::cmp::Reverse(span.size()));
+
+        let mut last_err = anyhow!("No function of the player looks like the n-function.");
+        for span in candidates.into_iter().take(MAX_N_CANDIDATES) {
+            match self.n_program(span).and_then(|program| {
+                validate_n_program(&program)?;
+                Ok(program)
+            }) {
+                Ok(program) => return Ok(program),
+                Err(err) => last_err = err,
+            }
+        }
+
+        Err(last_err)
+    }
+
+    /// Builds a program defining the n-function along with the declarations it depends on.
+    fn n_program(&self, span: Span) -> Result<String> {
+        let function_source = self.without_undefined_guards(self.source(span))?;
+
+        let mut included = HashSet::from([N_FUNCTION_NAME.to_string()]);
+        let mut queue = FreeIdentifiers::of(&format!("({})", function_source))?;
+        let mut declarations = vec![];
+        let mut len = function_source.len();
+
+        while let Some(name) = queue.pop() {
+            if !included.insert(name.clone()) {
+                continue;
+            }
+            // Globals of the browser, the interpreter tells about them when they're actually used.
+            let Some(declaration) = self.collector.declarations.get(&name) else {
+                continue;
+            };
+
+            let source = match declaration.is_function {
+                true => self.source(declaration.span).to_string(),
+                false => format!("var {} = {};", name, self.source(declaration.span)),
+            };
+            len += source.len();
+            if len > MAX_N_PROGRAM_LEN {
+                bail!("The n-function depends on too much of the player.")
+            }
+
+            queue.extend(FreeIdentifiers::of(&source)?);
+            declarations.push(source);
+        }
+
+        declarations.push(format!("var {} = {};", N_FUNCTION_NAME, function_source));
+        Ok(declarations.join("\n"))
+    }
+
+    /// Drops the `if (typeof x === "undefined") return a;` guards the player adds to the n-function,
+    /// which make copies of it return its input unchanged.
+    fn without_undefined_guards(&self, function_source: &str) -> Result<String> {
+        let allocator = Allocator::default();
+        let source = format!("({})", function_source);
+        let function = parse_function(&allocator, &source)?;
+        let param =
+            single_param(function).ok_or_else(|| anyhow!("Expected a single parameter."))?;
+
+        let mut guards = vec![];
+        for statement in function.body.iter().flat_map(|body| &body.statements) {
+            let Statement::IfStatement(statement) = statement else {
+                continue;
+            };
+            let returns_param = match &statement.consequent {
+                Statement::ReturnStatement(ret) => ret
+                    .argument
+                    .as_ref()
+                    .is_some_and(|arg| arg.is_specific_id(param)),
+                Statement::BlockStatement(block) => matches!(
+                    block.body.as_slice(),
+                    [Statement::ReturnStatement(ret)] if ret.argument.as_ref().is_some_and(|arg| arg.is_specific_id(param))
+                ),
+                _ => false,
+            };
+
+            if returns_param
+                && statement.alternate.is_none()
+                && let Expression::BinaryExpression(test) = statement.test.without_parentheses()
+                && is_undefined_check(test, &self.collector.string_tables)
+            {
+                guards.push(statement.span);
+            }
+        }
+
+        // Spans are offset by the opening parenthesis.
+        let mut stripped = function_source.to_string();
+        for span in guards.iter().rev() {
+            stripped.replace_range(span.start as usize - 1..span.end as usize - 1, ";");
+        }
+
+        Ok(stripped)
+    }
+}
+
+/// `typeof x === "undefined"`, in any order and with any equality operator.
+fn is_undefined_check(
+    test: &BinaryExpression,
+    string_tables: &HashMap<String, Vec<String>>,
+) -> bool {
+    let is_typeof = |expression: &Expression| matches!(expression.without_parentheses(), Expression::UnaryExpression(unary) if unary.operator == UnaryOperator::Typeof);
+    let is_undefined = |expression: &Expression| {
+        static_string(expression, string_tables).is_some_and(|value| value == "undefined")
+    };
+
+    test.operator.is_equality()
+        && ((is_typeof(&test.left) && is_undefined(&test.right))
+            || (is_undefined(&test.left) && is_typeof(&test.right)))
+}
+
+/// Makes sure the extracted program actually solves challenges, since the n-function throws
+/// internally and swallows that into an error string on anything unexpected.
+fn validate_n_program(program: &str) -> Result<()> {
+    let challenge = "4xMrV3wXlG5eKhtK".to_string();
+
+    run_n_program(program, std::slice::from_ref(&challenge))?
+        .remove(&challenge)
+        .ok_or_else(|| anyhow!("The extracted n-function wasn't run."))??;
+
+    Ok(())
+}
+
+struct OpClassifier<'t> {
+    string_tables: &'t HashMap<String, Vec<String>>,
+    /// Method names called in the function, with their argument count.
+    calls: Vec<(String, usize)>,
+    has_modulo: bool,
+}
+
+impl<'a> Visit<'a> for OpClassifier<'_> {
+    fn visit_call_expression(&mut self, it: &CallExpression<'a>) {
+        if let Some(name) = member_name(&it.callee, self.string_tables) {
+            self.calls.push((name, it.arguments.len()));
+        }
+
+        walk::walk_call_expression(self, it);
+    }
+
+    fn visit_binary_expression(&mut self, it: &BinaryExpression<'a>) {
+        self.has_modulo |= it.operator == BinaryOperator::Remainder;
+
+        walk::walk_binary_expression(self, it);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A player with the signature operations in a helper object and an n-function indexing a
+    /// string table and guarding its transforms with a `try`/`catch`.
+    const PLAYER: &str = r#"var _yt_player={};(function(g){var window=this;
+var T="split;join;reverse;splice;length;undefined".split(";");
+var Xy={Ab:function(a){a[T[2]]()},cD:function(a,b){a.splice(0,b)},eF:function(a,b){var c=a[0];a[0]=a[b%a.length];a[b%a.length]=c}};
+var sigf=function(a){a=a[T[0]]("");Xy.cD(a,2);Xy.Ab(a,5);Xy.eF(a,13);Xy["eF"](a,41);return a[T[1]]("")};
+var Qz=5;
+var nfn=function(a){var b=a[T[0]](""),c=[function(d,e){e=(e%d[T[4]]+d[T[4]])%d[T[4]];d[T[3]](0,1,d[T[3]](e,1,d[0])[0])},function(d){d[T[2]]()},b,"tydle"];
+if(typeof Qz===T[5])return a;
+try{c[0](c[2],7);c[1](c[2]);c[0](c[2],c[3][T[4]])}catch(d){return"enhanced_except_"+a}return b[T[1]]("")};
+g.sig=sigf;g.n=nfn;})(_yt_player);"#;
+
+    #[test]
+    fn extracts_helper_object_operations() {
+        let ops = analyse_player(PLAYER).signature.unwrap();
+
+        assert_eq!(ops, "s2,r,w13,w41");
+        assert_eq!(
+            decipher_signature(&ops, "abcdefghijklmnopqrstuvwxyz0123456789").unwrap(),
+            "2876543w10zyx9vutsrqponmlkjihgfedc"
+        );
+    }
+
+    #[test]
+    fn extracts_n_function_with_string_table() {
+        let program = analyse_player(PLAYER).nsig.unwrap();
+        let challenges = [
+            "4xMrV3wXlG5eKhtK".to_string(),
+            "T4NP2hYwGzXTQ2Vj".to_string(),
+        ];
+        let mut solved = run_n_program(&program, &challenges).unwrap();
+
+        assert_eq!(
+            solved.remove(&challenges[0]).unwrap().unwrap(),
+            "5thKeKGl4w3VrMxX"
+        );
+        assert_eq!(
+            solved.remove(&challenges[1]).unwrap().unwrap(),
+            "XV2QTjzGTYh2PN4w"
+        );
+    }
+
+    #[test]
+    fn rejects_n_function_with_unsupported_construct() {
+        let player = PLAYER.replace("c[1](c[2]);", "c[1](c[2]);new Date(1);");
+        let transforms = analyse_player(&player);
+
+        assert!(transforms.signature.is_some());
+        assert!(transforms.nsig.is_none());
+    }
+}
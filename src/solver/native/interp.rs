@@ -0,0 +1,1517 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+use anyhow::{Result, anyhow, bail};
+use oxc_allocator::Allocator;
+use oxc_ast::ast::{
+    Argument, ArrayExpressionElement, AssignmentOperator, AssignmentTarget, BinaryOperator,
+    Expression, ForStatementInit, FormalParameters, FunctionBody, LogicalOperator,
+    ObjectPropertyKind, PropertyKey, SimpleAssignmentTarget, Statement, UnaryOperator,
+    UpdateOperator, VariableDeclaration,
+};
+use oxc_parser::Parser;
+use oxc_span::SourceType;
+
+/// Name the extracted n-function is assigned to in the programs built by `analysis`.
+pub const N_FUNCTION_NAME: &str = "__tydle_n";
+
+/// Evaluation steps after which a run is given up on, so a bad extraction can't loop forever.
+const MAX_STEPS: usize = 2_000_000;
+
+/// Runs the n-function of an extracted program for every challenge, failing the challenges
+/// it couldn't solve on their own.
+pub fn run_n_program(
+    source: &str,
+    challenges: &[String],
+) -> Result<HashMap<String, Result<String>>> {
+    let allocator = Allocator::default();
+    let parsed = Parser::new(&allocator, source, SourceType::cjs()).parse();
+    if parsed.panicked || !parsed.errors.is_empty() {
+        bail!("The extracted n-function program couldn't be parsed.")
+    }
+
+    let interpreter = Interpreter::new(&parsed.program.body);
+    let n_function = interpreter
+        .lookup(N_FUNCTION_NAME)
+        .map_err(Abort::into_error)?;
+
+    let solve = |challenge: &String| -> Result<String> {
+        interpreter.steps.set(0);
+        let solved = interpreter
+            .call(
+                &n_function,
+                Value::Undefined,
+                vec![Value::string(challenge)],
+            )
+            .map_err(Abort::into_error)?;
+
+        match solved {
+            Value::String(solved) => check_n_solution(challenge, solved.to_string()),
+            _ => bail!("The n-function returned a non-string value."),
+        }
+    };
+
+    Ok(challenges
+        .iter()
+        .map(|challenge| (challenge.clone(), solve(challenge)))
+        .collect())
+}
+
+/// The n-function catches its own errors and returns them as a string built from the challenge,
+/// which has to be told apart from a real solution.
+fn check_n_solution(challenge: &str, solved: String) -> Result<String> {
+    if solved.is_empty()
+        || solved == challenge
+        || solved.starts_with("enhanced_except_")
+        || solved.ends_with(&format!("_w8_{}", challenge))
+    {
+        bail!(
+            "The n-function returned an invalid solution `{}` for `{}`.",
+            solved,
+            challenge
+        )
+    }
+
+    Ok(solved)
+}
+
+#[derive(Clone)]
+enum Value<'a> {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(Rc<str>),
+    Array(Rc<RefCell<Vec<Value<'a>>>>),
+    Object(Rc<RefCell<HashMap<String, Value<'a>>>>),
+    Function(Rc<Closure<'a>>),
+    Builtin(Builtin),
+}
+
+struct Closure<'a> {
+    params: &'a FormalParameters<'a>,
+    body: &'a FunctionBody<'a>,
+    /// Arrow functions like `x => x + 1` return their only expression statement.
+    is_expression: bool,
+    /// Arrow functions don't bind `this`.
+    is_arrow: bool,
+    scope: Rc<Scope<'a>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Builtin {
+    Math,
+    String,
+    MathFunction(&'static str),
+    FromCharCode,
+}
+
+impl<'a> Value<'a> {
+    fn string(s: &str) -> Self {
+        Self::String(Rc::from(s))
+    }
+
+    fn array(values: Vec<Value<'a>>) -> Self {
+        Self::Array(Rc::new(RefCell::new(values)))
+    }
+
+    fn to_bool(&self) -> bool {
+        match self {
+            Self::Undefined | Self::Null => false,
+            Self::Bool(b) => *b,
+            Self::Number(n) => *n != 0.0 && !n.is_nan(),
+            Self::String(s) => !s.is_empty(),
+            _ => true,
+        }
+    }
+
+    fn to_number(&self) -> Result<f64, Abort<'a>> {
+        Ok(match self {
+            Self::Undefined => f64::NAN,
+            Self::Null => 0.0,
+            Self::Bool(b) => *b as u8 as f64,
+            Self::Number(n) => *n,
+            Self::String(s) => string_to_number(s),
+            Self::Array(_) => string_to_number(&self.to_js_string()?),
+            _ => f64::NAN,
+        })
+    }
+
+    fn to_js_string(&self) -> Result<String, Abort<'a>> {
+        Ok(match self {
+            Self::Undefined => "undefined".into(),
+            Self::Null => "null".into(),
+            Self::Bool(b) => b.to_string(),
+            Self::Number(n) => number_to_string(*n),
+            Self::String(s) => s.to_string(),
+            Self::Array(values) => {
+                let values = values.borrow();
+                let mut parts = Vec::with_capacity(values.len());
+                for value in values.iter() {
+                    parts.push(match value {
+                        Self::Undefined | Self::Null => String::new(),
+                        value => value.to_js_string()?,
+                    });
+                }
+                parts.join(",")
+            }
+            Self::Object(_) => "[object Object]".into(),
+            Self::Function(_) | Self::Builtin(_) => {
+                return Err(Abort::unsupported("converting a function to a string"));
+            }
+        })
+    }
+
+    /// `ToPrimitive`, which only changes arrays and objects.
+    fn to_primitive(&self) -> Result<Value<'a>, Abort<'a>> {
+        Ok(match self {
+            Self::Array(_) | Self::Object(_) => Self::String(Rc::from(self.to_js_string()?)),
+            value => value.clone(),
+        })
+    }
+
+    fn type_of(&self) -> &'static str {
+        match self {
+            Self::Undefined => "undefined",
+            Self::Null | Self::Array(_) | Self::Object(_) => "object",
+            Self::Bool(_) => "boolean",
+            Self::Number(_) => "number",
+            Self::String(_) => "string",
+            Self::Function(_) => "function",
+            Self::Builtin(Builtin::Math | Builtin::String) => "object",
+            Self::Builtin(_) => "function",
+        }
+    }
+
+    fn strict_equals(&self, other: &Value<'a>) -> bool {
+        match (self, other) {
+            (Self::Undefined, Self::Undefined) | (Self::Null, Self::Null) => true,
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            (Self::Number(a), Self::Number(b)) => a == b,
+            (Self::String(a), Self::String(b)) => a == b,
+            (Self::Array(a), Self::Array(b)) => Rc::ptr_eq(a, b),
+            (Self::Object(a), Self::Object(b)) => Rc::ptr_eq(a, b),
+            (Self::Function(a), Self::Function(b)) => Rc::ptr_eq(a, b),
+            (Self::Builtin(a), Self::Builtin(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    fn loose_equals(&self, other: &Value<'a>) -> Result<bool, Abort<'a>> {
+        Ok(match (self, other) {
+            (Self::Undefined | Self::Null, Self::Undefined | Self::Null) => true,
+            (Self::Undefined | Self::Null, _) | (_, Self::Undefined | Self::Null) => false,
+            (Self::Number(_), Self::String(_)) | (Self::String(_), Self::Number(_)) => {
+                self.to_number()? == other.to_number()?
+            }
+            (Self::Bool(_), _) => Value::Number(self.to_number()?).loose_equals(other)?,
+            (_, Self::Bool(_)) => self.loose_equals(&Value::Number(other.to_number()?))?,
+            (Self::Array(_) | Self::Object(_), Self::Number(_) | Self::String(_))
+            | (Self::Number(_) | Self::String(_), Self::Array(_) | Self::Object(_)) => {
+                self.to_primitive()?.loose_equals(&other.to_primitive()?)?
+            }
+            _ => self.strict_equals(other),
+        })
+    }
+}
+
+fn string_to_number(s: &str) -> f64 {
+    let s = s.trim();
+    if s.is_empty() {
+        return 0.0;
+    }
+    if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        return i64::from_str_radix(hex, 16).map_or(f64::NAN, |n| n as f64);
+    }
+
+    match s {
+        "Infinity" | "+Infinity" => f64::INFINITY,
+        "-Infinity" => f64::NEG_INFINITY,
+        _ => s.parse().unwrap_or(f64::NAN),
+    }
+}
+
+fn number_to_string(n: f64) -> String {
+    if n.is_nan() {
+        "NaN".into()
+    } else if n.is_infinite() {
+        match n > 0.0 {
+            true => "Infinity".into(),
+            false => "-Infinity".into(),
+        }
+    } else if n.fract() == 0.0 && n.abs() < 1e21 {
+        // Also turns `-0` into `0`.
+        format!("{}", n as i64)
+    } else {
+        n.to_string()
+    }
+}
+
+fn to_int32(n: f64) -> i32 {
+    match n.is_finite() {
+        true => n.trunc().rem_euclid(4294967296.0) as u32 as i32,
+        false => 0,
+    }
+}
+
+/// Resolves a relative index like the `start` of `splice` and `slice`.
+fn relative_index(index: f64, len: usize) -> usize {
+    let index = if index.is_nan() { 0.0 } else { index.trunc() };
+    match index < 0.0 {
+        true => (len as f64 + index).max(0.0) as usize,
+        false => index.min(len as f64) as usize,
+    }
+}
+
+/// Either a JS exception, or something the interpreter can't run, which `catch` blocks don't catch.
+enum Abort<'a> {
+    Throw(Value<'a>),
+    Unsupported(String),
+}
+
+impl<'a> Abort<'a> {
+    fn unsupported(what: &str) -> Self {
+        Self::Unsupported(what.into())
+    }
+
+    fn type_error(message: &str) -> Self {
+        Self::Throw(Value::string(&format!("TypeError: {}", message)))
+    }
+
+    fn into_error(self) -> anyhow::Error {
+        match self {
+            Self::Throw(value) => anyhow!(
+                "The n-function threw: {}",
+                value.to_js_string().unwrap_or_default()
+            ),
+            Self::Unsupported(what) => {
+                anyhow!("The n-function uses {}, which isn't supported.", what)
+            }
+        }
+    }
+}
+
+type Eval<'a, T> = Result<T, Abort<'a>>;
+
+enum Flow<'a> {
+    Normal,
+    Return(Value<'a>),
+    Break,
+    Continue,
+}
+
+struct Scope<'a> {
+    vars: RefCell<HashMap<&'a str, Value<'a>>>,
+    parent: Option<Rc<Scope<'a>>>,
+}
+
+impl<'a> Scope<'a> {
+    fn new(parent: Option<Rc<Scope<'a>>>) -> Rc<Self> {
+        Rc::new(Self {
+            vars: RefCell::new(HashMap::new()),
+            parent,
+        })
+    }
+
+    fn get(&self, name: &str) -> Option<Value<'a>> {
+        match self.vars.borrow().get(name) {
+            Some(value) => Some(value.clone()),
+            None => self.parent.as_ref().and_then(|parent| parent.get(name)),
+        }
+    }
+
+    /// Assigns to the closest scope declaring `name`, returns `false` if none does.
+    fn set(&self, name: &'a str, value: Value<'a>) -> bool {
+        if let Some(var) = self.vars.borrow_mut().get_mut(name) {
+            *var = value;
+            return true;
+        }
+
+        match &self.parent {
+            Some(parent) => parent.set(name, value),
+            None => false,
+        }
+    }
+
+    fn declare(&self, name: &'a str, value: Value<'a>) {
+        self.vars.borrow_mut().insert(name, value);
+    }
+}
+
+/// A tree-walking interpreter for the small subset of JS the n-function is written in.
+struct Interpreter<'a> {
+    globals: Rc<Scope<'a>>,
+    /// Top-level declarations, only evaluated once something uses them.
+    lazy_globals: HashMap<&'a str, LazyGlobal<'a>>,
+    steps: Cell<usize>,
+}
+
+#[derive(Clone, Copy)]
+enum LazyGlobal<'a> {
+    Var(&'a Expression<'a>),
+    Function(&'a FormalParameters<'a>, &'a FunctionBody<'a>),
+}
+
+impl<'a> Interpreter<'a> {
+    fn new(program: &'a [Statement<'a>]) -> Self {
+        let mut lazy_globals = HashMap::new();
+
+        for statement in program {
+            match statement {
+                Statement::VariableDeclaration(declaration) => {
+                    for declarator in &declaration.declarations {
+                        if let (Some(name), Some(init)) =
+                            (declarator.id.get_identifier_name(), &declarator.init)
+                        {
+                            lazy_globals.insert(name.as_str(), LazyGlobal::Var(init));
+                        }
+                    }
+                }
+                Statement::FunctionDeclaration(function) => {
+                    if let (Some(id), Some(body)) = (&function.id, &function.body) {
+                        lazy_globals.insert(
+                            id.name.as_str(),
+                            LazyGlobal::Function(&function.params, body),
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            globals: Scope::new(None),
+            lazy_globals,
+            steps: Cell::new(0),
+        }
+    }
+
+    fn step(&self) -> Eval<'a, ()> {
+        self.steps.set(self.steps.get() + 1);
+        match self.steps.get() > MAX_STEPS {
+            true => Err(Abort::unsupported("more evaluation steps than allowed")),
+            false => Ok(()),
+        }
+    }
+
+    fn lookup(&self, name: &'a str) -> Eval<'a, Value<'a>> {
+        if let Some(value) = self.globals.get(name) {
+            return Ok(value);
+        }
+
+        let value = match self.lazy_globals.get(name).copied() {
+            Some(LazyGlobal::Var(init)) => {
+                // Like hoisting would, lets an initializer reading its own global see `undefined`.
+                self.globals.declare(name, Value::Undefined);
+                self.eval(init, &self.globals.clone())?
+            }
+            Some(LazyGlobal::Function(params, body)) => Value::Function(Rc::new(Closure {
+                params,
+                body,
+                is_expression: false,
+                is_arrow: false,
+                scope: self.globals.clone(),
+            })),
+            None => {
+                return match name {
+                    "undefined" => Ok(Value::Undefined),
+                    "NaN" => Ok(Value::Number(f64::NAN)),
+                    "Infinity" => Ok(Value::Number(f64::INFINITY)),
+                    "Math" => Ok(Value::Builtin(Builtin::Math)),
+                    "String" => Ok(Value::Builtin(Builtin::String)),
+                    _ => Err(Abort::Unsupported(format!("the unknown global `{}`", name))),
+                };
+            }
+        };
+
+        self.globals.declare(name, value.clone());
+        Ok(value)
+    }
+
+    fn get_var(&self, name: &'a str, scope: &Rc<Scope<'a>>) -> Eval<'a, Value<'a>> {
+        match scope.get(name) {
+            Some(value) => Ok(value),
+            None => self.lookup(name),
+        }
+    }
+
+    fn set_var(&self, name: &'a str, value: Value<'a>, scope: &Rc<Scope<'a>>) {
+        if !scope.set(name, value.clone()) {
+            self.globals.declare(name, value);
+        }
+    }
+
+    fn call(
+        &self,
+        function: &Value<'a>,
+        this: Value<'a>,
+        args: Vec<Value<'a>>,
+    ) -> Eval<'a, Value<'a>> {
+        match function {
+            Value::Function(closure) => {
+                let scope = Scope::new(Some(closure.scope.clone()));
+                if !closure.is_arrow {
+                    scope.declare("this", this);
+                }
+
+                for (i, param) in closure.params.items.iter().enumerate() {
+                    let Some(name) = param.pattern.get_identifier_name() else {
+                        return Err(Abort::unsupported("destructured parameters"));
+                    };
+                    let mut value = args.get(i).cloned().unwrap_or(Value::Undefined);
+                    if let (Value::Undefined, Some(initializer)) = (&value, &param.initializer) {
+                        value = self.eval(initializer, &scope)?;
+                    }
+                    scope.declare(name.as_str(), value);
+                }
+                if closure.params.rest.is_some() {
+                    return Err(Abort::unsupported("rest parameters"));
+                }
+
+                if closure.is_expression
+                    && let [Statement::ExpressionStatement(statement)] =
+                        closure.body.statements.as_slice()
+                {
+                    return self.eval(&statement.expression, &scope);
+                }
+
+                self.hoist(&closure.body.statements, &scope);
+                match self.exec_all(&closure.body.statements, &scope)? {
+                    Flow::Return(value) => Ok(value),
+                    _ => Ok(Value::Undefined),
+                }
+            }
+            Value::Builtin(Builtin::MathFunction(name)) => {
+                let n = |i: usize| -> Eval<'a, f64> {
+                    args.get(i).unwrap_or(&Value::Undefined).to_number()
+                };
+                Ok(Value::Number(match *name {
+                    "floor" => n(0)?.floor(),
+                    "ceil" => n(0)?.ceil(),
+                    "round" => (n(0)? + 0.5).floor(),
+                    "abs" => n(0)?.abs(),
+                    "pow" => n(0)?.powf(n(1)?),
+                    "min" => args
+                        .iter()
+                        .map(Value::to_number)
+                        .collect::<Eval<'a, Vec<f64>>>()?
+                        .into_iter()
+                        .fold(f64::INFINITY, f64::min),
+                    "max" => args
+                        .iter()
+                        .map(Value::to_number)
+                        .collect::<Eval<'a, Vec<f64>>>()?
+                        .into_iter()
+                        .fold(f64::NEG_INFINITY, f64::max),
+                    _ => return Err(Abort::Unsupported(format!("`Math.{}`", name))),
+                }))
+            }
+            Value::Builtin(Builtin::FromCharCode) => {
+                let mut s = String::new();
+                for arg in &args {
+                    let code = to_int32(arg.to_number()?) as u16;
+                    s.push(char::from_u32(code as u32).unwrap_or(char::REPLACEMENT_CHARACTER));
+                }
+                Ok(Value::String(Rc::from(s)))
+            }
+            _ => Err(Abort::type_error("not a function")),
+        }
+    }
+
+    /// Declares `var`s and function declarations ahead of running a function body, like JS does.
+    fn hoist(&self, statements: &'a [Statement<'a>], scope: &Rc<Scope<'a>>) {
+        for statement in statements {
+            match statement {
+                Statement::VariableDeclaration(declaration) => {
+                    self.hoist_declaration(declaration, scope)
+                }
+                Statement::FunctionDeclaration(function) => {
+                    if let (Some(id), Some(body)) = (&function.id, &function.body) {
+                        scope.declare(
+                            id.name.as_str(),
+                            Value::Function(Rc::new(Closure {
+                                params: &function.params,
+                                body,
+                                is_expression: false,
+                                is_arrow: false,
+                                scope: scope.clone(),
+                            })),
+                        );
+                    }
+                }
+                Statement::BlockStatement(block) => self.hoist(&block.body, scope),
+                Statement::IfStatement(statement) => {
+                    self.hoist(std::slice::from_ref(&statement.consequent), scope);
+                    if let Some(alternate) = &statement.alternate {
+                        self.hoist(std::slice::from_ref(alternate), scope);
+                    }
+                }
+                Statement::ForStatement(statement) => {
+                    if let Some(ForStatementInit::VariableDeclaration(declaration)) =
+                        &statement.init
+                    {
+                        self.hoist_declaration(declaration, scope);
+                    }
+                    self.hoist(std::slice::from_ref(&statement.body), scope);
+                }
+                Statement::WhileStatement(statement) => {
+                    self.hoist(std::slice::from_ref(&statement.body), scope)
+                }
+                Statement::DoWhileStatement(statement) => {
+                    self.hoist(std::slice::from_ref(&statement.body), scope)
+                }
+                Statement::TryStatement(statement) => {
+                    self.hoist(&statement.block.body, scope);
+                    if let Some(handler) = &statement.handler {
+                        self.hoist(&handler.body.body, scope);
+                    }
+                    if let Some(finalizer) = &statement.finalizer {
+                        self.hoist(&finalizer.body, scope);
+                    }
+                }
+                Statement::SwitchStatement(statement) => {
+                    for case in &statement.cases {
+                        self.hoist(&case.consequent, scope);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn hoist_declaration(&self, declaration: &'a VariableDeclaration<'a>, scope: &Rc<Scope<'a>>) {
+        for declarator in &declaration.declarations {
+            if let Some(name) = declarator.id.get_identifier_name()
+                && !scope.vars.borrow().contains_key(name.as_str())
+            {
+                scope.declare(name.as_str(), Value::Undefined);
+            }
+        }
+    }
+
+    fn exec_all(
+        &self,
+        statements: &'a [Statement<'a>],
+        scope: &Rc<Scope<'a>>,
+    ) -> Eval<'a, Flow<'a>> {
+        for statement in statements {
+            match self.exec(statement, scope)? {
+                Flow::Normal => {}
+                flow => return Ok(flow),
+            }
+        }
+
+        Ok(Flow::Normal)
+    }
+
+    /// Runs a loop body, returning the flow to leave the loop with, if any.
+    fn exec_loop_body(
+        &self,
+        body: &'a Statement<'a>,
+        scope: &Rc<Scope<'a>>,
+    ) -> Eval<'a, Option<Flow<'a>>> {
+        Ok(match self.exec(body, scope)? {
+            Flow::Break => Some(Flow::Normal),
+            Flow::Return(value) => Some(Flow::Return(value)),
+            Flow::Normal | Flow::Continue => None,
+        })
+    }
+
+    fn exec(&self, statement: &'a Statement<'a>, scope: &Rc<Scope<'a>>) -> Eval<'a, Flow<'a>> {
+        self.step()?;
+
+        Ok(match statement {
+            Statement::ExpressionStatement(statement) => {
+                self.eval(&statement.expression, scope)?;
+                Flow::Normal
+            }
+            Statement::VariableDeclaration(declaration) => {
+                self.exec_declaration(declaration, scope)?;
+                Flow::Normal
+            }
+            // Hoisted when the function was called.
+            Statement::FunctionDeclaration(_) | Statement::EmptyStatement(_) => Flow::Normal,
+            Statement::ReturnStatement(statement) => Flow::Return(match &statement.argument {
+                Some(argument) => self.eval(argument, scope)?,
+                None => Value::Undefined,
+            }),
+            Statement::BlockStatement(block) => self.exec_all(&block.body, scope)?,
+            Statement::IfStatement(statement) => {
+                match self.eval(&statement.test, scope)?.to_bool() {
+                    true => self.exec(&statement.consequent, scope)?,
+                    false => match &statement.alternate {
+                        Some(alternate) => self.exec(alternate, scope)?,
+                        None => Flow::Normal,
+                    },
+                }
+            }
+            Statement::ForStatement(statement) => {
+                match &statement.init {
+                    Some(ForStatementInit::VariableDeclaration(declaration)) => {
+                        self.exec_declaration(declaration, scope)?
+                    }
+                    Some(init) => {
+                        self.eval(init.to_expression(), scope)?;
+                    }
+                    None => {}
+                }
+
+                loop {
+                    if let Some(test) = &statement.test
+                        && !self.eval(test, scope)?.to_bool()
+                    {
+                        break Flow::Normal;
+                    }
+                    if let Some(flow) = self.exec_loop_body(&statement.body, scope)? {
+                        break flow;
+                    }
+                    if let Some(update) = &statement.update {
+                        self.eval(update, scope)?;
+                    }
+                }
+            }
+            Statement::WhileStatement(statement) => loop {
+                if !self.eval(&statement.test, scope)?.to_bool() {
+                    break Flow::Normal;
+                }
+                if let Some(flow) = self.exec_loop_body(&statement.body, scope)? {
+                    break flow;
+                }
+            },
+            Statement::DoWhileStatement(statement) => loop {
+                if let Some(flow) = self.exec_loop_body(&statement.body, scope)? {
+                    break flow;
+                }
+                if !self.eval(&statement.test, scope)?.to_bool() {
+                    break Flow::Normal;
+                }
+            },
+            Statement::BreakStatement(statement) if statement.label.is_none() => Flow::Break,
+            Statement::ContinueStatement(statement) if statement.label.is_none() => Flow::Continue,
+            Statement::SwitchStatement(statement) => {
+                let discriminant = self.eval(&statement.discriminant, scope)?;
+
+                let mut matched = None;
+                for (i, case) in statement.cases.iter().enumerate() {
+                    if let Some(test) = &case.test
+                        && self.eval(test, scope)?.strict_equals(&discriminant)
+                    {
+                        matched = Some(i);
+                        break;
+                    }
+                }
+                let start =
+                    matched.or_else(|| statement.cases.iter().position(|c| c.test.is_none()));
+
+                let mut flow = Flow::Normal;
+                if let Some(start) = start {
+                    for case in &statement.cases[start..] {
+                        match self.exec_all(&case.consequent, scope)? {
+                            Flow::Normal => {}
+                            Flow::Break => break,
+                            other => {
+                                flow = other;
+                                break;
+                            }
+                        }
+                    }
+                }
+                flow
+            }
+            Statement::TryStatement(statement) => {
+                let flow = match (
+                    self.exec_all(&statement.block.body, scope),
+                    &statement.handler,
+                ) {
+                    (Err(Abort::Throw(thrown)), Some(handler)) => {
+                        if let Some(param) = &handler.param {
+                            let Some(name) = param.pattern.get_identifier_name() else {
+                                return Err(Abort::unsupported("destructured catch parameters"));
+                            };
+                            scope.declare(name.as_str(), thrown);
+                        }
+                        self.exec_all(&handler.body.body, scope)
+                    }
+                    (result, _) => result,
+                };
+
+                match &statement.finalizer {
+                    Some(finalizer) => match self.exec_all(&finalizer.body, scope)? {
+                        Flow::Normal => flow?,
+                        finalizer_flow => finalizer_flow,
+                    },
+                    None => flow?,
+                }
+            }
+            Statement::ThrowStatement(statement) => {
+                return Err(Abort::Throw(self.eval(&statement.argument, scope)?));
+            }
+            _ => return Err(Abort::unsupported("a statement kind")),
+        })
+    }
+
+    fn exec_declaration(
+        &self,
+        declaration: &'a VariableDeclaration<'a>,
+        scope: &Rc<Scope<'a>>,
+    ) -> Eval<'a, ()> {
+        for declarator in &declaration.declarations {
+            let Some(name) = declarator.id.get_identifier_name() else {
+                return Err(Abort::unsupported("destructuring declarations"));
+            };
+
+            match &declarator.init {
+                Some(init) => {
+                    let value = self.eval(init, scope)?;
+                    scope.declare(name.as_str(), value);
+                }
+                None if !scope.vars.borrow().contains_key(name.as_str()) => {
+                    scope.declare(name.as_str(), Value::Undefined)
+                }
+                None => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn eval_args(
+        &self,
+        args: &'a [Argument<'a>],
+        scope: &Rc<Scope<'a>>,
+    ) -> Eval<'a, Vec<Value<'a>>> {
+        args.iter()
+            .map(|arg| match arg {
+                Argument::SpreadElement(_) => Err(Abort::unsupported("spread arguments")),
+                arg => self.eval(arg.to_expression(), scope),
+            })
+            .collect()
+    }
+
+    fn property_key(&self, key: &'a PropertyKey<'a>, scope: &Rc<Scope<'a>>) -> Eval<'a, String> {
+        match key {
+            PropertyKey::StaticIdentifier(id) => Ok(id.name.to_string()),
+            PropertyKey::PrivateIdentifier(_) => Err(Abort::unsupported("private fields")),
+            key => self.eval(key.to_expression(), scope)?.to_js_string(),
+        }
+    }
+
+    fn eval(&self, expression: &'a Expression<'a>, scope: &Rc<Scope<'a>>) -> Eval<'a, Value<'a>> {
+        self.step()?;
+
+        Ok(match expression {
+            Expression::BooleanLiteral(literal) => Value::Bool(literal.value),
+            Expression::NullLiteral(_) => Value::Null,
+            Expression::NumericLiteral(literal) => Value::Number(literal.value),
+            Expression::StringLiteral(literal) => Value::string(&literal.value),
+            Expression::TemplateLiteral(template) => {
+                let mut s = String::new();
+                for (i, quasi) in template.quasis.iter().enumerate() {
+                    let Some(cooked) = &quasi.value.cooked else {
+                        return Err(Abort::unsupported("invalid template escapes"));
+                    };
+                    s.push_str(cooked);
+                    if let Some(expression) = template.expressions.get(i) {
+                        s.push_str(&self.eval(expression, scope)?.to_js_string()?);
+                    }
+                }
+                Value::String(Rc::from(s))
+            }
+            Expression::Identifier(id) => self.get_var(id.name.as_str(), scope)?,
+            Expression::ThisExpression(_) => scope.get("this").unwrap_or(Value::Undefined),
+            Expression::ArrayExpression(array) => {
+                let mut values = Vec::with_capacity(array.elements.len());
+                for element in &array.elements {
+                    values.push(match element {
+                        ArrayExpressionElement::SpreadElement(_) => {
+                            return Err(Abort::unsupported("spread elements"));
+                        }
+                        ArrayExpressionElement::Elision(_) => Value::Undefined,
+                        element => self.eval(element.to_expression(), scope)?,
+                    });
+                }
+                Value::array(values)
+            }
+            Expression::ObjectExpression(object) => {
+                let mut properties = HashMap::new();
+                for property in &object.properties {
+                    let ObjectPropertyKind::ObjectProperty(property) = property else {
+                        return Err(Abort::unsupported("spread properties"));
+                    };
+                    let key = self.property_key(&property.key, scope)?;
+                    properties.insert(key, self.eval(&property.value, scope)?);
+                }
+                Value::Object(Rc::new(RefCell::new(properties)))
+            }
+            Expression::FunctionExpression(function) => {
+                let Some(body) = &function.body else {
+                    return Err(Abort::unsupported("functions without a body"));
+                };
+                Value::Function(Rc::new(Closure {
+                    params: &function.params,
+                    body,
+                    is_expression: false,
+                    is_arrow: false,
+                    scope: scope.clone(),
+                }))
+            }
+            Expression::ArrowFunctionExpression(function) => Value::Function(Rc::new(Closure {
+                params: &function.params,
+                body: &function.body,
+                is_expression: function.expression,
+                is_arrow: true,
+                scope: scope.clone(),
+            })),
+            Expression::StaticMemberExpression(member) => {
+                let object = self.eval(&member.object, scope)?;
+                self.get_property(&object, &member.property.name)?
+            }
+            Expression::ComputedMemberExpression(member) => {
+                let object = self.eval(&member.object, scope)?;
+                let key = self.eval(&member.expression, scope)?.to_js_string()?;
+                self.get_property(&object, &key)?
+            }
+            Expression::CallExpression(call) => match call.callee.without_parentheses() {
+                Expression::StaticMemberExpression(member) => {
+                    let object = self.eval(&member.object, scope)?;
+                    let args = self.eval_args(&call.arguments, scope)?;
+                    self.call_method(&object, &member.property.name, args)?
+                }
+                Expression::ComputedMemberExpression(member) => {
+                    let object = self.eval(&member.object, scope)?;
+                    let key = self.eval(&member.expression, scope)?.to_js_string()?;
+                    let args = self.eval_args(&call.arguments, scope)?;
+                    self.call_method(&object, &key, args)?
+                }
+                callee => {
+                    let function = self.eval(callee, scope)?;
+                    let args = self.eval_args(&call.arguments, scope)?;
+                    self.call(&function, Value::Undefined, args)?
+                }
+            },
+            Expression::UnaryExpression(unary) => {
+                if unary.operator == UnaryOperator::Typeof
+                    && let Expression::Identifier(id) = &unary.argument
+                {
+                    // `typeof` doesn't throw for undeclared variables.
+                    return Ok(Value::string(match self.get_var(id.name.as_str(), scope) {
+                        Ok(value) => value.type_of(),
+                        Err(_) => "undefined",
+                    }));
+                }
+
+                let value = self.eval(&unary.argument, scope)?;
+                match unary.operator {
+                    UnaryOperator::UnaryPlus => Value::Number(value.to_number()?),
+                    UnaryOperator::UnaryNegation => Value::Number(-value.to_number()?),
+                    UnaryOperator::LogicalNot => Value::Bool(!value.to_bool()),
+                    UnaryOperator::BitwiseNot => {
+                        Value::Number(!to_int32(value.to_number()?) as f64)
+                    }
+                    UnaryOperator::Typeof => Value::string(value.type_of()),
+                    UnaryOperator::Void => Value::Undefined,
+                    UnaryOperator::Delete => return Err(Abort::unsupported("`delete`")),
+                }
+            }
+            Expression::BinaryExpression(binary) => {
+                let left = self.eval(&binary.left, scope)?;
+                let right = self.eval(&binary.right, scope)?;
+                self.binary(binary.operator, &left, &right)?
+            }
+            Expression::LogicalExpression(logical) => {
+                let left = self.eval(&logical.left, scope)?;
+                let short_circuits = match logical.operator {
+                    LogicalOperator::Or => left.to_bool(),
+                    LogicalOperator::And => !left.to_bool(),
+                    LogicalOperator::Coalesce => !matches!(left, Value::Undefined | Value::Null),
+                };
+                match short_circuits {
+                    true => left,
+                    false => self.eval(&logical.right, scope)?,
+                }
+            }
+            Expression::ConditionalExpression(conditional) => {
+                match self.eval(&conditional.test, scope)?.to_bool() {
+                    true => self.eval(&conditional.consequent, scope)?,
+                    false => self.eval(&conditional.alternate, scope)?,
+                }
+            }
+            Expression::AssignmentExpression(assignment) => self.assign(
+                &assignment.left,
+                assignment.operator,
+                &assignment.right,
+                scope,
+            )?,
+            Expression::UpdateExpression(update) => {
+                let target = self.simple_target(&update.argument, scope)?;
+                let old = self.read_target(&target, scope)?.to_number()?;
+                let new = match update.operator {
+                    UpdateOperator::Increment => old + 1.0,
+                    UpdateOperator::Decrement => old - 1.0,
+                };
+                self.write_target(&target, Value::Number(new), scope)?;
+                Value::Number(match update.prefix {
+                    true => new,
+                    false => old,
+                })
+            }
+            Expression::SequenceExpression(sequence) => {
+                let mut last = Value::Undefined;
+                for expression in &sequence.expressions {
+                    last = self.eval(expression, scope)?;
+                }
+                last
+            }
+            Expression::ParenthesizedExpression(parenthesized) => {
+                self.eval(&parenthesized.expression, scope)?
+            }
+            _ => return Err(Abort::unsupported("an expression kind")),
+        })
+    }
+
+    fn binary(
+        &self,
+        operator: BinaryOperator,
+        left: &Value<'a>,
+        right: &Value<'a>,
+    ) -> Eval<'a, Value<'a>> {
+        let numbers = || -> Eval<'a, (f64, f64)> { Ok((left.to_number()?, right.to_number()?)) };
+        let ints = || -> Eval<'a, (i32, i32)> {
+            Ok((to_int32(left.to_number()?), to_int32(right.to_number()?)))
+        };
+
+        Ok(match operator {
+            BinaryOperator::Addition => {
+                let (left, right) = (left.to_primitive()?, right.to_primitive()?);
+                match (&left, &right) {
+                    (Value::String(_), _) | (_, Value::String(_)) => {
+                        Value::String(Rc::from(left.to_js_string()? + &right.to_js_string()?))
+                    }
+                    _ => Value::Number(left.to_number()? + right.to_number()?),
+                }
+            }
+            BinaryOperator::Subtraction => {
+                let (l, r) = numbers()?;
+                Value::Number(l - r)
+            }
+            BinaryOperator::Multiplication => {
+                let (l, r) = numbers()?;
+                Value::Number(l * r)
+            }
+            BinaryOperator::Division => {
+                let (l, r) = numbers()?;
+                Value::Number(l / r)
+            }
+            BinaryOperator::Remainder => {
+                let (l, r) = numbers()?;
+                Value::Number(l % r)
+            }
+            BinaryOperator::Exponential => {
+                let (l, r) = numbers()?;
+                Value::Number(l.powf(r))
+            }
+            BinaryOperator::ShiftLeft => {
+                let (l, r) = ints()?;
+                Value::Number(l.wrapping_shl(r as u32 & 31) as f64)
+            }
+            BinaryOperator::ShiftRight => {
+                let (l, r) = ints()?;
+                Value::Number(l.wrapping_shr(r as u32 & 31) as f64)
+            }
+            BinaryOperator::ShiftRightZeroFill => {
+                let (l, r) = ints()?;
+                Value::Number((l as u32).wrapping_shr(r as u32 & 31) as f64)
+            }
+            BinaryOperator::BitwiseAnd => {
+                let (l, r) = ints()?;
+                Value::Number((l & r) as f64)
+            }
+            BinaryOperator::BitwiseOR => {
+                let (l, r) = ints()?;
+                Value::Number((l | r) as f64)
+            }
+            BinaryOperator::BitwiseXOR => {
+                let (l, r) = ints()?;
+                Value::Number((l ^ r) as f64)
+            }
+            BinaryOperator::StrictEquality => Value::Bool(left.strict_equals(right)),
+            BinaryOperator::StrictInequality => Value::Bool(!left.strict_equals(right)),
+            BinaryOperator::Equality => Value::Bool(left.loose_equals(right)?),
+            BinaryOperator::Inequality => Value::Bool(!left.loose_equals(right)?),
+            BinaryOperator::LessThan
+            | BinaryOperator::LessEqualThan
+            | BinaryOperator::GreaterThan
+            | BinaryOperator::GreaterEqualThan => {
+                let (left, right) = (left.to_primitive()?, right.to_primitive()?);
+                let ordering = match (&left, &right) {
+                    (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+                    _ => left.to_number()?.partial_cmp(&right.to_number()?),
+                };
+                Value::Bool(ordering.is_some_and(|ordering| match operator {
+                    BinaryOperator::LessThan => ordering.is_lt(),
+                    BinaryOperator::LessEqualThan => ordering.is_le(),
+                    BinaryOperator::GreaterThan => ordering.is_gt(),
+                    _ => ordering.is_ge(),
+                }))
+            }
+            BinaryOperator::In | BinaryOperator::Instanceof => {
+                return Err(Abort::unsupported("`in` and `instanceof`"));
+            }
+        })
+    }
+
+    fn assign(
+        &self,
+        left: &'a AssignmentTarget<'a>,
+        operator: AssignmentOperator,
+        right: &'a Expression<'a>,
+        scope: &Rc<Scope<'a>>,
+    ) -> Eval<'a, Value<'a>> {
+        let Some(target) = left.as_simple_assignment_target() else {
+            return Err(Abort::unsupported("destructuring assignments"));
+        };
+        let target = self.simple_target(target, scope)?;
+
+        let value = match operator {
+            AssignmentOperator::Assign => self.eval(right, scope)?,
+            AssignmentOperator::LogicalOr
+            | AssignmentOperator::LogicalAnd
+            | AssignmentOperator::LogicalNullish => {
+                let current = self.read_target(&target, scope)?;
+                let short_circuits = match operator {
+                    AssignmentOperator::LogicalOr => current.to_bool(),
+                    AssignmentOperator::LogicalAnd => !current.to_bool(),
+                    _ => !matches!(current, Value::Undefined | Value::Null),
+                };
+                if short_circuits {
+                    return Ok(current);
+                }
+                self.eval(right, scope)?
+            }
+            operator => {
+                let current = self.read_target(&target, scope)?;
+                let right = self.eval(right, scope)?;
+                let Some(binary) = operator.to_binary_operator() else {
+                    return Err(Abort::unsupported("an assignment operator"));
+                };
+                self.binary(binary, &current, &right)?
+            }
+        };
+
+        self.write_target(&target, value.clone(), scope)?;
+        Ok(value)
+    }
+
+    fn simple_target(
+        &self,
+        target: &'a SimpleAssignmentTarget<'a>,
+        scope: &Rc<Scope<'a>>,
+    ) -> Eval<'a, Target<'a>> {
+        Ok(match target {
+            SimpleAssignmentTarget::AssignmentTargetIdentifier(id) => Target::Var(id.name.as_str()),
+            SimpleAssignmentTarget::StaticMemberExpression(member) => Target::Property(
+                self.eval(&member.object, scope)?,
+                member.property.name.to_string(),
+            ),
+            SimpleAssignmentTarget::ComputedMemberExpression(member) => {
+                let object = self.eval(&member.object, scope)?;
+                let key = self.eval(&member.expression, scope)?.to_js_string()?;
+                Target::Property(object, key)
+            }
+            _ => return Err(Abort::unsupported("an assignment target")),
+        })
+    }
+
+    fn read_target(&self, target: &Target<'a>, scope: &Rc<Scope<'a>>) -> Eval<'a, Value<'a>> {
+        match target {
+            Target::Var(name) => self.get_var(name, scope),
+            Target::Property(object, key) => self.get_property(object, key),
+        }
+    }
+
+    fn write_target(
+        &self,
+        target: &Target<'a>,
+        value: Value<'a>,
+        scope: &Rc<Scope<'a>>,
+    ) -> Eval<'a, ()> {
+        match target {
+            Target::Var(name) => {
+                self.set_var(name, value, scope);
+                Ok(())
+            }
+            Target::Property(object, key) => self.set_property(object, key, value),
+        }
+    }
+
+    fn get_property(&self, object: &Value<'a>, key: &str) -> Eval<'a, Value<'a>> {
+        let index = key.parse::<usize>().ok();
+
+        Ok(match object {
+            Value::Array(values) => match (key, index) {
+                ("length", _) => Value::Number(values.borrow().len() as f64),
+                (_, Some(index)) => values
+                    .borrow()
+                    .get(index)
+                    .cloned()
+                    .unwrap_or(Value::Undefined),
+                _ => return Err(Abort::Unsupported(format!("the array property `{}`", key))),
+            },
+            Value::String(s) => match (key, index) {
+                ("length", _) => Value::Number(s.chars().count() as f64),
+                (_, Some(index)) => s
+                    .chars()
+                    .nth(index)
+                    .map_or(Value::Undefined, |c| Value::String(Rc::from(c.to_string()))),
+                _ => return Err(Abort::Unsupported(format!("the string property `{}`", key))),
+            },
+            Value::Object(properties) => properties
+                .borrow()
+                .get(key)
+                .cloned()
+                .unwrap_or(Value::Undefined),
+            Value::Builtin(Builtin::Math) => match key {
+                "PI" => Value::Number(std::f64::consts::PI),
+                "floor" | "ceil" | "round" | "abs" | "pow" | "min" | "max" => {
+                    Value::Builtin(Builtin::MathFunction(match key {
+                        "floor" => "floor",
+                        "ceil" => "ceil",
+                        "round" => "round",
+                        "abs" => "abs",
+                        "pow" => "pow",
+                        "min" => "min",
+                        _ => "max",
+                    }))
+                }
+                _ => return Err(Abort::Unsupported(format!("`Math.{}`", key))),
+            },
+            Value::Builtin(Builtin::String) if key == "fromCharCode" => {
+                Value::Builtin(Builtin::FromCharCode)
+            }
+            Value::Undefined | Value::Null => {
+                return Err(Abort::type_error("cannot read properties of undefined"));
+            }
+            _ => return Err(Abort::Unsupported(format!("the property `{}`", key))),
+        })
+    }
+
+    fn set_property(&self, object: &Value<'a>, key: &str, value: Value<'a>) -> Eval<'a, ()> {
+        match object {
+            Value::Array(values) => {
+                let mut values = values.borrow_mut();
+                if key == "length" {
+                    let len = value.to_number()? as usize;
+                    values.resize(len, Value::Undefined);
+                } else if let Ok(index) = key.parse::<usize>() {
+                    if index >= values.len() {
+                        values.resize(index + 1, Value::Undefined);
+                    }
+                    values[index] = value;
+                } else {
+                    return Err(Abort::Unsupported(format!(
+                        "setting the array property `{}`",
+                        key
+                    )));
+                }
+            }
+            Value::Object(properties) => {
+                properties.borrow_mut().insert(key.into(), value);
+            }
+            Value::Undefined | Value::Null => {
+                return Err(Abort::type_error("cannot set properties of undefined"));
+            }
+            // Writes to primitives are silently ignored.
+            Value::Bool(_) | Value::Number(_) | Value::String(_) => {}
+            _ => {
+                return Err(Abort::Unsupported(format!(
+                    "setting the property `{}`",
+                    key
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn call_method(
+        &self,
+        object: &Value<'a>,
+        name: &str,
+        args: Vec<Value<'a>>,
+    ) -> Eval<'a, Value<'a>> {
+        let arg = |i: usize| args.get(i).cloned().unwrap_or(Value::Undefined);
+
+        match object {
+            // Functions stored in an array are called like `c[0](...)`.
+            Value::Array(values) if name.parse::<usize>().is_err() => {
+                self.call_array_method(object, values, name, args)
+            }
+            Value::String(s) => {
+                let chars: Vec<char> = s.chars().collect();
+                let substring = |start: usize, end: usize| {
+                    Value::String(Rc::from(
+                        chars[start..end.max(start)].iter().collect::<String>(),
+                    ))
+                };
+
+                Ok(match name {
+                    "split" => {
+                        let parts: Vec<Value<'a>> = match arg(0) {
+                            Value::Undefined => vec![object.clone()],
+                            separator => {
+                                let separator = separator.to_js_string()?;
+                                match separator.is_empty() {
+                                    true => chars
+                                        .iter()
+                                        .map(|c| Value::String(Rc::from(c.to_string())))
+                                        .collect(),
+                                    false => {
+                                        s.split(separator.as_str()).map(Value::string).collect()
+                                    }
+                                }
+                            }
+                        };
+                        Value::array(parts)
+                    }
+                    "charAt" => {
+                        let index = arg(0).to_number()?;
+                        match index >= 0.0 && (index as usize) < chars.len() {
+                            true => substring(index as usize, index as usize + 1),
+                            false => Value::string(""),
+                        }
+                    }
+                    "charCodeAt" => {
+                        let index = match arg(0) {
+                            Value::Undefined => 0.0,
+                            index => index.to_number()?,
+                        };
+                        match index >= 0.0 && (index as usize) < chars.len() {
+                            true => Value::Number(chars[index as usize] as u32 as f64),
+                            false => Value::Number(f64::NAN),
+                        }
+                    }
+                    "indexOf" => {
+                        let needle = arg(0).to_js_string()?;
+                        Value::Number(match s.find(needle.as_str()) {
+                            Some(byte_index) => s[..byte_index].chars().count() as f64,
+                            None => -1.0,
+                        })
+                    }
+                    "slice" | "substring" => {
+                        let start = arg(0).to_number()?;
+                        let end = match arg(1) {
+                            Value::Undefined => chars.len() as f64,
+                            end => end.to_number()?,
+                        };
+                        match name == "slice" {
+                            true => substring(
+                                relative_index(start, chars.len()),
+                                relative_index(end, chars.len()),
+                            ),
+                            false => {
+                                let clamp = |n: f64| {
+                                    if n.is_nan() {
+                                        0
+                                    } else {
+                                        n.clamp(0.0, chars.len() as f64) as usize
+                                    }
+                                };
+                                let (start, end) = (clamp(start), clamp(end));
+                                substring(start.min(end), start.max(end))
+                            }
+                        }
+                    }
+                    "concat" => {
+                        let mut concatenated = s.to_string();
+                        for arg in &args {
+                            concatenated.push_str(&arg.to_js_string()?);
+                        }
+                        Value::String(Rc::from(concatenated))
+                    }
+                    "toString" => object.clone(),
+                    _ => return Err(Abort::Unsupported(format!("the string method `{}`", name))),
+                })
+            }
+            Value::Function(_) if name == "call" => {
+                let mut args = args.into_iter();
+                let this = args.next().unwrap_or(Value::Undefined);
+                self.call(object, this, args.collect())
+            }
+            Value::Function(_) if name == "apply" => {
+                let args = match arg(1) {
+                    Value::Array(values) => values.borrow().clone(),
+                    _ => vec![],
+                };
+                self.call(object, arg(0), args)
+            }
+            object => {
+                let method = self.get_property(object, name)?;
+                self.call(&method, object.clone(), args)
+            }
+        }
+    }
+
+    fn call_array_method(
+        &self,
+        object: &Value<'a>,
+        values: &Rc<RefCell<Vec<Value<'a>>>>,
+        name: &str,
+        args: Vec<Value<'a>>,
+    ) -> Eval<'a, Value<'a>> {
+        let arg = |i: usize| args.get(i).cloned().unwrap_or(Value::Undefined);
+
+        Ok(match name {
+            "push" => {
+                let mut values = values.borrow_mut();
+                values.extend(args);
+                Value::Number(values.len() as f64)
+            }
+            "unshift" => {
+                let mut values = values.borrow_mut();
+                values.splice(0..0, args);
+                Value::Number(values.len() as f64)
+            }
+            "pop" => values.borrow_mut().pop().unwrap_or(Value::Undefined),
+            "shift" => {
+                let mut values = values.borrow_mut();
+                match values.is_empty() {
+                    true => Value::Undefined,
+                    false => values.remove(0),
+                }
+            }
+            "splice" => {
+                let mut values = values.borrow_mut();
+                let len = values.len();
+                let start = relative_index(arg(0).to_number()?, len);
+                let delete_count = match args.len() {
+                    0 => 0,
+                    1 => len - start,
+                    _ => {
+                        let count = arg(1).to_number()?;
+                        let count = if count.is_nan() { 0.0 } else { count.trunc() };
+                        count.clamp(0.0, (len - start) as f64) as usize
+                    }
+                };
+                let inserted = args.into_iter().skip(2);
+                Value::array(
+                    values
+                        .splice(start..start + delete_count, inserted)
+                        .collect(),
+                )
+            }
+            "reverse" => {
+                values.borrow_mut().reverse();
+                object.clone()
+            }
+            "slice" => {
+                let values = values.borrow();
+                let len = values.len();
+                let start = relative_index(arg(0).to_number()?, len);
+                let end = match arg(1) {
+                    Value::Undefined => len,
+                    end => relative_index(end.to_number()?, len),
+                };
+                Value::array(values[start..end.max(start)].to_vec())
+            }
+            "join" => {
+                let separator = match arg(0) {
+                    Value::Undefined => ",".to_string(),
+                    separator => separator.to_js_string()?,
+                };
+                let values = values.borrow();
+                let mut parts = Vec::with_capacity(values.len());
+                for value in values.iter() {
+                    parts.push(match value {
+                        Value::Undefined | Value::Null => String::new(),
+                        value => value.to_js_string()?,
+                    });
+                }
+                Value::String(Rc::from(parts.join(&separator)))
+            }
+            "indexOf" => {
+                let needle = arg(0);
+                Value::Number(
+                    values
+                        .borrow()
+                        .iter()
+                        .position(|value| value.strict_equals(&needle))
+                        .map_or(-1.0, |i| i as f64),
+                )
+            }
+            "concat" => {
+                let mut concatenated = values.borrow().clone();
+                for arg in args {
+                    match arg {
+                        Value::Array(other) => concatenated.extend(other.borrow().iter().cloned()),
+                        value => concatenated.push(value),
+                    }
+                }
+                Value::array(concatenated)
+            }
+            "forEach" | "map" => {
+                let (callback, this) = (arg(0), arg(1));
+                // The callback may change the array, so it's read again on every iteration like JS does.
+                let mut mapped = vec![];
+                let mut i = 0;
+                while i < values.borrow().len() {
+                    let value = values.borrow()[i].clone();
+                    mapped.push(self.call(
+                        &callback,
+                        this.clone(),
+                        vec![value, Value::Number(i as f64), object.clone()],
+                    )?);
+                    i += 1;
+                }
+                match name == "map" {
+                    true => Value::array(mapped),
+                    false => Value::Undefined,
+                }
+            }
+            _ => return Err(Abort::Unsupported(format!("the array method `{}`", name))),
+        })
+    }
+}
+
+enum Target<'a> {
+    Var(&'a str),
+    Property(Value<'a>, String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(source: &str, challenge: &str) -> Result<String> {
+        let challenge = challenge.to_string();
+        run_n_program(source, std::slice::from_ref(&challenge))?
+            .remove(&challenge)
+            .unwrap()
+    }
+
+    #[test]
+    fn runs_n_function() {
+        let source = r#"var T=["split","join"];
+var __tydle_n=function(a){var b=a[T[0]](""),c=[function(d){d.reverse()},b];
+try{c[0](c[1]);b.splice(0,1)}catch(d){return"enhanced_except_"+a}return b[T[1]]("")};"#;
+
+        assert_eq!(run(source, "abcdef").unwrap(), "edcba");
+    }
+
+    #[test]
+    fn rejects_caught_errors() {
+        let source =
+            r#"var __tydle_n=function(a){try{a.x.y}catch(d){return"enhanced_except_"+a}return a};"#;
+
+        assert!(run(source, "abcdef").is_err());
+    }
+
+    #[test]
+    fn fails_on_unsupported_construct() {
+        let source = r#"var __tydle_n=function(a){var b=new Date(1);return a+b};"#;
+
+        assert!(run(source, "abcdef").is_err());
+    }
+}
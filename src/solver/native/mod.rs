@@ -0,0 +1,235 @@
+//! Solves challenges without a JavaScript engine, from a static analysis of the player.
+//!
+//! The signature function is reduced to its reverse, splice and swap operations, and the n-function
+//! is extracted with the declarations it depends on to run on a small interpreter. Players that
+//! can't be analysed, or challenges the interpreter can't run, go to the wrapped JS backend.
+
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::{Result, bail};
+
+use crate::{
+    cache::{CacheAccess, CacheStore},
+    solver::{ChallengeSolver, SignatureType, Solutions, SolveFut, SolveRequest},
+};
+
+mod analysis;
+mod interp;
+
+use analysis::{analyse_player, decipher_signature};
+use interp::run_n_program;
+
+pub(crate) struct NativeSolver {
+    /// Holds the transforms extracted from each player, an empty one if the analysis failed.
+    player_cache: Arc<CacheStore<(String, String)>>,
+    fallback: Option<Box<dyn ChallengeSolver>>,
+}
+
+impl NativeSolver {
+    pub(crate) fn new(
+        player_cache: Arc<CacheStore<(String, String)>>,
+        fallback: Option<Box<dyn ChallengeSolver>>,
+    ) -> Self {
+        Self {
+            player_cache,
+            fallback,
+        }
+    }
+
+    fn cache_id(player_key: &str, signature_type: SignatureType) -> (String, String) {
+        (
+            format!("native-{}", signature_type.as_str()),
+            player_key.to_string(),
+        )
+    }
+
+    /// Gets the transform of the player for a challenge type, analysing the player if it's new.
+    fn get_transform(
+        player_cache: &CacheStore<(String, String)>,
+        request: &SolveRequest,
+        signature_type: SignatureType,
+    ) -> Result<String> {
+        let cache_id = Self::cache_id(&request.player_key, signature_type);
+        if let Some(transform) = player_cache.get(&cache_id)? {
+            return Ok(transform);
+        }
+
+        let transforms = analyse_player(&request.player_code);
+        for (transform_type, transform) in [
+            (SignatureType::Signature, transforms.signature),
+            (SignatureType::Nsignature, transforms.nsig),
+        ] {
+            player_cache.add(
+                Self::cache_id(&request.player_key, transform_type),
+                transform.unwrap_or_default(),
+            )?;
+        }
+
+        Ok(player_cache.get(&cache_id)?.unwrap_or_default())
+    }
+
+    /// Solves the challenges of a type, each with its own result.
+    fn solve_natively(
+        player_cache: &CacheStore<(String, String)>,
+        request: &SolveRequest,
+        signature_type: SignatureType,
+        challenges: &[String],
+    ) -> Result<HashMap<String, Result<String>>> {
+        let transform = Self::get_transform(player_cache, request, signature_type)?;
+        if transform.is_empty() {
+            bail!(
+                "Static analysis of player {} failed for {} challenges.",
+                request.player_key,
+                signature_type.as_str()
+            )
+        }
+
+        match signature_type {
+            SignatureType::Signature => Ok(challenges
+                .iter()
+                .map(|challenge| (challenge.clone(), decipher_signature(&transform, challenge)))
+                .collect()),
+            SignatureType::Nsignature => run_n_program(&transform, challenges),
+        }
+    }
+
+    /// Solves what it can of every request, returning the solutions with the indexes of the
+    /// requests and the challenges the JS backend has to solve.
+    fn solve_all(
+        player_cache: &CacheStore<(String, String)>,
+        request: &SolveRequest,
+    ) -> (Solutions, Vec<(usize, Vec<String>)>) {
+        let mut solutions = vec![HashMap::new(); request.challenges.len()];
+        let mut unsolved = vec![];
+
+        for (i, (signature_type, challenges)) in request.challenges.iter().enumerate() {
+            let failed: Vec<String> =
+                match Self::solve_natively(player_cache, request, *signature_type, challenges) {
+                    Ok(solved) => solved
+                        .into_iter()
+                        .filter_map(|(challenge, solution)| match solution {
+                            Ok(solution) => {
+                                solutions[i].insert(challenge, solution);
+                                None
+                            }
+                            Err(_err) => {
+                                #[cfg(feature = "logging")]
+                                log::warn!("Falling back to the JS solver: {}", _err);
+
+                                Some(challenge)
+                            }
+                        })
+                        .collect(),
+                    Err(_err) => {
+                        #[cfg(feature = "logging")]
+                        log::warn!("Falling back to the JS solver: {}", _err);
+
+                        challenges.clone()
+                    }
+                };
+
+            if !failed.is_empty() {
+                unsolved.push((i, failed));
+            }
+        }
+
+        (solutions, unsolved)
+    }
+}
+
+impl ChallengeSolver for NativeSolver {
+    fn solve<'a>(&'a self, request: SolveRequest) -> SolveFut<'a> {
+        Box::pin(async move {
+            let player_cache = self.player_cache.clone();
+
+            // Parsing and interpreting the player takes a while, so it stays off the async runtime.
+            #[cfg(not(target_arch = "wasm32"))]
+            let (request, (mut solutions, unsolved)) = tokio::task::spawn_blocking(move || {
+                let solved = Self::solve_all(&player_cache, &request);
+                (request, solved)
+            })
+            .await?;
+            #[cfg(target_arch = "wasm32")]
+            let (mut solutions, unsolved) = Self::solve_all(&player_cache, &request);
+
+            if unsolved.is_empty() {
+                return Ok(solutions);
+            }
+            let Some(fallback) = &self.fallback else {
+                bail!(
+                    "Some challenges couldn't be solved natively and no JS challenge solver is available, enable the `deno` or `quickjs` feature or pass one to `Tydle::with_solver`."
+                )
+            };
+
+            let challenges = unsolved
+                .iter()
+                .map(|(i, failed)| (request.challenges[*i].0, failed.clone()))
+                .collect();
+            let solved = fallback
+                .solve(SolveRequest {
+                    challenges,
+                    ..request
+                })
+                .await?;
+
+            for ((i, _), solved) in unsolved.into_iter().zip(solved) {
+                solutions[i].extend(solved);
+            }
+
+            Ok(solutions)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::SolverBundles;
+
+    /// Answers every challenge with a marker, failing if asked for a signature.
+    struct FallbackSolver;
+
+    impl ChallengeSolver for FallbackSolver {
+        fn solve<'a>(&'a self, request: SolveRequest) -> SolveFut<'a> {
+            Box::pin(async move {
+                request
+                    .challenges
+                    .iter()
+                    .map(|(signature_type, challenges)| match signature_type {
+                        SignatureType::Signature => bail!("Signatures are solved natively."),
+                        SignatureType::Nsignature => Ok(challenges
+                            .iter()
+                            .map(|challenge| (challenge.clone(), format!("js-{}", challenge)))
+                            .collect()),
+                    })
+                    .collect()
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn falls_back_on_unsupported_n_function() {
+        let player_code = r#"var _yt_player={};(function(g){var window=this;
+var Xy={Ab:function(a){a.reverse()},cD:function(a,b){a.splice(0,b)}};
+var sigf=function(a){a=a.split("");Xy.cD(a,1);Xy.Ab(a,3);return a.join("")};
+var nfn=function(a){var b=a.split("");try{b.push(new Date(1))}catch(d){return"enhanced_except_"+a}return b.join("")};
+g.sig=sigf;g.n=nfn;})(_yt_player);"#;
+        let solver = NativeSolver::new(Arc::new(CacheStore::new()), Some(Box::new(FallbackSolver)));
+
+        let solutions = solver
+            .solve(SolveRequest {
+                player_key: "player".into(),
+                player_code: player_code.into(),
+                bundles: SolverBundles::from_code(String::new(), String::new()),
+                challenges: vec![
+                    (SignatureType::Signature, vec!["abcdef".into()]),
+                    (SignatureType::Nsignature, vec!["4xMrV3wXlG5eKhtK".into()]),
+                ],
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(solutions[0]["abcdef"], "fedcb");
+        assert_eq!(solutions[1]["4xMrV3wXlG5eKhtK"], "js-4xMrV3wXlG5eKhtK");
+    }
+}
@@ -0,0 +1,50 @@
+use anyhow::anyhow;
+use rquickjs::{CatchResultExt, Context, Runtime};
+
+use crate::solver::{ChallengeSolver, Solutions, SolveFut, SolveRequest, parse_jsc_output};
+
+/// Solves challenges with an embedded QuickJS engine, a much smaller dependency than Deno.
+///
+/// Every request gets a fresh runtime, evaluated on tokio's blocking threads.
+pub struct QuickJsSolver;
+
+impl ChallengeSolver for QuickJsSolver {
+    fn solve<'a>(&'a self, request: SolveRequest) -> SolveFut<'a> {
+        Box::pin(async move {
+            let solver_env = request.solver_env().await?;
+
+            tokio::task::spawn_blocking(move || solve_blocking(solver_env, request)).await?
+        })
+    }
+}
+
+fn solve_blocking(solver_env: String, request: SolveRequest) -> anyhow::Result<Solutions> {
+    let runtime = Runtime::new()?;
+    let context = Context::full(&runtime)?;
+
+    context.with(|ctx| {
+        let js_error = |e: rquickjs::CaughtError| anyhow!("QuickJS failed to solve the challenges: {}", e);
+
+        ctx.eval::<(), _>(solver_env)
+            .catch(&ctx)
+            .map_err(js_error)?;
+
+        let globals = ctx.globals();
+        globals.set("__player", request.player_code.as_str())?;
+        globals.set(
+            "__requests",
+            ctx.json_parse(request.jsc_requests().to_string())
+                .catch(&ctx)
+                .map_err(js_error)?,
+        )?;
+
+        let output: String = ctx
+            .eval(
+                r#"JSON.stringify(jsc({"type": "player", "player": globalThis.__player, "requests": globalThis.__requests}))"#,
+            )
+            .catch(&ctx)
+            .map_err(js_error)?;
+
+        parse_jsc_output(&serde_json::from_str(&output)?)
+    })
+}
@@ -1,18 +1,33 @@
-use anyhow::{Result, anyhow};
+use anyhow::Result;
 use std::pin::Pin;
-use std::{
-    future::Future,
-    sync::{Arc, Mutex},
-};
+use std::{future::Future, sync::Arc};
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::wasm_bindgen;
 
 use crate::cache::CacheStore;
 use crate::cipher::decipher::{SignatureDecipher, SignatureDecipherHandle};
 use crate::cookies::DomainCookies;
-use crate::yt_interface::{YtManifest, YtStreamResponse, YtVideoInfo};
+use crate::format_sort::FormatSort;
+#[cfg(feature = "native-solver")]
+use crate::solver::NativeSolver;
+use crate::solver::{ChallengeSolver, default_solver};
+use crate::yt_interface::{
+    PlaylistId, YtCaptionTrack, YtChannel, YtChannelInfo, YtChannelTab, YtChannelTabOptions,
+    YtChannelTabResponse, YtClient, YtComment, YtCommentOptions, YtCommentsResponse, YtManifest,
+    YtPlaylist, YtPlaylistOptions, YtResolvedUrl, YtSearchFilters, YtSearchResponse,
+    YtSolverSource, YtStream, YtStreamResponse, YtSubtitle, YtSubtitleOptions, YtSubtitles,
+    YtVideoInfo,
+};
 use crate::{
-    extractor::extract::{InfoExtractor, YtExtractor},
+    extractor::{
+        channel::ExtractorChannelHandle,
+        comments::ExtractorCommentsHandle,
+        extract::{InfoExtractor, YtExtractor},
+        playlist::ExtractorPlaylistHandle,
+        resolve::ExtractorResolveHandle,
+        search::ExtractorSearchHandle,
+        subtitles::ExtractorSubtitlesHandle,
+    },
     yt_interface::VideoId,
 };
 
@@ -31,26 +46,66 @@ pub struct TydleOptions {
     pub prefer_insecure: bool,
     /// Provide an address to set it as the `X-Forwarded-For` header when requesting YouTube.
     pub source_address: String,
+    /// Don't download the HLS master playlists some clients return, skipping their pre-merged formats.
+    pub skip_hls: bool,
+    /// Don't download the DASH manifests returned for live streams.
+    pub skip_dash: bool,
+    /// Clients to prefer when several return the same format, most preferred first.
+    /// Uses the priority of each client when empty.
+    pub client_priority: Vec<YtClient>,
+    /// Keep the copy of a format every client returns instead of merging them into one.
+    pub keep_duplicate_formats: bool,
+    /// Where to load the ejs solver bundles used for signature deciphering from.
+    pub solver_source: YtSolverSource,
+    /// Always run the JS backend, instead of solving challenges from a static analysis of the player first.
+    pub skip_native_solver: bool,
 }
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
 pub struct Tydle {
-    yt_extractor: Arc<Mutex<YtExtractor>>,
-    signature_decipher: Arc<Mutex<SignatureDecipher>>,
+    yt_extractor: Arc<YtExtractor>,
+    signature_decipher: Arc<SignatureDecipher>,
 }
 
 impl Tydle {
     #[cfg(not(target_arch = "wasm32"))]
     pub fn new(options: TydleOptions) -> Result<Self> {
+        Self::build(options, default_solver())
+    }
+
+    /// Creates a `Tydle` deciphering signatures with the given JS backend instead of the default one.
+    pub fn with_solver(
+        options: TydleOptions,
+        solver: impl ChallengeSolver + 'static,
+    ) -> Result<Self> {
+        Self::build(options, Some(Box::new(solver)))
+    }
+
+    /// The default format sort, with `client` following `TydleOptions::client_priority` like the
+    /// merge of duplicate formats does.
+    pub fn format_sort(&self) -> FormatSort {
+        FormatSort::default()
+            .with_client_priority(self.yt_extractor.tydle_options.client_priority.clone())
+    }
+
+    fn build(options: TydleOptions, solver: Option<Box<dyn ChallengeSolver>>) -> Result<Self> {
         let player_cache = Arc::new(CacheStore::new());
         let code_cache = Arc::new(CacheStore::new());
+        let solver_source = options.solver_source.clone();
+
+        #[cfg(feature = "native-solver")]
+        let solver: Option<Box<dyn ChallengeSolver>> = match options.skip_native_solver {
+            true => solver,
+            false => Some(Box::new(NativeSolver::new(player_cache.clone(), solver))),
+        };
 
         let yt_extractor = YtExtractor::new(player_cache.clone(), code_cache.clone(), options)?;
-        let signature_decipher = SignatureDecipher::new(player_cache, code_cache);
+        let signature_decipher =
+            SignatureDecipher::new(player_cache, code_cache, solver_source, solver);
 
         Ok(Self {
-            yt_extractor: Arc::new(Mutex::new(yt_extractor)),
-            signature_decipher: Arc::new(Mutex::new(signature_decipher)),
+            yt_extractor: Arc::new(yt_extractor),
+            signature_decipher: Arc::new(signature_decipher),
         })
     }
 }
@@ -184,6 +239,321 @@ pub trait Extract {
     /// }
     /// ```
     fn get_streams<'a>(&'a self, video_id: &'a VideoId) -> Self::ExtractStreamFut<'a>;
+    /// Extract a playlist's metadata and its videos in playlist order.
+    ///
+    /// Use `YtPlaylistOptions` to only fetch a range of the playlist, continuation pages past the range are never requested.
+    ///
+    /// ```
+    /// use tydle::{Tydle, TydleOptions, Extract, PlaylistId, YtPlaylistOptions};
+    /// use anyhow::Result;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///   let ty = Tydle::new(TydleOptions{ ..Default::default() })?;
+    ///
+    ///   let playlist_id = PlaylistId::new("PLBCF2DAC6FFB574DE")?;
+    ///   let playlist = ty
+    ///     .get_playlist(&playlist_id, YtPlaylistOptions { limit: Some(10), ..Default::default() })
+    ///     .await?;
+    ///
+    ///   for entry in playlist.entries {
+    ///     println!("{}. {} ({})", entry.index, entry.title, entry.video_id);
+    ///   }
+    ///
+    ///   Ok(())
+    /// }
+    /// ```
+    fn get_playlist<'a>(
+        &'a self,
+        playlist_id: &'a PlaylistId,
+        options: YtPlaylistOptions,
+    ) -> Self::ExtractPlaylistFut<'a>;
+    /// Extract a channel's metadata, like its handle, avatar, banner and subscriber count.
+    ///
+    /// ```
+    /// use tydle::{Tydle, TydleOptions, Extract, YtChannel};
+    /// use anyhow::Result;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///   let ty = Tydle::new(TydleOptions{ ..Default::default() })?;
+    ///
+    ///   let channel = YtChannel::new("UCuAXFkgsw1L7xaCfnd5JJOw", None)?;
+    ///   let channel_info = ty.get_channel_info(&channel).await?;
+    ///
+    ///   println!("Channel: {:?}", channel_info);
+    ///   Ok(())
+    /// }
+    /// ```
+    fn get_channel_info<'a>(&'a self, channel: &'a YtChannel) -> Self::ExtractChannelInfoFut<'a>;
+    /// Extract the videos listed on a channel's Videos, Shorts or Live tab, newest first.
+    ///
+    /// The channel's metadata is returned alongside since it is part of the same response.
+    ///
+    /// ```
+    /// use tydle::{Tydle, TydleOptions, Extract, YtChannel, YtChannelTab, YtChannelTabOptions};
+    /// use anyhow::Result;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///   let ty = Tydle::new(TydleOptions{ ..Default::default() })?;
+    ///
+    ///   let channel = YtChannel::new("UCuAXFkgsw1L7xaCfnd5JJOw", None)?;
+    ///   let uploads = ty
+    ///     .get_channel_tab(&channel, YtChannelTab::Videos, YtChannelTabOptions { limit: Some(30) })
+    ///     .await?;
+    ///
+    ///   for entry in uploads.entries {
+    ///     println!("{} ({})", entry.title, entry.video_id);
+    ///   }
+    ///
+    ///   Ok(())
+    /// }
+    /// ```
+    fn get_channel_tab<'a>(
+        &'a self,
+        channel: &'a YtChannel,
+        tab: YtChannelTab,
+        options: YtChannelTabOptions,
+    ) -> Self::ExtractChannelTabFut<'a>;
+    /// Classify any YouTube URL as a video, playlist, channel or clip.
+    ///
+    /// `@handle`, `/c/name`, `/user/name` and `/clip/` URLs are resolved through InnerTube,
+    /// falling back to the channel page if InnerTube doesn't know the URL.
+    ///
+    /// ```
+    /// use tydle::{Tydle, TydleOptions, Extract, YtResolvedUrl};
+    /// use anyhow::Result;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///   let ty = Tydle::new(TydleOptions{ ..Default::default() })?;
+    ///
+    ///   match ty.resolve_url("https://www.youtube.com/@YouTube").await? {
+    ///     YtResolvedUrl::Channel(channel) => println!("Channel: {}", channel.get_id()),
+    ///     other => println!("Not a channel: {:?}", other),
+    ///   }
+    ///
+    ///   Ok(())
+    /// }
+    /// ```
+    fn resolve_url<'a>(&'a self, url: &'a str) -> Self::ExtractResolvedUrlFut<'a>;
+    /// Resolve a channel ID, `@handle`, `/c/name`, `/user/name` or `/channel/` URL to a `YtChannel` with its name.
+    ///
+    /// ```
+    /// use tydle::{Tydle, TydleOptions, Extract, YtChannel};
+    /// use anyhow::Result;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///   let ty = Tydle::new(TydleOptions{ ..Default::default() })?;
+    ///
+    ///   let channel: YtChannel = ty.resolve_channel("@YouTube").await?;
+    ///
+    ///   println!("Channel: {:?}", channel);
+    ///   Ok(())
+    /// }
+    /// ```
+    fn resolve_channel<'a>(&'a self, url: &'a str) -> Self::ExtractChannelFut<'a>;
+    /// Search YouTube and return the first page of videos, shorts, channels and playlists.
+    ///
+    /// Pass the page's `continuation` to `Tydle::search_continuation` to fetch more results.
+    ///
+    /// ```
+    /// use tydle::{Tydle, TydleOptions, Extract, YtSearchFilters, YtSearchSort, YtSearchType};
+    /// use anyhow::Result;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///   let ty = Tydle::new(TydleOptions{ ..Default::default() })?;
+    ///   let filters = YtSearchFilters {
+    ///     sort: YtSearchSort::ViewCount,
+    ///     result_type: Some(YtSearchType::Video),
+    ///     ..Default::default()
+    ///   };
+    ///
+    ///   let page = ty.search("rick astley", filters).await?;
+    ///
+    ///   println!("Results: {:?}", page.results);
+    ///   Ok(())
+    /// }
+    /// ```
+    fn search<'a>(&'a self, query: &'a str, filters: YtSearchFilters)
+    -> Self::ExtractSearchFut<'a>;
+    /// Fetch the next page of search results from a previous page's `continuation`.
+    ///
+    /// ```
+    /// use tydle::{Tydle, TydleOptions, Extract};
+    /// use anyhow::Result;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///   let ty = Tydle::new(TydleOptions{ ..Default::default() })?;
+    ///   let page = ty.search("rick astley", Default::default()).await?;
+    ///
+    ///   if let Some(continuation) = page.continuation {
+    ///     let next_page = ty.search_continuation(&continuation).await?;
+    ///     println!("More results: {:?}", next_page.results);
+    ///   }
+    ///
+    ///   Ok(())
+    /// }
+    /// ```
+    fn search_continuation<'a>(&'a self, continuation: &'a str) -> Self::ExtractSearchFut<'a>;
+    /// List the manual and auto-generated caption tracks of a video and the languages they can be translated into.
+    ///
+    /// If you already have a raw manifest fetched, use `Tydle::get_subtitles_from_manifest` instead to avoid refetching.
+    ///
+    /// ```
+    /// use tydle::{Tydle, TydleOptions, Extract, VideoId, YtSubtitles};
+    /// use anyhow::Result;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///   let ty = Tydle::new(TydleOptions{ ..Default::default() })?;
+    ///
+    ///   let video_id = VideoId::new("dQw4w9WgXcQ")?;
+    ///   let subtitles: YtSubtitles = ty.get_subtitles(&video_id).await?;
+    ///
+    ///   println!("Caption tracks: {:?}", subtitles.tracks);
+    ///   Ok(())
+    /// }
+    /// ```
+    fn get_subtitles<'a>(&'a self, video_id: &'a VideoId) -> Self::ExtractSubtitlesFut<'a>;
+    /// List the caption tracks from an already fetched manifest.
+    ///
+    /// If you do not require using the manifest directly, use `Tydle::get_subtitles` instead to fetch directly.
+    ///
+    /// ```
+    /// use tydle::{Tydle, TydleOptions, Extract, VideoId, YtSubtitles};
+    /// use anyhow::Result;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///   let ty = Tydle::new(TydleOptions{ ..Default::default() })?;
+    ///
+    ///   let video_id = VideoId::new("dQw4w9WgXcQ")?;
+    ///
+    ///   let manifest = ty.get_manifest(&video_id).await?;
+    ///   let subtitles: YtSubtitles = ty.get_subtitles_from_manifest(&manifest).await?;
+    ///
+    ///   println!("Caption tracks: {:?}", subtitles.tracks);
+    ///   Ok(())
+    /// }
+    /// ```
+    fn get_subtitles_from_manifest<'a>(
+        &'a self,
+        manifest: &'a YtManifest,
+    ) -> Self::ExtractSubtitlesFut<'a>;
+    /// Download a caption track, optionally machine-translated, as SRT, WebVTT, TTML, plain text or YouTube's own json3/srv3.
+    ///
+    /// ```
+    /// use tydle::{Tydle, TydleOptions, Extract, VideoId, YtSubtitleFormat, YtSubtitleOptions};
+    /// use anyhow::Result;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///   let ty = Tydle::new(TydleOptions{ ..Default::default() })?;
+    ///
+    ///   let video_id = VideoId::new("dQw4w9WgXcQ")?;
+    ///   let subtitles = ty.get_subtitles(&video_id).await?;
+    ///
+    ///   if let Some(track) = subtitles.find("en") {
+    ///     let options = YtSubtitleOptions { format: YtSubtitleFormat::Vtt, ..Default::default() };
+    ///     let subtitle = ty.download_subtitle(track, options).await?;
+    ///
+    ///     println!("{}", subtitle.content);
+    ///   }
+    ///
+    ///   Ok(())
+    /// }
+    /// ```
+    fn download_subtitle<'a>(
+        &'a self,
+        track: &'a YtCaptionTrack,
+        options: YtSubtitleOptions,
+    ) -> Self::ExtractSubtitleFut<'a>;
+    /// Fetch a video's top-level comments, sorted by "top" or "newest", until `max_comments` are collected.
+    ///
+    /// Replies aren't fetched, pass a comment to `Tydle::get_comment_replies` to load them.
+    ///
+    /// ```
+    /// use tydle::{Tydle, TydleOptions, Extract, VideoId, YtCommentOptions, YtCommentSort};
+    /// use anyhow::Result;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///   let ty = Tydle::new(TydleOptions{ ..Default::default() })?;
+    ///
+    ///   let video_id = VideoId::new("dQw4w9WgXcQ")?;
+    ///   let options = YtCommentOptions { sort: YtCommentSort::Newest, max_comments: Some(50) };
+    ///   let comments = ty.get_comments(&video_id, options).await?;
+    ///
+    ///   for comment in comments.comments {
+    ///     println!("{}: {}", comment.id, comment.text);
+    ///   }
+    ///
+    ///   Ok(())
+    /// }
+    /// ```
+    fn get_comments<'a>(
+        &'a self,
+        video_id: &'a VideoId,
+        options: YtCommentOptions,
+    ) -> Self::ExtractCommentsFut<'a>;
+    /// Fetch the replies to a top-level comment, until `max_comments` are collected.
+    ///
+    /// ```
+    /// use tydle::{Tydle, TydleOptions, Extract, VideoId, YtCommentOptions};
+    /// use anyhow::Result;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///   let ty = Tydle::new(TydleOptions{ ..Default::default() })?;
+    ///
+    ///   let video_id = VideoId::new("dQw4w9WgXcQ")?;
+    ///   let comments = ty.get_comments(&video_id, Default::default()).await?;
+    ///
+    ///   if let Some(thread) = comments.comments.iter().find(|c| c.reply_count > 0) {
+    ///     let replies = ty.get_comment_replies(thread, YtCommentOptions::default()).await?;
+    ///     println!("Replies: {:?}", replies.comments);
+    ///   }
+    ///
+    ///   Ok(())
+    /// }
+    /// ```
+    fn get_comment_replies<'a>(
+        &'a self,
+        comment: &'a YtComment,
+        options: YtCommentOptions,
+    ) -> Self::ExtractCommentsFut<'a>;
+    /// Keep fetching comments or replies from a previous response's `continuation`.
+    ///
+    /// ```
+    /// use tydle::{Tydle, TydleOptions, Extract, VideoId, YtCommentOptions};
+    /// use anyhow::Result;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///   let ty = Tydle::new(TydleOptions{ ..Default::default() })?;
+    ///
+    ///   let video_id = VideoId::new("dQw4w9WgXcQ")?;
+    ///   let options = YtCommentOptions { max_comments: Some(20), ..Default::default() };
+    ///   let comments = ty.get_comments(&video_id, options.clone()).await?;
+    ///
+    ///   if let Some(continuation) = comments.continuation {
+    ///     let more = ty.get_comments_continuation(&continuation, options).await?;
+    ///     println!("More comments: {:?}", more.comments);
+    ///   }
+    ///
+    ///   Ok(())
+    /// }
+    /// ```
+    fn get_comments_continuation<'a>(
+        &'a self,
+        continuation: &'a str,
+        options: YtCommentOptions,
+    ) -> Self::ExtractCommentsFut<'a>;
 
     type ExtractStreamFut<'a>: Future<Output = Result<YtStreamResponse>> + 'a
     where
@@ -194,6 +564,33 @@ pub trait Extract {
     type ExtractManifestFut<'a>: Future<Output = Result<YtManifest>> + 'a
     where
         Self: 'a;
+    type ExtractPlaylistFut<'a>: Future<Output = Result<YtPlaylist>> + 'a
+    where
+        Self: 'a;
+    type ExtractChannelInfoFut<'a>: Future<Output = Result<YtChannelInfo>> + 'a
+    where
+        Self: 'a;
+    type ExtractChannelTabFut<'a>: Future<Output = Result<YtChannelTabResponse>> + 'a
+    where
+        Self: 'a;
+    type ExtractResolvedUrlFut<'a>: Future<Output = Result<YtResolvedUrl>> + 'a
+    where
+        Self: 'a;
+    type ExtractChannelFut<'a>: Future<Output = Result<YtChannel>> + 'a
+    where
+        Self: 'a;
+    type ExtractSearchFut<'a>: Future<Output = Result<YtSearchResponse>> + 'a
+    where
+        Self: 'a;
+    type ExtractSubtitlesFut<'a>: Future<Output = Result<YtSubtitles>> + 'a
+    where
+        Self: 'a;
+    type ExtractSubtitleFut<'a>: Future<Output = Result<YtSubtitle>> + 'a
+    where
+        Self: 'a;
+    type ExtractCommentsFut<'a>: Future<Output = Result<YtCommentsResponse>> + 'a
+    where
+        Self: 'a;
 }
 
 pub trait Cipher {
@@ -203,44 +600,50 @@ pub trait Cipher {
         signature: String,
         player_url: String,
     ) -> Self::DecipherFut<'a>;
+    /// Returns a playable URL for any stream, with its `n` parameter solved so the download isn't
+    /// throttled. HLS and DASH streams resolve to the URL of their playlist or manifest.
+    fn resolve_stream_url<'a>(
+        &'a self,
+        stream: &'a YtStream,
+        player_url: String,
+    ) -> Self::DecipherFut<'a>;
+    /// Deciphers the signatures and `n` parameters of every stream in one go,
+    /// returning the response with playable URLs for all `URL` and `Signature` streams.
+    /// Streams that couldn't be deciphered keep their source.
+    fn decipher_streams<'a>(&'a self, response: YtStreamResponse) -> Self::DecipherStreamsFut<'a>;
     type DecipherFut<'a>: Future<Output = Result<String>> + 'a
     where
         Self: 'a;
+    type DecipherStreamsFut<'a>: Future<Output = Result<YtStreamResponse>> + 'a
+    where
+        Self: 'a;
 }
 
 impl Extract for Tydle {
     type ExtractStreamFut<'a> = Pin<Box<dyn Future<Output = Result<YtStreamResponse>> + 'a>>;
     type ExtractInfoFut<'a> = Pin<Box<dyn Future<Output = Result<YtVideoInfo>> + 'a>>;
     type ExtractManifestFut<'a> = Pin<Box<dyn Future<Output = Result<YtManifest>> + 'a>>;
+    type ExtractPlaylistFut<'a> = Pin<Box<dyn Future<Output = Result<YtPlaylist>> + 'a>>;
+    type ExtractChannelInfoFut<'a> = Pin<Box<dyn Future<Output = Result<YtChannelInfo>> + 'a>>;
+    type ExtractChannelTabFut<'a> =
+        Pin<Box<dyn Future<Output = Result<YtChannelTabResponse>> + 'a>>;
+    type ExtractResolvedUrlFut<'a> = Pin<Box<dyn Future<Output = Result<YtResolvedUrl>> + 'a>>;
+    type ExtractChannelFut<'a> = Pin<Box<dyn Future<Output = Result<YtChannel>> + 'a>>;
+    type ExtractSearchFut<'a> = Pin<Box<dyn Future<Output = Result<YtSearchResponse>> + 'a>>;
+    type ExtractSubtitlesFut<'a> = Pin<Box<dyn Future<Output = Result<YtSubtitles>> + 'a>>;
+    type ExtractSubtitleFut<'a> = Pin<Box<dyn Future<Output = Result<YtSubtitle>> + 'a>>;
+    type ExtractCommentsFut<'a> = Pin<Box<dyn Future<Output = Result<YtCommentsResponse>> + 'a>>;
 
     fn get_streams<'a>(&'a self, video_id: &'a VideoId) -> Self::ExtractStreamFut<'a> {
-        Box::pin(async move {
-            let extractor = self
-                .yt_extractor
-                .lock()
-                .map_err(|e| anyhow!(e.to_string()))?;
-            extractor.extract_streams(video_id).await
-        })
+        Box::pin(async move { self.yt_extractor.extract_streams(video_id).await })
     }
 
     fn get_manifest<'a>(&'a self, video_id: &'a VideoId) -> Self::ExtractManifestFut<'a> {
-        Box::pin(async move {
-            let extractor = self
-                .yt_extractor
-                .lock()
-                .map_err(|e| anyhow!(e.to_string()))?;
-            extractor.extract_manifest(video_id).await
-        })
+        Box::pin(async move { self.yt_extractor.extract_manifest(video_id).await })
     }
 
     fn get_video_info<'a>(&'a self, video_id: &'a VideoId) -> Self::ExtractInfoFut<'a> {
-        Box::pin(async move {
-            let extractor = self
-                .yt_extractor
-                .lock()
-                .map_err(|e| anyhow!(e.to_string()))?;
-            extractor.extract_video_info(video_id).await
-        })
+        Box::pin(async move { self.yt_extractor.extract_video_info(video_id).await })
     }
 
     fn get_streams_from_manifest<'a>(
@@ -248,11 +651,9 @@ impl Extract for Tydle {
         manifest: &'a YtManifest,
     ) -> Self::ExtractStreamFut<'a> {
         Box::pin(async move {
-            let extractor = self
-                .yt_extractor
-                .lock()
-                .map_err(|e| anyhow!(e.to_string()))?;
-            extractor.extract_streams_from_manifest(manifest).await
+            self.yt_extractor
+                .extract_streams_from_manifest(manifest)
+                .await
         })
     }
 
@@ -261,17 +662,129 @@ impl Extract for Tydle {
         manifest: &'a YtManifest,
     ) -> Self::ExtractInfoFut<'a> {
         Box::pin(async move {
-            let extractor = self
+            self.yt_extractor
+                .extract_video_info_from_manifest(manifest)
+                .await
+        })
+    }
+
+    fn get_playlist<'a>(
+        &'a self,
+        playlist_id: &'a PlaylistId,
+        options: YtPlaylistOptions,
+    ) -> Self::ExtractPlaylistFut<'a> {
+        Box::pin(async move {
+            self.yt_extractor
+                .extract_playlist(playlist_id, &options)
+                .await
+        })
+    }
+
+    fn get_channel_info<'a>(&'a self, channel: &'a YtChannel) -> Self::ExtractChannelInfoFut<'a> {
+        Box::pin(async move { self.yt_extractor.extract_channel_info(channel).await })
+    }
+
+    fn get_channel_tab<'a>(
+        &'a self,
+        channel: &'a YtChannel,
+        tab: YtChannelTab,
+        options: YtChannelTabOptions,
+    ) -> Self::ExtractChannelTabFut<'a> {
+        Box::pin(async move {
+            self.yt_extractor
+                .extract_channel_tab(channel, tab, &options)
+                .await
+        })
+    }
+
+    fn resolve_url<'a>(&'a self, url: &'a str) -> Self::ExtractResolvedUrlFut<'a> {
+        Box::pin(async move { self.yt_extractor.resolve_url(url).await })
+    }
+
+    fn resolve_channel<'a>(&'a self, url: &'a str) -> Self::ExtractChannelFut<'a> {
+        Box::pin(async move { self.yt_extractor.resolve_channel(url).await })
+    }
+
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        filters: YtSearchFilters,
+    ) -> Self::ExtractSearchFut<'a> {
+        Box::pin(async move { self.yt_extractor.extract_search(query, &filters).await })
+    }
+
+    fn search_continuation<'a>(&'a self, continuation: &'a str) -> Self::ExtractSearchFut<'a> {
+        Box::pin(async move {
+            self.yt_extractor
+                .extract_search_continuation(continuation)
+                .await
+        })
+    }
+
+    fn get_subtitles<'a>(&'a self, video_id: &'a VideoId) -> Self::ExtractSubtitlesFut<'a> {
+        Box::pin(async move {
+            let yt_manifest = self.yt_extractor.extract_manifest(video_id).await?;
+            Ok(self
+                .yt_extractor
+                .extract_subtitles(&yt_manifest.extracted_manifest))
+        })
+    }
+
+    fn get_subtitles_from_manifest<'a>(
+        &'a self,
+        manifest: &'a YtManifest,
+    ) -> Self::ExtractSubtitlesFut<'a> {
+        Box::pin(async move {
+            Ok(self
                 .yt_extractor
-                .lock()
-                .map_err(|e| anyhow!(e.to_string()))?;
-            extractor.extract_video_info_from_manifest(manifest).await
+                .extract_subtitles(&manifest.extracted_manifest))
+        })
+    }
+
+    fn download_subtitle<'a>(
+        &'a self,
+        track: &'a YtCaptionTrack,
+        options: YtSubtitleOptions,
+    ) -> Self::ExtractSubtitleFut<'a> {
+        Box::pin(async move { self.yt_extractor.download_subtitle(track, &options).await })
+    }
+
+    fn get_comments<'a>(
+        &'a self,
+        video_id: &'a VideoId,
+        options: YtCommentOptions,
+    ) -> Self::ExtractCommentsFut<'a> {
+        Box::pin(async move { self.yt_extractor.extract_comments(video_id, &options).await })
+    }
+
+    fn get_comment_replies<'a>(
+        &'a self,
+        comment: &'a YtComment,
+        options: YtCommentOptions,
+    ) -> Self::ExtractCommentsFut<'a> {
+        Box::pin(async move {
+            self.yt_extractor
+                .extract_comment_replies(comment, &options)
+                .await
+        })
+    }
+
+    fn get_comments_continuation<'a>(
+        &'a self,
+        continuation: &'a str,
+        options: YtCommentOptions,
+    ) -> Self::ExtractCommentsFut<'a> {
+        Box::pin(async move {
+            self.yt_extractor
+                .extract_comments_continuation(continuation, &options)
+                .await
         })
     }
 }
 
 impl Cipher for Tydle {
     type DecipherFut<'a> = Pin<Box<dyn Future<Output = Result<String>> + 'a>>;
+    type DecipherStreamsFut<'a> = Pin<Box<dyn Future<Output = Result<YtStreamResponse>> + 'a>>;
 
     fn decipher_signature<'a>(
         &'a self,
@@ -279,40 +792,42 @@ impl Cipher for Tydle {
         player_url: String,
     ) -> Self::DecipherFut<'a> {
         Box::pin(async move {
-            let signature_decipher = self
-                .signature_decipher
-                .lock()
-                .map_err(|e| anyhow!(e.to_string()))?;
-            signature_decipher.decipher(signature, player_url).await
+            self.signature_decipher
+                .decipher(signature, player_url)
+                .await
+        })
+    }
+
+    fn resolve_stream_url<'a>(
+        &'a self,
+        stream: &'a YtStream,
+        player_url: String,
+    ) -> Self::DecipherFut<'a> {
+        Box::pin(async move {
+            self.signature_decipher
+                .resolve_stream_url(stream, player_url)
+                .await
         })
     }
+
+    fn decipher_streams<'a>(&'a self, response: YtStreamResponse) -> Self::DecipherStreamsFut<'a> {
+        Box::pin(async move { self.signature_decipher.decipher_streams(response).await })
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
 mod wasm_api {
     use super::*;
+    use crate::format_selector::FormatSelector;
+    use crate::yt_interface::{Filterable, YtFormatSelection, YtStreamList};
     use wasm_bindgen::JsValue;
 
     #[wasm_bindgen]
     impl Tydle {
         #[wasm_bindgen(constructor)]
         pub fn new(options: Option<TydleOptions>) -> Result<Tydle, JsValue> {
-            let player_cache = Arc::new(CacheStore::new());
-            let code_cache = Arc::new(CacheStore::new());
-
-            let yt_extractor = YtExtractor::new(
-                player_cache.clone(),
-                code_cache.clone(),
-                options.unwrap_or_default(),
-            )
-            .map_err(|e| JsValue::from_str(&e.to_string()))?;
-
-            let signature_decipher = SignatureDecipher::new(player_cache, code_cache);
-
-            Ok(Tydle {
-                yt_extractor: Arc::new(Mutex::new(yt_extractor)),
-                signature_decipher: Arc::new(Mutex::new(signature_decipher)),
-            })
+            Tydle::build(options.unwrap_or_default(), default_solver())
+                .map_err(|e| JsValue::from_str(&e.to_string()))
         }
 
         #[wasm_bindgen(js_name = "fetchStreams")]
@@ -320,7 +835,7 @@ mod wasm_api {
             &self,
             #[wasm_bindgen(js_name = "videoId")] video_id: String,
         ) -> Result<YtStreamResponse, JsValue> {
-            let id = VideoId::new(&video_id).map_err(|e| JsValue::from_str(&e.to_string()))?;
+            let id = VideoId::from_url(&video_id).map_err(|e| JsValue::from_str(&e.to_string()))?;
 
             Ok(self
                 .get_streams(&id)
@@ -333,7 +848,7 @@ mod wasm_api {
             &self,
             #[wasm_bindgen(js_name = "videoId")] video_id: String,
         ) -> Result<YtVideoInfo, JsValue> {
-            let id = VideoId::new(&video_id).map_err(|e| JsValue::from_str(&e.to_string()))?;
+            let id = VideoId::from_url(&video_id).map_err(|e| JsValue::from_str(&e.to_string()))?;
 
             Ok(self
                 .get_video_info(&id)
@@ -368,7 +883,7 @@ mod wasm_api {
             &self,
             #[wasm_bindgen(js_name = "videoId")] video_id: String,
         ) -> Result<YtManifest, JsValue> {
-            let id = VideoId::new(&video_id).map_err(|e| JsValue::from_str(&e.to_string()))?;
+            let id = VideoId::from_url(&video_id).map_err(|e| JsValue::from_str(&e.to_string()))?;
 
             Ok(self
                 .get_manifest(&id)
@@ -376,6 +891,210 @@ mod wasm_api {
                 .map_err(|e| JsValue::from_str(&e.to_string()))?)
         }
 
+        #[wasm_bindgen(js_name = "fetchPlaylist")]
+        pub async fn fetch_playlist(
+            &self,
+            #[wasm_bindgen(js_name = "playlistId")] playlist_id: String,
+            options: Option<YtPlaylistOptions>,
+        ) -> Result<YtPlaylist, JsValue> {
+            let id = PlaylistId::from_url(&playlist_id)
+                .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+            Ok(self
+                .get_playlist(&id, options.unwrap_or_default())
+                .await
+                .map_err(|e| JsValue::from_str(&e.to_string()))?)
+        }
+
+        #[wasm_bindgen(js_name = "fetchChannelInfo")]
+        pub async fn fetch_channel_info(
+            &self,
+            #[wasm_bindgen(js_name = "channelId")] channel_id: String,
+        ) -> Result<YtChannelInfo, JsValue> {
+            let channel =
+                YtChannel::new(channel_id, None).map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+            Ok(self
+                .get_channel_info(&channel)
+                .await
+                .map_err(|e| JsValue::from_str(&e.to_string()))?)
+        }
+
+        #[wasm_bindgen(js_name = "fetchChannelTab")]
+        pub async fn fetch_channel_tab(
+            &self,
+            #[wasm_bindgen(js_name = "channelId")] channel_id: String,
+            tab: YtChannelTab,
+            options: Option<YtChannelTabOptions>,
+        ) -> Result<YtChannelTabResponse, JsValue> {
+            let channel =
+                YtChannel::new(channel_id, None).map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+            Ok(self
+                .get_channel_tab(&channel, tab, options.unwrap_or_default())
+                .await
+                .map_err(|e| JsValue::from_str(&e.to_string()))?)
+        }
+
+        #[wasm_bindgen(js_name = "resolveUrl")]
+        pub async fn resolve_url_js(&self, url: String) -> Result<YtResolvedUrl, JsValue> {
+            Ok(self
+                .resolve_url(&url)
+                .await
+                .map_err(|e| JsValue::from_str(&e.to_string()))?)
+        }
+
+        #[wasm_bindgen(js_name = "resolveChannel")]
+        pub async fn resolve_channel_js(&self, url: String) -> Result<YtChannel, JsValue> {
+            Ok(self
+                .resolve_channel(&url)
+                .await
+                .map_err(|e| JsValue::from_str(&e.to_string()))?)
+        }
+
+        #[wasm_bindgen(js_name = "search")]
+        pub async fn search_js(
+            &self,
+            query: String,
+            filters: Option<YtSearchFilters>,
+        ) -> Result<YtSearchResponse, JsValue> {
+            Ok(self
+                .search(&query, filters.unwrap_or_default())
+                .await
+                .map_err(|e| JsValue::from_str(&e.to_string()))?)
+        }
+
+        #[wasm_bindgen(js_name = "searchContinuation")]
+        pub async fn search_continuation_js(
+            &self,
+            continuation: String,
+        ) -> Result<YtSearchResponse, JsValue> {
+            Ok(self
+                .search_continuation(&continuation)
+                .await
+                .map_err(|e| JsValue::from_str(&e.to_string()))?)
+        }
+
+        #[wasm_bindgen(js_name = "fetchSubtitles")]
+        pub async fn fetch_subtitles(
+            &self,
+            #[wasm_bindgen(js_name = "videoId")] video_id: String,
+        ) -> Result<YtSubtitles, JsValue> {
+            let id = VideoId::from_url(&video_id).map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+            Ok(self
+                .get_subtitles(&id)
+                .await
+                .map_err(|e| JsValue::from_str(&e.to_string()))?)
+        }
+
+        #[wasm_bindgen(js_name = "fetchSubtitlesFromManifest")]
+        pub async fn fetch_subtitles_from_manifest(
+            &self,
+            manifest: YtManifest,
+        ) -> Result<YtSubtitles, JsValue> {
+            Ok(self
+                .get_subtitles_from_manifest(&manifest)
+                .await
+                .map_err(|e| JsValue::from_str(&e.to_string()))?)
+        }
+
+        #[wasm_bindgen(js_name = "downloadSubtitle")]
+        pub async fn download_subtitle_js(
+            &self,
+            track: YtCaptionTrack,
+            options: Option<YtSubtitleOptions>,
+        ) -> Result<YtSubtitle, JsValue> {
+            Ok(self
+                .download_subtitle(&track, options.unwrap_or_default())
+                .await
+                .map_err(|e| JsValue::from_str(&e.to_string()))?)
+        }
+
+        #[wasm_bindgen(js_name = "fetchComments")]
+        pub async fn fetch_comments(
+            &self,
+            #[wasm_bindgen(js_name = "videoId")] video_id: String,
+            options: Option<YtCommentOptions>,
+        ) -> Result<YtCommentsResponse, JsValue> {
+            let id = VideoId::from_url(&video_id).map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+            Ok(self
+                .get_comments(&id, options.unwrap_or_default())
+                .await
+                .map_err(|e| JsValue::from_str(&e.to_string()))?)
+        }
+
+        #[wasm_bindgen(js_name = "fetchCommentReplies")]
+        pub async fn fetch_comment_replies(
+            &self,
+            comment: YtComment,
+            options: Option<YtCommentOptions>,
+        ) -> Result<YtCommentsResponse, JsValue> {
+            Ok(self
+                .get_comment_replies(&comment, options.unwrap_or_default())
+                .await
+                .map_err(|e| JsValue::from_str(&e.to_string()))?)
+        }
+
+        #[wasm_bindgen(js_name = "fetchCommentsContinuation")]
+        pub async fn fetch_comments_continuation(
+            &self,
+            continuation: String,
+            options: Option<YtCommentOptions>,
+        ) -> Result<YtCommentsResponse, JsValue> {
+            Ok(self
+                .get_comments_continuation(&continuation, options.unwrap_or_default())
+                .await
+                .map_err(|e| JsValue::from_str(&e.to_string()))?)
+        }
+
+        #[wasm_bindgen(js_name = "generateDashManifest")]
+        pub fn generate_dash_manifest_js(
+            &self,
+            #[wasm_bindgen(js_name = "streamResponse")] stream_response: YtStreamResponse,
+        ) -> Result<String, JsValue> {
+            stream_response
+                .to_dash_manifest()
+                .map_err(|e| JsValue::from_str(&e.to_string()))
+        }
+
+        #[wasm_bindgen(js_name = "selectFormat")]
+        pub fn select_format_js(
+            &self,
+            streams: YtStreamList,
+            selector: String,
+        ) -> Result<YtFormatSelection, JsValue> {
+            let sort = self.format_sort();
+
+            FormatSelector::parse(&selector)
+                .map_err(|e| JsValue::from_str(&e.to_string()))?
+                .select_sorted(&streams, &sort)
+                .ok_or_else(|| JsValue::from_str("Requested format is not available."))
+        }
+
+        #[wasm_bindgen(js_name = "sortFormats")]
+        pub fn sort_formats_js(
+            &self,
+            streams: YtStreamList,
+            sort: Option<String>,
+        ) -> Result<YtStreamList, JsValue> {
+            let default_sort = self.format_sort();
+            let sort = match sort {
+                Some(sort) => FormatSort::parse(&sort)
+                    .map_err(|e| JsValue::from_str(&e.to_string()))?
+                    .with_client_priority(default_sort.client_priority),
+                None => default_sort,
+            };
+
+            Ok(streams.sort_formats(&sort))
+        }
+
+        #[wasm_bindgen(js_name = "muxStreams")]
+        pub fn mux_streams_js(&self, video: Vec<u8>, audio: Vec<u8>) -> Result<Vec<u8>, JsValue> {
+            crate::muxer::mux(&video, &audio).map_err(|e| JsValue::from_str(&e.to_string()))
+        }
+
         #[wasm_bindgen(js_name = "decipherSignature")]
         pub async fn decipher_signature_js(
             &self,
@@ -388,5 +1107,26 @@ mod wasm_api {
                 .map_err(|e| JsValue::from_str(&e.to_string()))?;
             Ok(res)
         }
+
+        #[wasm_bindgen(js_name = "resolveStreamUrl")]
+        pub async fn resolve_stream_url_js(
+            &self,
+            stream: YtStream,
+            #[wasm_bindgen(js_name = "playerUrl")] player_url: String,
+        ) -> Result<String, JsValue> {
+            self.resolve_stream_url(&stream, player_url)
+                .await
+                .map_err(|e| JsValue::from_str(&e.to_string()))
+        }
+
+        #[wasm_bindgen(js_name = "decipherStreams")]
+        pub async fn decipher_streams_js(
+            &self,
+            response: YtStreamResponse,
+        ) -> Result<YtStreamResponse, JsValue> {
+            self.decipher_streams(response)
+                .await
+                .map_err(|e| JsValue::from_str(&e.to_string()))
+        }
     }
 }
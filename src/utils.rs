@@ -1,8 +1,10 @@
 use anyhow::Result;
+use fancy_regex::{Captures, Regex};
 use maplit::hashmap;
+use once_cell::sync::Lazy;
 use std::collections::HashMap;
 
-use crate::yt_interface::Ext;
+use crate::yt_interface::{Ext, YtDynamicRange};
 use url::{Url, form_urlencoded};
 
 pub fn parse_query_string(qs: &str) -> Option<HashMap<String, String>> {
@@ -34,6 +36,38 @@ pub fn replace_n_sig_query_param(
     Ok(url.to_string())
 }
 
+/// The `n` parameter of an HLS or DASH manifest URL, which carries it as a `/n/<value>/` path segment.
+pub fn n_sig_path_segment(manifest_url: &str) -> Option<String> {
+    let url = Url::parse(manifest_url).ok()?;
+    let mut segments = url.path_segments()?;
+
+    segments.find(|segment| *segment == "n")?;
+    segments.next().map(String::from)
+}
+
+/// Replace the value of the `/n/<value>/` path segment of a manifest URL.
+pub fn replace_n_sig_path_segment(
+    manifest_url: &str,
+    deciphered_n: &str,
+) -> Result<String, url::ParseError> {
+    let mut url = Url::parse(manifest_url)?;
+    let Some(mut segments) = url
+        .path_segments()
+        .map(|segments| segments.map(String::from).collect::<Vec<_>>())
+    else {
+        return Ok(manifest_url.to_string());
+    };
+
+    if let Some(position) = segments.iter().position(|segment| segment == "n")
+        && let Some(value) = segments.get_mut(position + 1)
+    {
+        *value = deciphered_n.to_string();
+        url.set_path(&segments.join("/"));
+    }
+
+    Ok(url.to_string())
+}
+
 #[cfg(target_arch = "wasm32")]
 pub fn unix_timestamp_secs() -> f64 {
     js_sys::Date::now() / 1000.0
@@ -48,9 +82,231 @@ pub fn unix_timestamp_secs() -> f64 {
     epoch.as_secs_f64()
 }
 
-/// Returns the file size in bytes.
-pub fn file_size_from_tbr(tbr: f64, duration: f64) -> f64 {
-    duration * tbr * (1000 / 8) as f64
+/// Parse human readable counts like `"1,234 videos"` or `"1.2M views"`.
+pub fn parse_count(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let number_len = text
+        .find(|c: char| !(c.is_ascii_digit() || c == ',' || c == '.'))
+        .unwrap_or(text.len());
+    let (number, rest) = text.split_at(number_len);
+    let number = number.replace(',', "");
+
+    if number.is_empty() {
+        return text.to_lowercase().starts_with("no ").then_some(0);
+    }
+
+    let mut rest_chars = rest.chars();
+    let suffix = rest_chars.next().map(|c| c.to_ascii_uppercase());
+    let multiplier = match (suffix, rest_chars.next()) {
+        (_, Some(c)) if c.is_alphabetic() => 1.0,
+        (Some('K'), _) => 1_000.0,
+        (Some('M'), _) => 1_000_000.0,
+        (Some('B'), _) => 1_000_000_000.0,
+        _ => 1.0,
+    };
+
+    number
+        .parse::<f64>()
+        .ok()
+        .map(|n| (n * multiplier).round() as u64)
+}
+
+/// Parse `[[hh:]mm:]ss` durations as shown in thumbnail overlays into seconds.
+pub fn parse_duration(text: &str) -> Option<u64> {
+    let mut seconds = 0u64;
+
+    for part in text.trim().split(':') {
+        seconds = seconds * 60 + part.trim().parse::<u64>().ok()?;
+    }
+
+    Some(seconds)
+}
+
+/// Parse an ISO 8601 date-time like `2024-05-01T18:00:00+00:00` into a unix timestamp.
+pub fn parse_iso8601(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let (date, time) = text.split_once('T')?;
+
+    let mut date_parts = date.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+    let (year, month, day) = (
+        date_parts.next()??,
+        date_parts.next()??,
+        date_parts.next()??,
+    );
+
+    let (clock, offset_seconds) = if let Some(clock) = time.strip_suffix('Z') {
+        (clock, 0)
+    } else if let Some(sign_pos) = time.rfind(['+', '-']) {
+        let (clock, offset) = time.split_at(sign_pos);
+        let sign = if offset.starts_with('-') { -1 } else { 1 };
+        let (hours, minutes) = offset[1..].split_once(':').unwrap_or((&offset[1..], "0"));
+
+        (
+            clock,
+            sign * (hours.parse::<i64>().ok()? * 3600 + minutes.parse::<i64>().ok()? * 60),
+        )
+    } else {
+        (time, 0)
+    };
+
+    let mut clock_parts = clock.splitn(3, ':');
+    let hour: i64 = clock_parts.next()?.parse().ok()?;
+    let minute: i64 = clock_parts.next()?.parse().ok()?;
+    let second = clock_parts
+        .next()
+        .map(|s| s.split('.').next().unwrap_or_default().parse::<i64>().ok())
+        .unwrap_or(Some(0))?;
+
+    // Days since the unix epoch for a proleptic Gregorian date.
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * m + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    u64::try_from(days * 86_400 + hour * 3600 + minute * 60 + second - offset_seconds).ok()
+}
+
+/// Standard padded base64, as used for InnerTube `params` values.
+pub fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+static XML_ENTITY_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"&(?P<entity>#x[0-9a-fA-F]+|#[0-9]+|amp|lt|gt|quot|apos);")
+        .expect("XML entity regex is valid")
+});
+
+/// Decode the predefined XML entities and numeric character references.
+pub fn unescape_xml(text: &str) -> Result<String> {
+    Ok(XML_ENTITY_RE
+        .replace_all(text, |caps: &Captures| {
+            let entity = caps.name("entity").map(|e| e.as_str()).unwrap_or_default();
+            let code_point = if let Some(hex) = entity.strip_prefix("#x") {
+                u32::from_str_radix(hex, 16).ok()
+            } else if let Some(dec) = entity.strip_prefix('#') {
+                dec.parse().ok()
+            } else {
+                None
+            };
+
+            match (entity, code_point) {
+                (_, Some(cp)) => char::from_u32(cp).map(String::from).unwrap_or_default(),
+                ("amp", _) => "&".into(),
+                ("lt", _) => "<".into(),
+                ("gt", _) => ">".into(),
+                ("quot", _) => "\"".into(),
+                _ => "'".into(),
+            }
+        })
+        .into_owned())
+}
+
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const VIDEO_CODECS: [&str; 11] = [
+    "avc1", "avc3", "hev1", "hvc1", "dvh1", "dvhe", "dav1", "vp8", "vp9", "vp09", "av01",
+];
+const AUDIO_CODECS: [&str; 9] = [
+    "mp4a", "opus", "vorbis", "ac-3", "ec-3", "flac", "mp3", "alac", "dtsc",
+];
+
+/// Split RFC 6381 codecs into the video and the audio codec.
+/// A lone codec that isn't recognized goes with the media type of the MIME type.
+pub fn split_codecs(
+    mime_type: Option<&str>,
+    codecs: Option<&str>,
+) -> (Option<String>, Option<String>) {
+    let Some(codecs) = codecs else {
+        return (None, None);
+    };
+    let codecs: Vec<&str> = codecs
+        .split(',')
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .collect();
+    let is_one_of = |codec: &str, known: &[&str]| known.iter().any(|k| codec.starts_with(k));
+
+    let mut vcodec = codecs.iter().find(|c| is_one_of(c, &VIDEO_CODECS));
+    let mut acodec = codecs.iter().find(|c| is_one_of(c, &AUDIO_CODECS));
+
+    if let [codec] = codecs.as_slice()
+        && vcodec.is_none()
+        && acodec.is_none()
+    {
+        match mime_type.is_some_and(|m| m.starts_with("audio/")) {
+            true => acodec = Some(codec),
+            false => vcodec = Some(codec),
+        }
+    }
+
+    (vcodec.map(|c| c.to_string()), acodec.map(|c| c.to_string()))
+}
+
+/// Work out the dynamic range of a video from its codec and transfer characteristics,
+/// given as `smpte2084` (PQ) or `arib_std_b67` (HLG).
+pub fn dynamic_range(vcodec: &str, transfer_characteristics: Option<&str>) -> YtDynamicRange {
+    if ["dvh1", "dvhe", "dav1"]
+        .iter()
+        .any(|dv| vcodec.starts_with(dv))
+    {
+        return YtDynamicRange::DolbyVision;
+    }
+
+    // The bit depth is the fourth part of VP9 and AV1 codec strings, like `vp09.02.51.12`.
+    let bit_depth = vcodec.split('.').nth(3).and_then(|d| d.parse::<u8>().ok());
+
+    // YouTube's `colorInfo` spells PQ as `SMPTEST2084`.
+    let transfer_characteristics = transfer_characteristics.map(|transfer| match transfer {
+        "smptest2084" => "smpte2084",
+        transfer => transfer,
+    });
+
+    match transfer_characteristics {
+        Some("smpte2084") if bit_depth == Some(12) => YtDynamicRange::Hdr12,
+        Some("smpte2084") => YtDynamicRange::Hdr10,
+        Some("arib_std_b67") => YtDynamicRange::Hlg,
+        // VP9 profile 2 is only served for HDR.
+        None if vcodec.starts_with("vp09.02") => YtDynamicRange::Hdr10,
+        _ => YtDynamicRange::Sdr,
+    }
+}
+
+/// Returns the file size in bytes of a stream with a bitrate in bits per second and a duration
+/// in milliseconds, the units of `YtStream::tbr` and `YtStream::format_duration`.
+pub fn file_size_from_tbr(tbr: f64, duration_ms: f64) -> f64 {
+    duration_ms / 1000.0 * tbr / 8.0
 }
 
 pub fn mime_type_to_ext(mime_type: &str) -> Ext {
@@ -119,6 +375,8 @@ pub fn mime_type_to_ext(mime_type: &str) -> Ext {
         "ttaf+xml" => Ext::Dfxp,
         "ttml+xml" => Ext::Ttml,
         "x-ms-sami" => Ext::Sami,
+        "x-subrip" => Ext::Srt,
+        "vtt" => Ext::Vtt,
         // Misc
         "gzip" => Ext::Gz,
         "json" => Ext::Json,
@@ -144,3 +402,15 @@ pub fn mime_type_to_ext(mime_type: &str) -> Ext {
         .copied()
         .unwrap_or_default()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_size_from_bps_and_ms() {
+        // A 128 kbps audio stream lasting 3:32.
+        assert_eq!(file_size_from_tbr(128_000.0, 212_000.0), 3_392_000.0);
+        assert_eq!(file_size_from_tbr(128_000.0, 0.0), 0.0);
+    }
+}
@@ -1,22 +1,35 @@
 use core::fmt;
-use std::{collections::HashMap, ops::Deref, str::FromStr};
+use std::{
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+    str::FromStr,
+};
 
 use anyhow::{Result, anyhow, bail};
 use serde_json::Value;
 
+use crate::{
+    extractor::dash::generate_dash_manifest, format_selector::FormatSelector,
+    format_sort::FormatSort,
+};
+
 #[derive(Debug)]
 pub enum YtEndpoint {
-    // Browse,
+    Browse,
     Player,
     Next,
+    ResolveUrl,
+    Search,
 }
 
 impl YtEndpoint {
     pub fn as_str(&self) -> &'static str {
         match self {
-            // Self::Browse => "browse",
+            Self::Browse => "browse",
             Self::Player => "player",
             Self::Next => "next",
+            Self::ResolveUrl => "navigation/resolve_url",
+            Self::Search => "search",
         }
     }
 }
@@ -166,6 +179,11 @@ pub enum PlayerIdentifier {
     PlayerUrl(String),
 }
 
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
 pub struct VideoId(String);
 
@@ -214,6 +232,252 @@ impl fmt::Display for VideoId {
     }
 }
 
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct PlaylistId(String);
+
+impl PlaylistId {
+    pub fn new<S: Into<String>>(s: S) -> Result<Self> {
+        let s = s.into();
+        if s.len() < 2 {
+            bail!(
+                "invalid length: expected at least 2 characters, got {}",
+                s.len()
+            );
+        }
+
+        if !s
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            bail!("invalid characters in playlist ID: {}", s);
+        }
+
+        Ok(Self(s))
+    }
+
+    /// Extract the playlist ID from a bare ID or any YouTube URL carrying a `list=` parameter.
+    pub fn from_url<S: AsRef<str>>(url: S) -> Result<Self> {
+        let input = url.as_ref().trim();
+
+        if let Ok(playlist_id) = Self::new(input) {
+            return Ok(playlist_id);
+        }
+
+        let with_scheme = if input.contains("://") {
+            input.to_string()
+        } else {
+            format!("https://{}", input)
+        };
+        let url = url::Url::parse(&with_scheme)
+            .map_err(|e| anyhow!("invalid YouTube URL \"{}\": {}", input, e))?;
+
+        match url.query_pairs().find(|(k, _)| k == "list") {
+            Some((_, list)) => Self::new(list.into_owned()),
+            None => bail!("no playlist ID found in URL \"{}\"", input),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The browse ID the InnerTube `browse` endpoint expects for this playlist.
+    pub fn browse_id(&self) -> String {
+        format!("VL{}", self.0)
+    }
+
+    pub fn get_url(&self) -> String {
+        format!("{}/playlist?list={}", YT_URL, self.0)
+    }
+}
+
+impl FromStr for PlaylistId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl fmt::Display for PlaylistId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+pub(crate) const YT_VIDEO_HOSTS: [&str; 6] = [
+    "youtube.com",
+    "www.youtube.com",
+    "m.youtube.com",
+    "music.youtube.com",
+    "youtube-nocookie.com",
+    "www.youtube-nocookie.com",
+];
+
+const YT_VIDEO_PATH_PREFIXES: [&str; 5] = ["shorts", "embed", "live", "v", "e"];
+
+/// A video reference resolved from any of the URL forms YouTube hands out.
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YtVideoUrl {
+    #[cfg_attr(target_arch = "wasm32", tsify(type = "string"))]
+    pub video_id: VideoId,
+    /// Playback start offset in seconds, from the `t=` or `start=` parameter.
+    pub start_time: Option<u64>,
+    /// Playlist the video was opened from, from the `list=` parameter.
+    #[cfg_attr(target_arch = "wasm32", tsify(type = "string | undefined"))]
+    pub playlist_id: Option<PlaylistId>,
+}
+
+impl YtVideoUrl {
+    /// Resolve a bare video ID or any `watch?v=`, `youtu.be/`, `/shorts/`, `/embed/`, `/live/`,
+    /// `m.youtube.com` or `music.youtube.com` link.
+    ///
+    /// ```
+    /// use tydle::YtVideoUrl;
+    ///
+    /// let resolved = YtVideoUrl::parse("https://youtu.be/dQw4w9WgXcQ?t=1m30s&list=PL590L5WQmH8fJ54F369BLDSqIwcs-TCfs").unwrap();
+    ///
+    /// assert_eq!(resolved.video_id.as_str(), "dQw4w9WgXcQ");
+    /// assert_eq!(resolved.start_time, Some(90));
+    /// assert_eq!(
+    ///     resolved.playlist_id.map(|p| p.to_string()).as_deref(),
+    ///     Some("PL590L5WQmH8fJ54F369BLDSqIwcs-TCfs")
+    /// );
+    /// ```
+    pub fn parse<S: AsRef<str>>(input: S) -> Result<Self> {
+        let input = input.as_ref().trim();
+
+        if let Ok(video_id) = VideoId::new(input) {
+            return Ok(Self {
+                video_id,
+                start_time: None,
+                playlist_id: None,
+            });
+        }
+
+        let with_scheme = if input.contains("://") {
+            input.to_string()
+        } else {
+            format!("https://{}", input)
+        };
+        let url = url::Url::parse(&with_scheme)
+            .map_err(|e| anyhow!("invalid YouTube URL \"{}\": {}", input, e))?;
+        let host = url.host_str().unwrap_or_default().to_lowercase();
+        let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+        let mut segments = url
+            .path_segments()
+            .into_iter()
+            .flatten()
+            .filter(|s| !s.is_empty());
+
+        let raw_id = if host == "youtu.be" || host == "www.youtu.be" {
+            segments.next().map(|s| s.to_string())
+        } else if YT_VIDEO_HOSTS.contains(&host.as_str()) {
+            match segments.next() {
+                Some("watch") | None => query.get("v").cloned(),
+                Some(prefix) if YT_VIDEO_PATH_PREFIXES.contains(&prefix) => {
+                    segments.next().map(|s| s.to_string())
+                }
+                Some(_) => None,
+            }
+        } else {
+            bail!("\"{}\" is not a YouTube URL", input)
+        };
+
+        let Some(raw_id) = raw_id else {
+            bail!("no video ID found in URL \"{}\"", input)
+        };
+
+        let fragment_query: HashMap<String, String> = url
+            .fragment()
+            .map(|f| {
+                url::form_urlencoded::parse(f.as_bytes())
+                    .into_owned()
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let start_time = ["t", "start", "time_continue"]
+            .iter()
+            .find_map(|k| query.get(*k).or_else(|| fragment_query.get(*k)))
+            .and_then(|t| parse_timestamp(t));
+
+        Ok(Self {
+            video_id: VideoId::new(raw_id)?,
+            start_time,
+            playlist_id: query.get("list").and_then(|l| PlaylistId::new(l).ok()),
+        })
+    }
+}
+
+impl FromStr for YtVideoUrl {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl VideoId {
+    /// Extract the video ID from a bare ID or any YouTube video URL.
+    ///
+    /// Use `YtVideoUrl::parse` if you also need the start offset or playlist ID.
+    pub fn from_url<S: AsRef<str>>(url: S) -> Result<Self> {
+        Ok(YtVideoUrl::parse(url)?.video_id)
+    }
+}
+
+/// Resolve a bare video ID or any YouTube video URL into its ID, start offset and playlist ID.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen::prelude::wasm_bindgen(js_name = "parseVideoUrl")]
+pub fn parse_video_url_js(url: String) -> Result<YtVideoUrl, wasm_bindgen::JsValue> {
+    YtVideoUrl::parse(url).map_err(|e| wasm_bindgen::JsValue::from_str(&e.to_string()))
+}
+
+/// Parse `t=`/`start=` values like `90`, `90s`, `1m30s` or `1h2m3s` into seconds.
+fn parse_timestamp(raw: &str) -> Option<u64> {
+    let raw = raw.trim();
+
+    if let Ok(secs) = raw.parse::<f64>() {
+        return (secs >= 0.0).then_some(secs as u64);
+    }
+
+    let mut total = 0u64;
+    let mut digits = String::new();
+    for c in raw.chars() {
+        match c {
+            '0'..='9' => digits.push(c),
+            'h' | 'm' | 's' => {
+                let value: u64 = digits.parse().ok()?;
+                digits.clear();
+                total += match c {
+                    'h' => value * 3600,
+                    'm' => value * 60,
+                    _ => value,
+                };
+            }
+            _ => return None,
+        }
+    }
+
+    if !digits.is_empty() {
+        return None;
+    }
+
+    Some(total)
+}
+
 #[cfg_attr(
     target_arch = "wasm32",
     derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
@@ -240,21 +504,151 @@ impl YtChannel {
         &self.id
     }
 
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
     pub fn get_url(&self) -> String {
         format!("{}/channel/{}", YT_URL, self.id)
     }
 }
 
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct YtClip {
+    /// The `Ugkx…` ID from the `/clip/` URL.
+    pub id: String,
+    #[cfg_attr(target_arch = "wasm32", tsify(type = "string"))]
+    pub video_id: VideoId,
+    /// Start of the clipped section in seconds.
+    pub start_time: Option<f64>,
+    /// End of the clipped section in seconds.
+    pub end_time: Option<f64>,
+}
+
+/// What a YouTube URL points to.
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum YtResolvedUrl {
+    Video(YtVideoUrl),
+    Playlist(#[cfg_attr(target_arch = "wasm32", tsify(type = "string"))] PlaylistId),
+    Channel(YtChannel),
+    Clip(YtClip),
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YtChannelTab {
+    Videos,
+    Shorts,
+    Live,
+}
+
+impl YtChannelTab {
+    /// The `params` value the `browse` endpoint expects to open this tab.
+    pub fn browse_params(&self) -> &'static str {
+        match self {
+            Self::Videos => "EgZ2aWRlb3PyBgQKAjoA",
+            Self::Shorts => "EgZzaG9ydHPyBgUKA5oBAA==",
+            Self::Live => "EgdzdHJlYW1z8gYECgJ6AA==",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::Videos => "Videos",
+            Self::Shorts => "Shorts",
+            Self::Live => "Live",
+        }
+    }
+}
+
 #[cfg_attr(
     target_arch = "wasm32",
     derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
     tsify(into_wasm_abi, from_wasm_abi),
     serde(rename_all = "lowercase")
 )]
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum YtStreamSource {
     URL(String),
     Signature(String),
+    /// A variant of an HLS master playlist, played through its media playlist.
+    Hls(YtHlsVariant),
+    /// A representation of a DASH manifest, played through its segments.
+    Dash(YtDashRepresentation),
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct YtHlsVariant {
+    /// URL of the variant's media playlist.
+    pub url: String,
+    /// Peak bitrate in bits per second.
+    pub bandwidth: u64,
+    pub average_bandwidth: Option<u64>,
+    /// RFC 6381 codecs, like `avc1.640028,mp4a.40.2`.
+    pub codecs: Option<String>,
+    pub frame_rate: Option<f64>,
+    /// `SDR`, `PQ` or `HLG`.
+    pub video_range: Option<String>,
+    /// Group ID of the audio renditions this variant plays with.
+    pub audio_group: Option<String>,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct YtDashRepresentation {
+    /// URL of the MPD this representation was parsed from.
+    pub manifest_url: String,
+    /// The representation's `id`, which YouTube sets to the itag.
+    pub id: String,
+    pub base_url: String,
+    pub bandwidth: u64,
+    pub codecs: Option<String>,
+    pub frame_rate: Option<f64>,
+    /// Absolute URL of the initialization segment, if the representation lists its segments.
+    pub initialization_url: Option<String>,
+    /// Absolute URLs of the media segments, empty if the whole representation sits at `base_url`.
+    pub segment_urls: Vec<String>,
+}
+
+/// An inclusive byte range inside a stream, like the `initRange` and `indexRange` of adaptive formats.
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct YtByteRange {
+    pub start: u64,
+    pub end: u64,
 }
 
 #[cfg_attr(
@@ -266,10 +660,14 @@ pub enum YtStreamSource {
 #[derive(Debug, Clone)]
 pub struct YtStream {
     pub asr: Option<u64>,
+    /// Number of audio channels of formats with audio.
+    pub audio_channels: Option<u64>,
     pub file_size: Option<u64>,
     pub file_size_approx: f64,
     pub height: Option<u64>,
     pub width: Option<u64>,
+    /// Frames per second of formats with video.
+    pub fps: Option<f64>,
     pub format_duration: f64,
     pub has_drm: bool,
     pub itag: Option<String>,
@@ -281,7 +679,110 @@ pub struct YtStream {
     pub projection: Option<String>,
     pub spatial_audio: Option<String>,
     pub client: YtClient,
+    /// Every client that returned this format, `client` is the one its source comes from.
+    pub clients: Vec<YtClient>,
     pub ext: Ext,
+    /// Whether this is a segment-based format of an ongoing live stream rather than a VOD file.
+    pub is_live: bool,
+    /// MIME type without parameters, like `video/mp4`.
+    pub mime_type: Option<String>,
+    /// RFC 6381 codecs, like `avc1.640028` or `opus`.
+    pub codecs: Option<String>,
+    /// The video codec out of `codecs`, `None` for streams without video.
+    pub vcodec: Option<String>,
+    /// The audio codec out of `codecs`, `None` for streams without audio.
+    pub acodec: Option<String>,
+    /// Loudness relative to YouTube's normalization target, negative when the audio is quieter.
+    pub loudness_db: Option<f64>,
+    pub color_info: Option<YtColorInfo>,
+    /// Dynamic range of formats with video.
+    pub dynamic_range: Option<YtDynamicRange>,
+    /// Byte range of the initialization segment of adaptive formats.
+    pub init_range: Option<YtByteRange>,
+    /// Byte range of the segment index (`sidx` or `Cues`) of adaptive formats.
+    pub index_range: Option<YtByteRange>,
+    /// When the format was last modified, in microseconds since the Unix epoch.
+    pub last_modified: Option<u64>,
+}
+
+/// Whether a stream carries video, audio or both.
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi)
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StreamKind {
+    Muxed,
+    VideoOnly,
+    AudioOnly,
+}
+
+impl YtStream {
+    /// Classify the stream by its codecs, MIME type and dimensions.
+    /// Streams that give none of them away are assumed to be muxed, like old progressive formats.
+    pub fn kind(&self) -> StreamKind {
+        let mime_type_is = |prefix: &str| {
+            self.mime_type
+                .as_deref()
+                .is_some_and(|mime_type| mime_type.starts_with(prefix))
+        };
+
+        let has_video = self.vcodec.is_some()
+            || self.height.is_some()
+            || self.width.is_some()
+            || mime_type_is("video/");
+        let has_audio = self.acodec.is_some()
+            || self.asr.is_some()
+            || self.audio_channels.is_some()
+            || mime_type_is("audio/")
+            || matches!(&self.source, YtStreamSource::Hls(variant) if variant.audio_group.is_some());
+        // With known codecs, a missing one means that track isn't there.
+        let codecs_known = self.codecs.is_some();
+
+        match (has_video, has_audio) {
+            (true, true) => StreamKind::Muxed,
+            (true, false) if codecs_known || mime_type_is("video/") => StreamKind::VideoOnly,
+            (false, true) if codecs_known || mime_type_is("audio/") => StreamKind::AudioOnly,
+            _ => StreamKind::Muxed,
+        }
+    }
+}
+
+/// Color description of a video format, like `bt2020` primaries with `smpte2084` transfer.
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct YtColorInfo {
+    pub primaries: Option<String>,
+    pub transfer_characteristics: Option<String>,
+    pub matrix_coefficients: Option<String>,
+}
+
+/// Dynamic range of a video format, from worst to best.
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi)
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum YtDynamicRange {
+    Sdr,
+    Hlg,
+    Hdr10,
+    /// HDR10 with a 12-bit depth.
+    Hdr12,
+    DolbyVision,
+}
+
+impl YtDynamicRange {
+    pub fn is_hdr(&self) -> bool {
+        *self != Self::Sdr
+    }
 }
 
 #[cfg_attr(
@@ -293,6 +794,8 @@ pub struct YtStream {
 #[derive(Debug, Clone)]
 pub struct AudioTrackInfo {
     pub display_name: Option<String>,
+    /// BCP-47 language code of the audio track, like `en` or `pt-BR`.
+    pub language: Option<String>,
     pub is_default: bool,
 }
 
@@ -334,6 +837,12 @@ impl Deref for YtStreamList {
     }
 }
 
+impl DerefMut for YtStreamList {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
 pub trait Filterable {
     /// Filter to return video-only streams.
     ///
@@ -377,6 +886,27 @@ pub trait Filterable {
     /// }
     /// ```
     fn audio_only(&self) -> YtStreamList;
+    /// Filter to return streams that carry both video and audio.
+    ///
+    /// ```
+    /// use tydle::{Tydle, TydleOptions, Extract, VideoId, Filterable};
+    /// use anyhow::Result;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///   let ty = Tydle::new(TydleOptions { ..Default::default() })?;
+    ///   // Get the streams that play without merging.
+    ///   let muxed = ty
+    ///      .get_streams(&VideoId::new("dQw4w9WgXcQ")?)
+    ///      .await?
+    ///      .streams
+    ///      .muxed();
+    ///
+    ///    println!("Muxed streams: {:?}", muxed);
+    ///    Ok(())
+    /// }
+    /// ```
+    fn muxed(&self) -> YtStreamList;
     /// Filter to return only those streams which do not require signature deciphering.
     ///
     /// ```
@@ -468,56 +998,163 @@ pub trait Filterable {
     /// }
     /// ```
     fn only_urls(&self) -> YtStreamList;
-}
-
-impl Filterable for YtStreamList {
-    fn with_highest_bitrate(&self) -> YtStreamList {
-        let mut streams = self.0.clone();
-        streams.sort_by(|a, b| {
-            b.tbr
-                .partial_cmp(&a.tbr)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-
-        YtStreamList(streams)
-    }
-
-    fn audio_only(&self) -> YtStreamList {
-        let streams = self.0.clone();
-        YtStreamList(
-            streams
-                .iter()
-                .filter(|s| AUDIO_ONLY_FORMATS.contains(&s.quality_label.clone().as_str()))
-                .cloned()
-                .collect(),
-        )
-    }
-
-    fn video_only(&self) -> YtStreamList {
-        let streams = self.0.clone();
-        YtStreamList(
-            streams
-                .iter()
-                .filter(|s| VIDEO_ONLY_FORMATS.contains(&s.quality_label.clone().as_str()))
-                .cloned()
-                .collect(),
-        )
-    }
-
-    fn with_lowest_bitrate(&self) -> YtStreamList {
-        let mut streams = self.0.clone();
-        streams.sort_by(|a, b| {
-            a.tbr
-                .partial_cmp(&b.tbr)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-
-        YtStreamList(streams)
-    }
-
-    fn only_urls(&self) -> YtStreamList {
-        YtStreamList(
-            self.0
+    /// Filter streams to return only the variants parsed from HLS master playlists.
+    ///
+    /// ```
+    /// use tydle::{Tydle, TydleOptions, Extract, VideoId, Filterable};
+    /// use anyhow::Result;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///   let ty = Tydle::new(TydleOptions { ..Default::default() })?;
+    ///   // Get pre-merged HLS streams.
+    ///   let hls_streams = ty
+    ///      .get_streams(&VideoId::new("dQw4w9WgXcQ")?)
+    ///      .await?
+    ///      .streams
+    ///      .only_hls();
+    ///
+    ///   for stream in hls_streams {
+    ///     println!("HLS: {:?}", stream.source);
+    ///   }
+    ///
+    ///   Ok(())
+    /// }
+    /// ```
+    fn only_hls(&self) -> YtStreamList;
+    /// Filter streams to return only the representations parsed from DASH manifests.
+    ///
+    /// ```
+    /// use tydle::{Tydle, TydleOptions, Extract, VideoId, Filterable};
+    /// use anyhow::Result;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///   let ty = Tydle::new(TydleOptions { ..Default::default() })?;
+    ///   // DASH manifests are returned for live streams.
+    ///   let dash_streams = ty
+    ///      .get_streams(&VideoId::new("jfKfPfyJRdk")?)
+    ///      .await?
+    ///      .streams
+    ///      .only_dash();
+    ///
+    ///   for stream in dash_streams {
+    ///     println!("DASH: {:?}", stream.source);
+    ///   }
+    ///
+    ///   Ok(())
+    /// }
+    /// ```
+    fn only_dash(&self) -> YtStreamList;
+    /// Pick a stream, or a video and an audio stream to merge, with a yt-dlp style format selector.
+    /// See `format_selector` for the supported syntax.
+    ///
+    /// ```
+    /// use tydle::{Tydle, TydleOptions, Extract, VideoId, Filterable, YtFormatSelection};
+    /// use anyhow::Result;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///   let ty = Tydle::new(TydleOptions { ..Default::default() })?;
+    ///   let selection = ty
+    ///      .get_streams(&VideoId::new("dQw4w9WgXcQ")?)
+    ///      .await?
+    ///      .streams
+    ///      .select_format("bv*[height<=1080][ext=mp4]+ba[ext=m4a]/b")?;
+    ///
+    ///   match selection {
+    ///     Some(YtFormatSelection::Merged { video, audio }) => {
+    ///       println!("Merge {:?} with {:?}", video.itag, audio.itag)
+    ///     }
+    ///     Some(YtFormatSelection::Single(stream)) => println!("Single: {:?}", stream.itag),
+    ///     None => println!("No stream matches."),
+    ///   }
+    ///
+    ///   Ok(())
+    /// }
+    /// ```
+    fn select_format(&self, selector: &str) -> Result<Option<YtFormatSelection>>;
+    /// Sort streams from best to worst by a list of fields, like yt-dlp's `-S`.
+    /// See `format_sort` for the supported fields.
+    ///
+    /// ```
+    /// use tydle::{Tydle, TydleOptions, Extract, VideoId, Filterable};
+    /// use tydle::format_sort::FormatSort;
+    /// use anyhow::Result;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///   let ty = Tydle::new(TydleOptions { ..Default::default() })?;
+    ///   // Prefer H.264 at 720p or less, then the smallest file.
+    ///   let sorted = ty
+    ///      .get_streams(&VideoId::new("dQw4w9WgXcQ")?)
+    ///      .await?
+    ///      .streams
+    ///      .sort_formats(&FormatSort::parse("res:720,vcodec:h264,+size")?);
+    ///
+    ///   println!("Best stream: {:?}", sorted.first());
+    ///   Ok(())
+    /// }
+    /// ```
+    fn sort_formats(&self, sort: &FormatSort) -> YtStreamList;
+}
+
+impl Filterable for YtStreamList {
+    fn with_highest_bitrate(&self) -> YtStreamList {
+        let mut streams = self.0.clone();
+        streams.sort_by(|a, b| {
+            b.tbr
+                .partial_cmp(&a.tbr)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        YtStreamList(streams)
+    }
+
+    fn audio_only(&self) -> YtStreamList {
+        YtStreamList(
+            self.0
+                .iter()
+                .filter(|s| s.kind() == StreamKind::AudioOnly)
+                .cloned()
+                .collect(),
+        )
+    }
+
+    fn video_only(&self) -> YtStreamList {
+        YtStreamList(
+            self.0
+                .iter()
+                .filter(|s| s.kind() == StreamKind::VideoOnly)
+                .cloned()
+                .collect(),
+        )
+    }
+
+    fn muxed(&self) -> YtStreamList {
+        YtStreamList(
+            self.0
+                .iter()
+                .filter(|s| s.kind() == StreamKind::Muxed)
+                .cloned()
+                .collect(),
+        )
+    }
+
+    fn with_lowest_bitrate(&self) -> YtStreamList {
+        let mut streams = self.0.clone();
+        streams.sort_by(|a, b| {
+            a.tbr
+                .partial_cmp(&b.tbr)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        YtStreamList(streams)
+    }
+
+    fn only_urls(&self) -> YtStreamList {
+        YtStreamList(
+            self.0
                 .iter()
                 .filter(|s| matches!(s.source, YtStreamSource::URL(_)))
                 .cloned()
@@ -534,6 +1171,53 @@ impl Filterable for YtStreamList {
                 .collect(),
         )
     }
+
+    fn only_hls(&self) -> YtStreamList {
+        YtStreamList(
+            self.0
+                .iter()
+                .filter(|s| matches!(s.source, YtStreamSource::Hls(_)))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    fn only_dash(&self) -> YtStreamList {
+        YtStreamList(
+            self.0
+                .iter()
+                .filter(|s| matches!(s.source, YtStreamSource::Dash(_)))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    fn select_format(&self, selector: &str) -> Result<Option<YtFormatSelection>> {
+        Ok(FormatSelector::parse(selector)?.select(&self.0))
+    }
+
+    fn sort_formats(&self, sort: &FormatSort) -> YtStreamList {
+        let mut streams = self.0.clone();
+        streams.sort_by(|a, b| sort.compare(b, a));
+
+        YtStreamList(streams)
+    }
+}
+
+/// The result of a format selector, either one stream or a video and an audio stream to merge.
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone)]
+pub enum YtFormatSelection {
+    Single(Box<YtStream>),
+    Merged {
+        video: Box<YtStream>,
+        audio: Box<YtStream>,
+    },
 }
 
 #[cfg_attr(
@@ -546,6 +1230,13 @@ impl Filterable for YtStreamList {
 pub struct YtStreamResponse {
     pub player_url: String,
     pub streams: YtStreamList,
+    pub live_status: YtLiveStatus,
+    /// Master playlist of the HLS formats, returned for live streams and by some clients for VODs.
+    pub hls_manifest_url: Option<String>,
+    /// DASH manifest, returned for live streams and post-live DVR.
+    pub dash_manifest_url: Option<String>,
+    /// How far back a live stream can be rewound in seconds, `None` if DVR is disabled.
+    pub dvr_window: Option<u64>,
 }
 
 impl YtStreamResponse {
@@ -553,8 +1244,34 @@ impl YtStreamResponse {
         Self {
             player_url,
             streams: YtStreamList(streams),
+            live_status: YtLiveStatus::default(),
+            hls_manifest_url: None,
+            dash_manifest_url: None,
+            dvr_window: None,
         }
     }
+
+    /// Generate a static MPD from the adaptive formats, for players like dash.js or Shaka.
+    ///
+    /// Only formats with a plain URL and known `initRange`/`indexRange` can be described,
+    /// so streams that still need their signature deciphered are left out.
+    ///
+    /// ```
+    /// use tydle::{Tydle, TydleOptions, Extract, VideoId};
+    /// use anyhow::Result;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///   let ty = Tydle::new(TydleOptions { ..Default::default() })?;
+    ///   let stream_response = ty.get_streams(&VideoId::new("dQw4w9WgXcQ")?).await?;
+    ///
+    ///   std::fs::write("dQw4w9WgXcQ.mpd", stream_response.to_dash_manifest()?)?;
+    ///   Ok(())
+    /// }
+    /// ```
+    pub fn to_dash_manifest(&self) -> Result<String> {
+        generate_dash_manifest(self)
+    }
 }
 
 #[cfg_attr(
@@ -567,6 +1284,9 @@ impl YtStreamResponse {
 pub struct YtManifest {
     pub extracted_manifest: Vec<HashMap<String, Value>>,
     pub player_url: String,
+    /// The watch page's `next` response, which holds chapters and other data outside the player responses.
+    #[cfg_attr(target_arch = "wasm32", serde(default))]
+    pub initial_data: HashMap<String, Value>,
 }
 
 impl YtManifest {
@@ -574,8 +1294,15 @@ impl YtManifest {
         Self {
             extracted_manifest,
             player_url,
+            initial_data: HashMap::new(),
         }
     }
+
+    /// Attaches the watch page's `next` response, which chapters are read from.
+    pub fn with_initial_data(mut self, initial_data: HashMap<String, Value>) -> Self {
+        self.initial_data = initial_data;
+        self
+    }
 }
 
 #[cfg_attr(
@@ -584,7 +1311,26 @@ impl YtManifest {
     tsify(into_wasm_abi, from_wasm_abi),
     serde(rename_all = "camelCase")
 )]
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum YtLiveStatus {
+    #[default]
+    NotLive,
+    /// Scheduled but not started yet.
+    Upcoming,
+    Live,
+    /// Ended recently and still being processed, only the DVR formats are available.
+    PostLive,
+    /// Ended and processed into a regular video.
+    WasLive,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum YtMediaType {
     LiveStream,
     Short,
@@ -611,7 +1357,7 @@ pub enum YtAgeLimit {
     tsify(into_wasm_abi, from_wasm_abi),
     serde(rename_all = "camelCase")
 )]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct YtThumbnail {
     pub url: String,
     pub height: Option<u64>,
@@ -636,6 +1382,589 @@ pub struct YtVideoInfo {
     pub thumbnails: Vec<YtThumbnail>,
     pub media_type: YtMediaType,
     pub age_limit: YtAgeLimit,
+    /// Chapters in playback order, empty if the video has none.
+    pub chapters: Vec<YtChapter>,
+    pub live_status: YtLiveStatus,
+    /// Unix timestamp the stream is scheduled to start at, for upcoming and past live streams.
+    pub scheduled_start_time: Option<u64>,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone)]
+pub struct YtChapter {
+    pub title: String,
+    /// Start of the chapter in seconds.
+    pub start_time: f64,
+    /// End of the chapter in seconds, which is the start of the next chapter or the end of the video.
+    pub end_time: f64,
+    pub thumbnails: Vec<YtThumbnail>,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase"),
+    serde(default)
+)]
+#[derive(Debug, Default, Clone)]
+pub struct YtPlaylistOptions {
+    /// 1-based index of the first entry to return.
+    pub start: Option<u64>,
+    /// 1-based index of the last entry to return, inclusive.
+    pub end: Option<u64>,
+    /// Maximum number of entries to return, counted from `start`.
+    pub limit: Option<u64>,
+}
+
+impl YtPlaylistOptions {
+    /// Whether the entry at the 1-based `index` falls inside the requested range.
+    pub fn contains(&self, index: u64) -> bool {
+        index >= self.start.unwrap_or(1) && self.last_index().is_none_or(|last| index <= last)
+    }
+
+    /// 1-based index of the last entry to return, if the range is bounded.
+    pub fn last_index(&self) -> Option<u64> {
+        let start = self.start.unwrap_or(1);
+        let from_limit = self
+            .limit
+            .map(|l| start.saturating_add(l).saturating_sub(1));
+
+        match (self.end, from_limit) {
+            (Some(end), Some(limit_end)) => Some(end.min(limit_end)),
+            (end, limit_end) => end.or(limit_end),
+        }
+    }
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone)]
+pub struct YtPlaylistEntry {
+    #[cfg_attr(target_arch = "wasm32", tsify(type = "string"))]
+    pub video_id: VideoId,
+    pub title: String,
+    /// Duration of the video in seconds, missing for upcoming and live videos.
+    pub duration: Option<u64>,
+    pub channel: Option<YtChannel>,
+    /// 1-based position of the video in the playlist.
+    pub index: u64,
+    pub thumbnails: Vec<YtThumbnail>,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug)]
+pub struct YtPlaylist {
+    #[cfg_attr(target_arch = "wasm32", tsify(type = "string"))]
+    pub id: PlaylistId,
+    pub title: String,
+    pub description: Option<String>,
+    pub channel: Option<YtChannel>,
+    /// Number of videos YouTube reports for the whole playlist, regardless of the requested range.
+    pub video_count: Option<u64>,
+    pub view_count: Option<u64>,
+    pub thumbnails: Vec<YtThumbnail>,
+    pub entries: Vec<YtPlaylistEntry>,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase"),
+    serde(default)
+)]
+#[derive(Debug, Default, Clone)]
+pub struct YtChannelTabOptions {
+    /// Maximum number of entries to return, continuation pages past it are never requested.
+    pub limit: Option<u64>,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone)]
+pub struct YtChannelInfo {
+    pub channel: YtChannel,
+    /// The channel's `@handle`, including the `@`.
+    pub handle: Option<String>,
+    pub description: Option<String>,
+    /// Subscriber count as YouTube displays it, like `"1.2M subscribers"`.
+    pub subscriber_count_text: Option<String>,
+    pub avatar: Vec<YtThumbnail>,
+    pub banner: Vec<YtThumbnail>,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone)]
+pub struct YtChannelEntry {
+    #[cfg_attr(target_arch = "wasm32", tsify(type = "string"))]
+    pub video_id: VideoId,
+    pub title: String,
+    /// Duration of the video in seconds, missing for shorts and live streams.
+    pub duration: Option<u64>,
+    pub view_count: Option<u64>,
+    /// Relative publish time as YouTube displays it, like `"3 days ago"`.
+    pub published_time_text: Option<String>,
+    pub media_type: YtMediaType,
+    pub thumbnails: Vec<YtThumbnail>,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug)]
+pub struct YtChannelTabResponse {
+    pub info: YtChannelInfo,
+    pub tab: YtChannelTab,
+    pub entries: Vec<YtChannelEntry>,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum YtCommentSort {
+    #[default]
+    Top,
+    Newest,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase"),
+    serde(default)
+)]
+#[derive(Debug, Default, Clone)]
+pub struct YtCommentOptions {
+    /// Only applies when fetching a video's top-level comments, replies are always in YouTube's order.
+    pub sort: YtCommentSort,
+    /// Stop fetching once this many comments are collected. The last page is kept whole, so up to
+    /// a page more may be returned and the continuation resumes without skipping any.
+    pub max_comments: Option<u64>,
+}
+
+/// A piece of comment text, with the link it points to if it's a timestamp, hashtag, mention or URL.
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone)]
+pub struct YtTextRun {
+    pub text: String,
+    pub url: Option<String>,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone)]
+pub struct YtComment {
+    pub id: String,
+    /// ID of the comment this one replies to, `None` for top-level comments.
+    pub parent_id: Option<String>,
+    pub text: String,
+    pub runs: Vec<YtTextRun>,
+    pub author: Option<YtChannel>,
+    pub author_thumbnails: Vec<YtThumbnail>,
+    /// Whether the comment was written by the channel that uploaded the video.
+    pub is_author_uploader: bool,
+    pub like_count: Option<u64>,
+    pub reply_count: u64,
+    pub is_pinned: bool,
+    /// Whether the uploader gave the comment a heart.
+    pub is_hearted: bool,
+    /// Relative publish time as YouTube displays it, like `"2 years ago (edited)"`.
+    pub published_time_text: Option<String>,
+    /// Pass the comment to `Tydle::get_comment_replies` to fetch its replies through this token.
+    pub replies_continuation: Option<String>,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Default)]
+pub struct YtCommentsResponse {
+    pub comments: Vec<YtComment>,
+    /// The comment count YouTube shows above the comments, only present on a video's first page.
+    pub total_count: Option<u64>,
+    /// Pass to `Tydle::get_comments_continuation` to keep fetching after the last page.
+    pub continuation: Option<String>,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum YtSearchSort {
+    #[default]
+    Relevance,
+    Rating,
+    UploadDate,
+    ViewCount,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YtSearchUploadDate {
+    LastHour,
+    Today,
+    ThisWeek,
+    ThisMonth,
+    ThisYear,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YtSearchType {
+    Video,
+    Short,
+    Channel,
+    Playlist,
+    Movie,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YtSearchDuration {
+    /// Under 4 minutes.
+    Short,
+    /// Between 4 and 20 minutes.
+    Medium,
+    /// Over 20 minutes.
+    Long,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YtSearchFeature {
+    Live,
+    FourK,
+    Hd,
+    Subtitles,
+    CreativeCommons,
+    ThreeSixty,
+    Vr180,
+    ThreeD,
+    Hdr,
+    Location,
+    Purchased,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase"),
+    serde(default)
+)]
+#[derive(Debug, Default, Clone)]
+pub struct YtSearchFilters {
+    pub sort: YtSearchSort,
+    pub upload_date: Option<YtSearchUploadDate>,
+    pub result_type: Option<YtSearchType>,
+    pub duration: Option<YtSearchDuration>,
+    pub features: Vec<YtSearchFeature>,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone)]
+pub struct YtSearchVideo {
+    #[cfg_attr(target_arch = "wasm32", tsify(type = "string"))]
+    pub video_id: VideoId,
+    pub title: String,
+    /// Duration of the video in seconds, missing for shorts and live streams.
+    pub duration: Option<u64>,
+    pub view_count: Option<u64>,
+    /// Relative publish time as YouTube displays it, like `"3 days ago"`.
+    pub published_time_text: Option<String>,
+    pub channel: Option<YtChannel>,
+    pub description_snippet: Option<String>,
+    pub is_live: bool,
+    pub thumbnails: Vec<YtThumbnail>,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone)]
+pub struct YtSearchChannel {
+    pub channel: YtChannel,
+    /// The channel's `@handle`, including the `@`.
+    pub handle: Option<String>,
+    /// Subscriber count as YouTube displays it, like `"1.2M subscribers"`.
+    pub subscriber_count_text: Option<String>,
+    pub description_snippet: Option<String>,
+    pub thumbnails: Vec<YtThumbnail>,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone)]
+pub struct YtSearchPlaylist {
+    #[cfg_attr(target_arch = "wasm32", tsify(type = "string"))]
+    pub playlist_id: PlaylistId,
+    pub title: String,
+    pub video_count: Option<u64>,
+    pub channel: Option<YtChannel>,
+    pub thumbnails: Vec<YtThumbnail>,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone)]
+pub enum YtSearchResult {
+    Video(YtSearchVideo),
+    Short(YtSearchVideo),
+    Channel(YtSearchChannel),
+    Playlist(YtSearchPlaylist),
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug)]
+pub struct YtSearchResponse {
+    pub results: Vec<YtSearchResult>,
+    /// YouTube's rough estimate of the total number of results, only present on the first page.
+    pub estimated_results: Option<u64>,
+    /// Pass to `Tydle::search_continuation` to fetch the next page.
+    pub continuation: Option<String>,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YtCaptionKind {
+    /// Uploaded by the channel.
+    Manual,
+    /// Generated by YouTube's speech recognition.
+    AutoGenerated,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone)]
+pub struct YtCaptionLanguage {
+    pub language_code: String,
+    pub name: String,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone)]
+pub struct YtCaptionTrack {
+    pub language_code: String,
+    pub name: String,
+    pub kind: YtCaptionKind,
+    /// YouTube's identifier for the track, like `.en` or `a.en` for auto-generated captions.
+    pub vss_id: String,
+    /// Whether YouTube can machine-translate this track into `YtSubtitles::translation_languages`.
+    pub is_translatable: bool,
+    pub base_url: String,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Default, Clone)]
+pub struct YtSubtitles {
+    pub tracks: Vec<YtCaptionTrack>,
+    /// Languages every translatable track can be translated into.
+    pub translation_languages: Vec<YtCaptionLanguage>,
+}
+
+impl YtSubtitles {
+    pub fn manual(&self) -> impl Iterator<Item = &YtCaptionTrack> {
+        self.tracks
+            .iter()
+            .filter(|t| t.kind == YtCaptionKind::Manual)
+    }
+
+    pub fn auto_generated(&self) -> impl Iterator<Item = &YtCaptionTrack> {
+        self.tracks
+            .iter()
+            .filter(|t| t.kind == YtCaptionKind::AutoGenerated)
+    }
+
+    /// The first track in `language_code`, preferring manual captions over auto-generated ones.
+    pub fn find(&self, language_code: &str) -> Option<&YtCaptionTrack> {
+        self.manual()
+            .chain(self.auto_generated())
+            .find(|t| t.language_code == language_code)
+    }
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum YtSubtitleFormat {
+    /// YouTube's own JSON timed text, returned as downloaded.
+    Json3,
+    /// YouTube's own XML timed text, returned as downloaded.
+    Srv3,
+    #[default]
+    Srt,
+    Vtt,
+    Ttml,
+    /// Cue text only, one cue per line.
+    Txt,
+}
+
+impl YtSubtitleFormat {
+    pub fn ext(&self) -> Ext {
+        match self {
+            Self::Json3 => Ext::Json,
+            Self::Srv3 => Ext::Xml,
+            Self::Srt => Ext::Srt,
+            Self::Vtt => Ext::Vtt,
+            Self::Ttml => Ext::Ttml,
+            Self::Txt => Ext::Txt,
+        }
+    }
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase"),
+    serde(default)
+)]
+#[derive(Debug, Default, Clone)]
+pub struct YtSubtitleOptions {
+    pub format: YtSubtitleFormat,
+    /// Language code to machine-translate the track into, see `YtSubtitles::translation_languages`.
+    pub translate_to: Option<String>,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct YtSubtitleCue {
+    /// Start of the cue in seconds.
+    pub start: f64,
+    /// End of the cue in seconds.
+    pub end: f64,
+    pub text: String,
+}
+
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Clone)]
+pub struct YtSubtitle {
+    pub language_code: String,
+    pub format: YtSubtitleFormat,
+    pub cues: Vec<YtSubtitleCue>,
+    /// The subtitle file in `format`.
+    pub content: String,
 }
 
 #[cfg_attr(
@@ -644,7 +1973,7 @@ pub struct YtVideoInfo {
     tsify(into_wasm_abi, from_wasm_abi),
     serde(rename_all = "lowercase")
 )]
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum Ext {
     #[default]
     Unknown,
@@ -694,8 +2023,53 @@ pub enum Ext {
     Dfxp,
     Ttml,
     Sami,
+    Srt,
+    Vtt,
+    Txt,
     Gz,
     Json,
     Xml,
     Zip,
 }
+
+/// Where the ejs solver bundles used for signature deciphering are loaded from.
+#[cfg_attr(
+    target_arch = "wasm32",
+    derive(serde::Serialize, serde::Deserialize, tsify::Tsify),
+    tsify(into_wasm_abi, from_wasm_abi),
+    serde(rename_all = "camelCase")
+)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum YtSolverSource {
+    /// The bundles embedded with the `embedded-solver` feature,
+    /// otherwise the pinned ejs release downloaded from GitHub.
+    #[default]
+    Pinned,
+    /// A base URL serving `yt.solver.lib.min.js` and `yt.solver.core.min.js`, like a mirror of the ejs release.
+    /// The bundles have to match the pinned digests.
+    Url(String),
+    /// A local directory holding `yt.solver.lib.min.js` and `yt.solver.core.min.js`, not available in WebAssembly.
+    /// Local bundles are trusted as they are and can be of any ejs version.
+    Directory(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds_playlist_ranges() {
+        let options = |start, end, limit| YtPlaylistOptions { start, end, limit };
+
+        assert_eq!(options(None, None, None).last_index(), None);
+        assert_eq!(options(Some(5), None, Some(10)).last_index(), Some(14));
+        assert_eq!(options(Some(5), Some(8), Some(10)).last_index(), Some(8));
+        assert_eq!(
+            options(Some(2), None, Some(u64::MAX)).last_index(),
+            Some(u64::MAX - 1)
+        );
+        assert!(options(Some(2), None, Some(u64::MAX)).contains(1_000_000));
+        assert!(!options(Some(2), None, Some(3)).contains(1));
+        assert!(!options(Some(2), None, Some(3)).contains(5));
+    }
+}